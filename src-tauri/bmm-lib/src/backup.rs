@@ -0,0 +1,949 @@
+use crate::database::{Database, ModBackup};
+use crate::errors::AppError;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_MAX_BACKUPS_PER_MOD: usize = 3;
+pub const DEFAULT_BACKUP_QUOTA_BYTES: u64 = 2 * 1024 * 1024 * 1024;
+
+// Snapshots run without the database lock, so garbage collection must not sweep
+// objects a snapshot has stored (or found already stored) before its manifest exists
+static STORE_LOCK: Mutex<()> = Mutex::new(());
+
+/// Where backups were kept before the store existed; see [`BackupStore::import_legacy`].
+pub fn legacy_backup_dir() -> PathBuf {
+    std::env::temp_dir().join("balatro_mod_manager_backups")
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupReason {
+    Manual,
+    Update,
+    Uninstall,
+}
+
+impl BackupReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BackupReason::Manual => "manual",
+            BackupReason::Update => "update",
+            BackupReason::Uninstall => "uninstall",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+    pub max_per_mod: usize,
+    pub quota_bytes: u64,
+}
+
+impl Default for RetentionPolicy {
+    fn default() -> Self {
+        Self {
+            max_per_mod: DEFAULT_MAX_BACKUPS_PER_MOD,
+            quota_bytes: DEFAULT_BACKUP_QUOTA_BYTES,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+enum EntryKind {
+    Dir,
+    File,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct ManifestEntry {
+    // '/'-separated path relative to the backed up root; empty for a single-file backup
+    path: String,
+    kind: EntryKind,
+    #[serde(default)]
+    hash: Option<String>,
+    #[serde(default)]
+    size: u64,
+    #[serde(default)]
+    mode: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct BackupManifest {
+    version: u32,
+    root_is_dir: bool,
+    entries: Vec<ManifestEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StoreUsage {
+    /// Sum of the uncompressed sizes of every backup.
+    pub logical_bytes: u64,
+    /// Bytes the store actually occupies on disk.
+    pub disk_bytes: u64,
+    pub object_count: u64,
+}
+
+/// A mod copied into the store but not yet indexed in the database.
+#[derive(Debug)]
+pub struct Snapshot {
+    manifest_path: PathBuf,
+    original_path: PathBuf,
+    size_bytes: u64,
+    created_at: Duration,
+}
+
+/// Mod backups kept under the config directory and indexed in the database,
+/// so they survive temp-dir cleanups and can be listed and restored by id.
+///
+/// File contents are stored once as gzip-compressed, SHA-256 addressed objects
+/// (`objects/ab/abcdef….gz`) and every backup is a small JSON manifest pointing
+/// at them, so repeated backups of a large texture pack only cost the files
+/// that actually changed.
+pub struct BackupStore {
+    root: PathBuf,
+}
+
+impl BackupStore {
+    pub fn new() -> Result<Self, AppError> {
+        let root = crate::paths::manager_dir()?.join("backups");
+        Ok(Self::with_root(root))
+    }
+
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Back up a mod folder (or single file) and record it in the database.
+    pub fn create(
+        &self,
+        db: &Database,
+        path: &Path,
+        mod_name: &str,
+        version: Option<&str>,
+        reason: BackupReason,
+    ) -> Result<ModBackup, AppError> {
+        let snapshot = self.snapshot(path)?;
+        self.record(db, snapshot, mod_name, version, reason)
+    }
+
+    /// Copy a mod into the store without touching the database, so the slow part
+    /// of a backup can run while the database is free. Pass the result to [`Self::record`].
+    pub fn snapshot(&self, path: &Path) -> Result<Snapshot, AppError> {
+        let created_at = SystemTime::now().duration_since(UNIX_EPOCH)?;
+        self.write_snapshot(path, path, created_at)
+    }
+
+    /// Index a snapshot in the database, dropping its manifest if that fails.
+    pub fn record(
+        &self,
+        db: &Database,
+        snapshot: Snapshot,
+        mod_name: &str,
+        version: Option<&str>,
+        reason: BackupReason,
+    ) -> Result<ModBackup, AppError> {
+        let Snapshot {
+            manifest_path,
+            original_path,
+            size_bytes,
+            created_at,
+        } = snapshot;
+        let id = match db.add_mod_backup(
+            mod_name,
+            version,
+            &original_path.to_string_lossy(),
+            &manifest_path.to_string_lossy(),
+            size_bytes,
+            reason.as_str(),
+            created_at.as_secs(),
+        ) {
+            Ok(id) => id,
+            Err(e) => {
+                let _ = remove_path(&manifest_path);
+                return Err(e);
+            }
+        };
+        log::info!(
+            "Backed up '{mod_name}' from {} ({size_bytes} bytes, id {id})",
+            original_path.display()
+        );
+
+        db.get_mod_backup(id)?
+            .ok_or_else(|| AppError::InvalidState(format!("Backup {id} missing after insert")))
+    }
+
+    /// Move backups made before the store existed (plain copies under the temp
+    /// dir, one folder per backup with a `metadata.json`) into the store.
+    /// Imported folders are deleted. Returns how many backups were imported.
+    pub fn import_legacy(&self, db: &Database, legacy_dir: &Path) -> Result<usize, AppError> {
+        let entries = fs::read_dir(legacy_dir).map_err(|e| AppError::FileRead {
+            path: legacy_dir.to_path_buf(),
+            source: e.to_string(),
+        })?;
+        let mut imported = 0usize;
+        for entry in entries.flatten() {
+            let dir = entry.path();
+            match self.import_legacy_backup(db, &dir) {
+                Ok(()) => {
+                    remove_path(&dir)?;
+                    imported += 1;
+                }
+                Err(e) => log::warn!("Skipping legacy backup {}: {e}", dir.display()),
+            }
+        }
+        if fs::read_dir(legacy_dir).is_ok_and(|mut rd| rd.next().is_none()) {
+            remove_path(legacy_dir)?;
+        }
+        if imported > 0 {
+            log::info!("Imported {imported} legacy backup(s) into the backup store");
+        }
+        Ok(imported)
+    }
+
+    fn import_legacy_backup(&self, db: &Database, dir: &Path) -> Result<(), AppError> {
+        let metadata_path = dir.join("metadata.json");
+        let metadata: serde_json::Value =
+            serde_json::from_slice(&fs::read(&metadata_path).map_err(|e| AppError::FileRead {
+                path: metadata_path.clone(),
+                source: e.to_string(),
+            })?)
+            .map_err(|e| AppError::JsonParse {
+                path: metadata_path.clone(),
+                source: e.to_string(),
+            })?;
+        let original_path = metadata
+            .get("original_path")
+            .and_then(|v| v.as_str())
+            .map(PathBuf::from)
+            .ok_or_else(|| AppError::InvalidState("No original path recorded".to_string()))?;
+        let backup_time = metadata
+            .get("backup_time")
+            .and_then(|v| v.as_u64())
+            .unwrap_or_default();
+        let file_name = original_path
+            .file_name()
+            .ok_or_else(|| AppError::invalid_path(&original_path, "Path has no file name"))?;
+
+        // The copy sits next to the metadata under the original's name
+        let snapshot = self.write_snapshot(
+            &dir.join(file_name),
+            &original_path,
+            Duration::from_secs(backup_time),
+        )?;
+        let mod_name = file_name.to_string_lossy();
+        self.record(db, snapshot, &mod_name, None, BackupReason::Update)
+            .map(|_| ())
+    }
+
+    fn write_snapshot(
+        &self,
+        source: &Path,
+        original_path: &Path,
+        created_at: Duration,
+    ) -> Result<Snapshot, AppError> {
+        if !source.exists() {
+            return Err(AppError::PathValidation {
+                path: source.to_path_buf(),
+                reason: "Path doesn't exist".into(),
+            });
+        }
+        let _guard = STORE_LOCK.lock()?;
+        let manifests_dir = self.root.join("manifests");
+        fs::create_dir_all(&manifests_dir).map_err(|e| AppError::DirCreate {
+            path: manifests_dir.clone(),
+            source: e.to_string(),
+        })?;
+        let mut manifest_path =
+            manifests_dir.join(format!("backup_{}.json", created_at.as_millis()));
+        let mut n = 1;
+        while manifest_path.exists() {
+            manifest_path =
+                manifests_dir.join(format!("backup_{}_{n}.json", created_at.as_millis()));
+            n += 1;
+        }
+
+        let mut entries = Vec::new();
+        if source.is_dir() {
+            self.store_dir(source, "", &mut entries)?;
+        } else {
+            entries.push(self.store_file(source, String::new())?);
+        }
+        let size_bytes = entries.iter().map(|e| e.size).sum();
+
+        let manifest = BackupManifest {
+            version: 1,
+            root_is_dir: source.is_dir(),
+            entries,
+        };
+        fs::write(&manifest_path, serde_json::to_vec(&manifest)?).map_err(|e| {
+            AppError::FileWrite {
+                path: manifest_path.clone(),
+                source: e.to_string(),
+            }
+        })?;
+
+        Ok(Snapshot {
+            manifest_path,
+            original_path: original_path.to_path_buf(),
+            size_bytes,
+            created_at,
+        })
+    }
+
+    pub fn list(
+        &self,
+        db: &Database,
+        original_path: Option<&Path>,
+    ) -> Result<Vec<ModBackup>, AppError> {
+        let original = original_path.map(|p| p.to_string_lossy().to_string());
+        db.get_mod_backups(original.as_deref())
+    }
+
+    /// Restore a backup over its original location. Returns the restored path.
+    pub fn restore(&self, db: &Database, id: i64) -> Result<PathBuf, AppError> {
+        let backup = db
+            .get_mod_backup(id)?
+            .ok_or_else(|| AppError::InvalidState(format!("Backup {id} not found")))?;
+        let target = PathBuf::from(&backup.original_path);
+        let backup_path = PathBuf::from(&backup.backup_path);
+        if !backup_path.exists() {
+            return Err(AppError::FileNotFound {
+                path: backup_path,
+                source: format!("Backup {id} is missing its data"),
+            });
+        }
+
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| AppError::DirCreate {
+                path: parent.to_path_buf(),
+                source: e.to_string(),
+            })?;
+        }
+
+        let manifest = read_manifest(&backup_path)?;
+        // Rebuild the mod next to its live copy and only swap it in once every
+        // object has been verified, so a damaged store never leaves a half-restored mod
+        let staging = sibling_path(&target, "restore")?;
+        remove_path(&staging)?;
+        if let Err(e) = self.restore_into(&manifest, &staging) {
+            let _ = remove_path(&staging);
+            return Err(e);
+        }
+        swap_into_place(&staging, &target)?;
+
+        log::info!("Restored backup {id} to {}", target.display());
+        Ok(target)
+    }
+
+    /// Restore the newest backup taken of `original_path`.
+    pub fn restore_latest(&self, db: &Database, original_path: &Path) -> Result<PathBuf, AppError> {
+        let latest = self
+            .list(db, Some(original_path))?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::InvalidState("No backup found for this path".to_string()))?;
+        self.restore(db, latest.id)
+    }
+
+    pub fn remove(&self, db: &Database, id: i64) -> Result<(), AppError> {
+        if let Some(backup) = db.get_mod_backup(id)? {
+            let dir = PathBuf::from(&backup.backup_path);
+            if dir.starts_with(&self.root) {
+                remove_path(&dir)?;
+                db.remove_mod_backup(id)?;
+                return self.collect_garbage(db).map(|_| ());
+            } else {
+                log::warn!(
+                    "Not deleting backup data outside the backup store: {}",
+                    dir.display()
+                );
+            }
+        }
+        db.remove_mod_backup(id)
+    }
+
+    pub fn remove_for_path(&self, db: &Database, original_path: &Path) -> Result<usize, AppError> {
+        let backups = self.list(db, Some(original_path))?;
+        for backup in &backups {
+            self.remove(db, backup.id)?;
+        }
+        Ok(backups.len())
+    }
+
+    /// Enforce retention: keep at most `max_per_mod` backups per original path,
+    /// then drop the oldest backups until the store fits in `quota_bytes`.
+    /// Returns how many backups were removed.
+    pub fn cleanup(&self, db: &Database, policy: RetentionPolicy) -> Result<usize, AppError> {
+        let mut removed = 0usize;
+        let backups = self.list(db, None)?;
+
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        let mut kept = Vec::new();
+        for backup in &backups {
+            let count = seen.entry(backup.original_path.as_str()).or_insert(0);
+            *count += 1;
+            if *count > policy.max_per_mod.max(1) {
+                self.remove(db, backup.id)?;
+                removed += 1;
+            } else {
+                kept.push(backup);
+            }
+        }
+
+        // Shared objects make per-backup sizes meaningless here, so measure the
+        // real footprint after every eviction. `kept` is newest first.
+        while self.disk_usage() > policy.quota_bytes {
+            let Some(oldest) = kept.pop() else { break };
+            self.remove(db, oldest.id)?;
+            removed += 1;
+        }
+
+        if removed > 0 {
+            log::info!("Backup cleanup removed {removed} backup(s)");
+        }
+        Ok(removed)
+    }
+
+    /// Bytes currently used by backup data on disk.
+    pub fn disk_usage(&self) -> u64 {
+        dir_size(&self.root)
+    }
+
+    pub fn usage(&self, db: &Database) -> Result<StoreUsage, AppError> {
+        let logical_bytes = self.list(db, None)?.iter().map(|b| b.size_bytes).sum();
+        let object_count = walk_files(&self.root.join("objects")).len() as u64;
+        Ok(StoreUsage {
+            logical_bytes,
+            disk_bytes: self.disk_usage(),
+            object_count,
+        })
+    }
+
+    /// Delete objects no longer referenced by any manifest. Returns how many were removed.
+    ///
+    /// Every manifest in the store counts, recorded or not: one written by another
+    /// process may not be in this database yet.
+    pub fn collect_garbage(&self, db: &Database) -> Result<usize, AppError> {
+        let _guard = STORE_LOCK.lock()?;
+        let mut manifests: HashSet<PathBuf> = walk_files(&self.root.join("manifests"))
+            .into_iter()
+            .filter(|p| p.extension().is_some_and(|e| e == "json"))
+            .collect();
+        manifests.extend(
+            self.list(db, None)?
+                .into_iter()
+                .map(|b| PathBuf::from(b.backup_path))
+                .filter(|p| p.is_file()),
+        );
+
+        let mut referenced = HashSet::new();
+        for path in manifests {
+            match read_manifest(&path) {
+                Ok(manifest) => {
+                    referenced.extend(manifest.entries.into_iter().filter_map(|e| e.hash));
+                }
+                Err(e) => {
+                    // Never collect while a manifest is unreadable; we could drop live data
+                    log::warn!("Skipping backup garbage collection: {e}");
+                    return Ok(0);
+                }
+            }
+        }
+
+        let mut removed = 0usize;
+        for object in walk_files(&self.root.join("objects")) {
+            let hash = object
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(".gz"))
+                .unwrap_or_default()
+                .to_string();
+            if !referenced.contains(&hash) {
+                remove_path(&object)?;
+                removed += 1;
+            }
+        }
+        Ok(removed)
+    }
+
+    fn object_path(&self, hash: &str) -> PathBuf {
+        let prefix = hash.get(..2).unwrap_or("00");
+        self.root
+            .join("objects")
+            .join(prefix)
+            .join(format!("{hash}.gz"))
+    }
+
+    fn store_dir(
+        &self,
+        dir: &Path,
+        rel: &str,
+        entries: &mut Vec<ManifestEntry>,
+    ) -> Result<(), AppError> {
+        let mut children: Vec<_> = fs::read_dir(dir)
+            .map_err(|e| AppError::FileRead {
+                path: dir.to_path_buf(),
+                source: e.to_string(),
+            })?
+            .flatten()
+            .collect();
+        children.sort_by_key(|e| e.file_name());
+
+        for child in children {
+            let name = child.file_name().to_string_lossy().to_string();
+            let child_rel = if rel.is_empty() {
+                name
+            } else {
+                format!("{rel}/{name}")
+            };
+            let path = child.path();
+            if path.is_dir() {
+                entries.push(ManifestEntry {
+                    path: child_rel.clone(),
+                    kind: EntryKind::Dir,
+                    hash: None,
+                    size: 0,
+                    mode: get_mode(&path),
+                });
+                self.store_dir(&path, &child_rel, entries)?;
+            } else {
+                entries.push(self.store_file(&path, child_rel)?);
+            }
+        }
+        Ok(())
+    }
+
+    fn store_file(&self, path: &Path, rel: String) -> Result<ManifestEntry, AppError> {
+        let data = fs::read(path).map_err(|e| AppError::FileRead {
+            path: path.to_path_buf(),
+            source: e.to_string(),
+        })?;
+        let hash = sha256_hex(&data);
+        let object = self.object_path(&hash);
+
+        if !object.exists() {
+            let parent = object.parent().unwrap_or(&self.root);
+            fs::create_dir_all(parent).map_err(|e| AppError::DirCreate {
+                path: parent.to_path_buf(),
+                source: e.to_string(),
+            })?;
+            // Write to a temp name first so an interrupted backup never leaves a truncated object
+            let tmp = object.with_extension("gz.tmp");
+            let write = || -> std::io::Result<()> {
+                let mut encoder = GzEncoder::new(fs::File::create(&tmp)?, Compression::default());
+                encoder.write_all(&data)?;
+                encoder.finish()?.sync_all()?;
+                fs::rename(&tmp, &object)
+            };
+            write().map_err(|e| {
+                let _ = fs::remove_file(&tmp);
+                AppError::FileWrite {
+                    path: object.clone(),
+                    source: e.to_string(),
+                }
+            })?;
+        }
+
+        Ok(ManifestEntry {
+            path: rel,
+            kind: EntryKind::File,
+            hash: Some(hash),
+            size: data.len() as u64,
+            mode: get_mode(path),
+        })
+    }
+
+    fn restore_into(&self, manifest: &BackupManifest, dest_root: &Path) -> Result<(), AppError> {
+        if manifest.root_is_dir {
+            fs::create_dir_all(dest_root).map_err(|e| AppError::DirCreate {
+                path: dest_root.to_path_buf(),
+                source: e.to_string(),
+            })?;
+        }
+        for entry in &manifest.entries {
+            let dest = if entry.path.is_empty() {
+                dest_root.to_path_buf()
+            } else {
+                safe_join(dest_root, &entry.path)?
+            };
+            match entry.kind {
+                EntryKind::Dir => {
+                    fs::create_dir_all(&dest).map_err(|e| AppError::DirCreate {
+                        path: dest.clone(),
+                        source: e.to_string(),
+                    })?;
+                }
+                EntryKind::File => {
+                    let hash = entry.hash.as_deref().ok_or_else(|| {
+                        AppError::InvalidState(format!("Backup entry {} has no object", entry.path))
+                    })?;
+                    self.restore_object(hash, &dest)?;
+                    set_mode(&dest, entry.mode);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn restore_object(&self, hash: &str, dest: &Path) -> Result<(), AppError> {
+        let object = self.object_path(hash);
+        let file = fs::File::open(&object).map_err(|e| AppError::FileRead {
+            path: object.clone(),
+            source: e.to_string(),
+        })?;
+        let mut data = Vec::new();
+        GzDecoder::new(file)
+            .read_to_end(&mut data)
+            .map_err(|e| AppError::FileRead {
+                path: object.clone(),
+                source: e.to_string(),
+            })?;
+        if sha256_hex(&data) != hash {
+            return Err(AppError::InvalidState(format!(
+                "Backup object {} is corrupted (checksum mismatch)",
+                object.display()
+            )));
+        }
+
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent).map_err(|e| AppError::DirCreate {
+                path: parent.to_path_buf(),
+                source: e.to_string(),
+            })?;
+        }
+        fs::write(dest, &data).map_err(|e| AppError::FileWrite {
+            path: dest.to_path_buf(),
+            source: e.to_string(),
+        })
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<BackupManifest, AppError> {
+    let data = fs::read(path).map_err(|e| AppError::FileRead {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    serde_json::from_slice(&data).map_err(|e| AppError::JsonParse {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn safe_join(base: &Path, rel: &str) -> Result<PathBuf, AppError> {
+    let mut out = base.to_path_buf();
+    for part in rel.split('/') {
+        if part.is_empty() || part == "." || part == ".." || part.contains('\\') {
+            return Err(AppError::PathValidation {
+                path: base.join(rel),
+                reason: "Path traversal attempt detected".into(),
+            });
+        }
+        out.push(part);
+    }
+    Ok(out)
+}
+
+#[cfg(unix)]
+fn get_mode(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata().ok().map(|m| m.permissions().mode())
+}
+
+#[cfg(not(unix))]
+fn get_mode(_path: &Path) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn set_mode(path: &Path, mode: Option<u32>) {
+    use std::os::unix::fs::PermissionsExt;
+    if let Some(mode) = mode {
+        let _ = fs::set_permissions(path, fs::Permissions::from_mode(mode));
+    }
+}
+
+#[cfg(not(unix))]
+fn set_mode(_path: &Path, _mode: Option<u32>) {}
+
+fn walk_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            files.extend(walk_files(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+// A hidden path beside `path` in the same directory, so renames between them stay atomic
+pub(crate) fn sibling_path(path: &Path, purpose: &str) -> Result<PathBuf, AppError> {
+    let name = path
+        .file_name()
+        .ok_or_else(|| AppError::invalid_path(path, "Path has no file name"))?;
+    Ok(path.with_file_name(format!(".{}.bmm-{purpose}", name.to_string_lossy())))
+}
+
+// Replace `target` with `staged`, putting the old copy back if the swap fails
+fn swap_into_place(staged: &Path, target: &Path) -> Result<(), AppError> {
+    let rename_error = |from: &Path, e: std::io::Error| AppError::FileWrite {
+        path: from.to_path_buf(),
+        source: e.to_string(),
+    };
+    let old = sibling_path(target, "old")?;
+    remove_path(&old)?;
+    let had_old = target.exists();
+    if had_old {
+        fs::rename(target, &old).map_err(|e| rename_error(target, e))?;
+    }
+    if let Err(e) = fs::rename(staged, target) {
+        if had_old {
+            let _ = fs::rename(&old, target);
+        }
+        let _ = remove_path(staged);
+        return Err(rename_error(staged, e));
+    }
+    if had_old {
+        if let Err(e) = remove_path(&old) {
+            log::warn!("Failed to remove replaced copy {}: {e}", old.display());
+        }
+    }
+    Ok(())
+}
+
+pub(crate) fn remove_path(path: &Path) -> Result<(), AppError> {
+    let result = if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else if path.exists() {
+        fs::remove_file(path)
+    } else {
+        Ok(())
+    };
+    result.map_err(|e| AppError::FileWrite {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })
+}
+
+fn dir_size(path: &Path) -> u64 {
+    if path.is_file() {
+        return path.metadata().map(|m| m.len()).unwrap_or(0);
+    }
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries.flatten().map(|e| dir_size(&e.path())).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn make_mod(dir: &Path, contents: &str) {
+        fs::create_dir_all(dir.join("assets")).unwrap();
+        fs::write(dir.join("main.lua"), contents).unwrap();
+        fs::write(dir.join("assets/icon.png"), [0u8; 16]).unwrap();
+    }
+
+    #[test]
+    fn create_and_restore_by_id() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let db = Database::open_in_memory()?;
+        let store = BackupStore::with_root(td.path().join("backups"));
+        let mod_dir = td.path().join("Mods").join("CoolMod");
+        make_mod(&mod_dir, "v1");
+
+        let backup = store.create(&db, &mod_dir, "CoolMod", Some("1.0"), BackupReason::Update)?;
+        assert_eq!(backup.mod_name, "CoolMod");
+        assert_eq!(backup.version.as_deref(), Some("1.0"));
+        assert!(backup.size_bytes > 0);
+
+        fs::write(mod_dir.join("main.lua"), "v2").unwrap();
+        fs::write(mod_dir.join("new.lua"), "extra").unwrap();
+
+        store.restore(&db, backup.id)?;
+        assert_eq!(fs::read_to_string(mod_dir.join("main.lua")).unwrap(), "v1");
+        assert!(!mod_dir.join("new.lua").exists());
+        assert!(mod_dir.join("assets/icon.png").exists());
+        Ok(())
+    }
+
+    #[test]
+    fn cleanup_enforces_per_mod_limit_and_quota() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let db = Database::open_in_memory()?;
+        let store = BackupStore::with_root(td.path().join("backups"));
+        let a = td.path().join("Mods").join("A");
+        let b = td.path().join("Mods").join("B");
+        make_mod(&a, "a");
+        make_mod(&b, "b");
+
+        for _ in 0..3 {
+            store.create(&db, &a, "A", None, BackupReason::Manual)?;
+        }
+        store.create(&db, &b, "B", None, BackupReason::Manual)?;
+
+        let removed = store.cleanup(
+            &db,
+            RetentionPolicy {
+                max_per_mod: 2,
+                quota_bytes: u64::MAX,
+            },
+        )?;
+        assert_eq!(removed, 1);
+        assert_eq!(store.list(&db, Some(&a))?.len(), 2);
+
+        // A zero quota evicts everything
+        store.cleanup(
+            &db,
+            RetentionPolicy {
+                max_per_mod: 2,
+                quota_bytes: 0,
+            },
+        )?;
+        assert!(store.list(&db, None)?.is_empty());
+        assert_eq!(store.disk_usage(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn restore_latest_without_backup_fails() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let db = Database::open_in_memory()?;
+        let store = BackupStore::with_root(td.path().join("backups"));
+        assert!(store.restore_latest(&db, &td.path().join("nope")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn identical_content_is_stored_once() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let db = Database::open_in_memory()?;
+        let store = BackupStore::with_root(td.path().join("backups"));
+        let mod_dir = td.path().join("Mods").join("Textures");
+        make_mod(&mod_dir, "same");
+
+        store.create(&db, &mod_dir, "Textures", None, BackupReason::Manual)?;
+        let after_first = store.usage(&db)?;
+        store.create(&db, &mod_dir, "Textures", None, BackupReason::Manual)?;
+        let after_second = store.usage(&db)?;
+
+        assert_eq!(after_first.object_count, 2);
+        assert_eq!(after_second.object_count, 2);
+        assert_eq!(after_second.logical_bytes, after_first.logical_bytes * 2);
+
+        // Removing one backup must keep the objects the other still references
+        let newest = store.list(&db, None)?[0].id;
+        store.remove(&db, newest)?;
+        assert_eq!(store.usage(&db)?.object_count, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn corrupted_object_aborts_restore() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let db = Database::open_in_memory()?;
+        let store = BackupStore::with_root(td.path().join("backups"));
+        let mod_dir = td.path().join("Mods").join("CoolMod");
+        make_mod(&mod_dir, "v1");
+        let backup = store.create(&db, &mod_dir, "CoolMod", None, BackupReason::Manual)?;
+
+        let object = store.object_path(&sha256_hex(b"v1"));
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(b"tampered").unwrap();
+        fs::write(&object, encoder.finish().unwrap()).unwrap();
+
+        make_mod(&mod_dir, "v2");
+        assert!(store.restore(&db, backup.id).is_err());
+        // The live mod is untouched and no staging copy is left behind
+        assert_eq!(fs::read_to_string(mod_dir.join("main.lua")).unwrap(), "v2");
+        assert!(mod_dir.join("assets/icon.png").exists());
+        let leftovers: Vec<_> = fs::read_dir(mod_dir.parent().unwrap())
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name())
+            .collect();
+        assert_eq!(leftovers, ["CoolMod"]);
+        Ok(())
+    }
+
+    #[test]
+    fn garbage_collection_keeps_objects_of_unrecorded_snapshots() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let db = Database::open_in_memory()?;
+        let store = BackupStore::with_root(td.path().join("backups"));
+        let mod_dir = td.path().join("Mods").join("CoolMod");
+        make_mod(&mod_dir, "v1");
+
+        // Taken but not yet recorded, as while the app snapshots without the db lock
+        let snapshot = store.snapshot(&mod_dir)?;
+        assert_eq!(store.collect_garbage(&db)?, 0);
+
+        let backup = store.record(&db, snapshot, "CoolMod", None, BackupReason::Manual)?;
+        fs::write(mod_dir.join("main.lua"), "v2").unwrap();
+        store.restore(&db, backup.id)?;
+        assert_eq!(fs::read_to_string(mod_dir.join("main.lua")).unwrap(), "v1");
+        Ok(())
+    }
+
+    #[test]
+    fn imports_legacy_temp_dir_backups() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let db = Database::open_in_memory()?;
+        let store = BackupStore::with_root(td.path().join("backups"));
+        let mod_dir = td.path().join("Mods").join("OldMod");
+        let legacy = td.path().join("legacy");
+        make_mod(&legacy.join("backup_1000").join("OldMod"), "old");
+        fs::write(
+            legacy.join("backup_1000/metadata.json"),
+            serde_json::json!({
+                "original_path": mod_dir.to_string_lossy(),
+                "backup_time": 1
+            })
+            .to_string(),
+        )
+        .unwrap();
+        fs::create_dir_all(legacy.join("stray")).unwrap();
+
+        assert_eq!(store.import_legacy(&db, &legacy)?, 1);
+        let backups = store.list(&db, Some(&mod_dir))?;
+        assert_eq!(backups.len(), 1);
+        assert_eq!(backups[0].mod_name, "OldMod");
+        assert_eq!(backups[0].created_at, 1);
+        // Unreadable entries stay where they are
+        assert!(!legacy.join("backup_1000").exists());
+        assert!(legacy.join("stray").exists());
+
+        store.restore(&db, backups[0].id)?;
+        assert_eq!(fs::read_to_string(mod_dir.join("main.lua")).unwrap(), "old");
+        assert!(mod_dir.join("assets/icon.png").exists());
+        Ok(())
+    }
+}
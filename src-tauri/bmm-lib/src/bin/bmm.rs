@@ -0,0 +1,487 @@
+//! `bmm`: the mod manager without its UI, for scripted setups on test machines and servers.
+
+use bmm_lib::cache::{self, Mod};
+use bmm_lib::database::Database;
+use bmm_lib::errors::AppError;
+use bmm_lib::forge::{self, ForgeClient, RepoRef};
+use bmm_lib::launch_session::{unix_millis, LaunchSession, LaunchVariant};
+use bmm_lib::launcher::{self, LaunchOptions};
+use bmm_lib::save_backup::{self, SaveBackupReason};
+use bmm_lib::thunderstore::{self, ThunderstoreClient};
+use bmm_lib::{lovely, mod_manager, mod_sources, paths, profile};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::process::ExitCode;
+
+const USAGE: &str = "\
+Usage: bmm [--json] <command> [args]
+
+Commands:
+  search <query>                 Search the mod catalog
+  install <name|url> [--name N]  Install a catalog mod, or an archive URL tracked as N
+  install thunderstore:<Ns-Name> Install a Thunderstore package and its dependencies
+  install repo:<url|owner/repo[@ref]> [--asset FILE]
+                                 Install from a GitHub/GitLab repository (a release
+                                 asset with --asset, else the source archive)
+  versions <url|owner/repo>      List a repository's releases and tags
+  uninstall <name> [--cascade]   Remove a mod (and, with --cascade, mods depending on it)
+  update [name]                  Update outdated mods from the catalog
+  list                           List installed mods
+  enable <name>                  Enable an installed mod
+  disable <name>                 Disable an installed mod
+  profile apply <file>           Make the installed mods match a profile file
+  lovely install [version] [--allow-unverified]
+                                 Install Lovely (latest or the pinned release, or a
+                                 tag; --allow-unverified accepts a tag that publishes
+                                 no checksum)
+  launch [--vanilla|--safe-mode] [--wait]
+                                 Start the game; --wait reports how it exited
+
+Exit codes:
+  0  success
+  1  the operation failed
+  2  invalid usage
+  3  mod not found
+  4  the game crashed (launch --wait)
+";
+
+const EXIT_FAILURE: u8 = 1;
+const EXIT_USAGE: u8 = 2;
+const EXIT_NOT_FOUND: u8 = 3;
+const EXIT_GAME_CRASHED: u8 = 4;
+
+// Options that take a value; every other `--x` is a flag
+const VALUE_OPTIONS: &[&str] = &["--name", "--asset"];
+const FLAGS: &[&str] = &[
+    "--json",
+    "--help",
+    "-h",
+    "--cascade",
+    "--allow-unverified",
+    "--vanilla",
+    "--safe-mode",
+    "--wait",
+];
+
+enum CliError {
+    Usage(String),
+    App(AppError),
+}
+
+impl From<AppError> for CliError {
+    fn from(e: AppError) -> Self {
+        CliError::App(e)
+    }
+}
+
+impl CliError {
+    fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Usage(_) => EXIT_USAGE,
+            CliError::App(AppError::ModNotFound { .. }) => EXIT_NOT_FOUND,
+            CliError::App(_) => EXIT_FAILURE,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            CliError::Usage(msg) => msg.clone(),
+            CliError::App(e) => e.to_string(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Args {
+    positional: Vec<String>,
+    flags: HashSet<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(raw: impl Iterator<Item = String>) -> Result<Self, CliError> {
+        let mut args = Args::default();
+        let mut raw = raw.peekable();
+        while let Some(arg) = raw.next() {
+            if VALUE_OPTIONS.contains(&arg.as_str()) {
+                let value = raw
+                    .next()
+                    .ok_or_else(|| CliError::Usage(format!("{arg} needs a value")))?;
+                args.options.insert(arg, value);
+            } else if FLAGS.contains(&arg.as_str()) {
+                args.flags.insert(arg);
+            } else if arg.starts_with("--") {
+                return Err(CliError::Usage(format!("unknown option: {arg}")));
+            } else {
+                args.positional.push(arg);
+            }
+        }
+        Ok(args)
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.flags.contains(name)
+    }
+
+    fn option(&self, name: &str) -> Option<&str> {
+        self.options.get(name).map(String::as_str)
+    }
+
+    fn arg(&self, index: usize, what: &str) -> Result<&str, CliError> {
+        self.positional
+            .get(index)
+            .map(String::as_str)
+            .ok_or_else(|| CliError::Usage(format!("missing {what}")))
+    }
+}
+
+struct Output {
+    json: bool,
+}
+
+impl Output {
+    fn print<T: Serialize>(&self, value: &T, text: impl FnOnce(&T) -> String) {
+        if self.json {
+            match serde_json::to_string_pretty(value) {
+                Ok(s) => println!("{s}"),
+                Err(e) => eprintln!("Failed to serialize output: {e}"),
+            }
+        } else {
+            let text = text(value);
+            if !text.is_empty() {
+                println!("{text}");
+            }
+        }
+    }
+
+    fn error(&self, err: &CliError) {
+        if self.json {
+            eprintln!(
+                "{}",
+                json!({ "error": err.message(), "code": err.exit_code() })
+            );
+        } else {
+            eprintln!("bmm: {}", err.message());
+            if matches!(err, CliError::Usage(_)) {
+                eprintln!("\n{USAGE}");
+            }
+        }
+    }
+}
+
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(e) => {
+            Output { json: false }.error(&e);
+            return ExitCode::from(e.exit_code());
+        }
+    };
+    let out = Output {
+        json: args.flag("--json"),
+    };
+    if args.flag("--help") || args.flag("-h") || args.positional.is_empty() {
+        print!("{USAGE}");
+        return if args.positional.is_empty() && !args.flag("--help") && !args.flag("-h") {
+            ExitCode::from(EXIT_USAGE)
+        } else {
+            ExitCode::SUCCESS
+        };
+    }
+
+    match run(&args, &out).await {
+        Ok(code) => ExitCode::from(code),
+        Err(e) => {
+            out.error(&e);
+            ExitCode::from(e.exit_code())
+        }
+    }
+}
+
+async fn run(args: &Args, out: &Output) -> Result<u8, CliError> {
+    let db = Database::new()?;
+    paths::refresh(&db)?;
+
+    match args.arg(0, "command")? {
+        "search" => {
+            let catalog = load_catalog(&db).await?;
+            let found: Vec<&Mod> = mod_manager::search(&catalog, args.arg(1, "search query")?);
+            out.print(&found, |found| {
+                found
+                    .iter()
+                    .map(|m| {
+                        format!(
+                            "{} by {}{}",
+                            m.title,
+                            m.publisher,
+                            m.version
+                                .as_deref()
+                                .map(|v| format!(" ({v})"))
+                                .unwrap_or_default()
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+            if found.is_empty() {
+                return Ok(EXIT_NOT_FOUND);
+            }
+        }
+        "install" => {
+            let target = args.arg(1, "mod name or URL")?;
+            if let Some(full_name) = target.strip_prefix("thunderstore:") {
+                let packages = ThunderstoreClient::new().fetch_packages().await?;
+                let installed = thunderstore::install(&db, &packages, full_name).await?;
+                out.print(&installed, |installed| {
+                    installed
+                        .iter()
+                        .map(|m| format!("Installed {} to {}", m.name, m.path))
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                });
+                return Ok(0);
+            }
+            if let Some(repo) = target.strip_prefix("repo:") {
+                let repo = RepoRef::parse(repo)?;
+                save_backup::snapshot_quietly(
+                    &db,
+                    SaveBackupReason::for_install(Some(repo.name())),
+                );
+                let install = ForgeClient::new()
+                    .install(&repo, None, args.option("--asset"))
+                    .await?;
+                forge::track(&db, &install)?;
+                out.print(&install, |i| {
+                    format!(
+                        "Installed {} {} to {}",
+                        i.installed.name, i.origin.git_ref, i.installed.path
+                    )
+                });
+                return Ok(0);
+            }
+            let installed = if target.starts_with("http://") || target.starts_with("https://") {
+                mod_manager::install_from_url(&db, target, args.option("--name")).await?
+            } else {
+                let catalog = load_catalog(&db).await?;
+                let entry = mod_manager::find_in_catalog(&catalog, target)
+                    .ok_or_else(|| not_found(target))?;
+                mod_manager::install_from_catalog(&db, entry).await?
+            };
+            out.print(&installed, |m| {
+                format!("Installed {} to {}", m.name, m.path)
+            });
+        }
+        "versions" => {
+            let repo = RepoRef::parse(args.arg(1, "repository")?)?;
+            let versions = ForgeClient::new().list_versions(&repo).await?;
+            out.print(&versions, |versions| {
+                versions
+                    .iter()
+                    .map(|v| {
+                        let kind = match (v.release, v.prerelease) {
+                            (false, _) => " (tag)",
+                            (true, true) => " (pre-release)",
+                            (true, false) => "",
+                        };
+                        let assets: Vec<&str> = v.assets.iter().map(|a| a.name.as_str()).collect();
+                        if assets.is_empty() {
+                            format!("{}{kind}", v.tag)
+                        } else {
+                            format!("{}{kind}: {}", v.tag, assets.join(", "))
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+        }
+        "uninstall" => {
+            let removed =
+                mod_manager::uninstall(&db, args.arg(1, "mod name")?, args.flag("--cascade"))?;
+            out.print(&removed, |removed| {
+                format!("Removed {}", removed.join(", "))
+            });
+        }
+        "update" => {
+            let catalog = load_catalog(&db).await?;
+            let updated =
+                mod_manager::update(&db, &catalog, args.positional.get(1).map(String::as_str))
+                    .await?;
+            out.print(&updated, |updated| {
+                if updated.is_empty() {
+                    return "Everything is up to date".to_string();
+                }
+                updated
+                    .iter()
+                    .map(|u| {
+                        format!(
+                            "Updated {} {} -> {}",
+                            u.name,
+                            u.from.as_deref().unwrap_or("?"),
+                            u.to.as_deref().unwrap_or("?")
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+        }
+        "list" => {
+            let mods = mod_manager::list(&db)?;
+            out.print(&mods, |mods| {
+                mods.iter()
+                    .map(|m| {
+                        format!(
+                            "{}{} {}",
+                            m.installed.name,
+                            m.installed
+                                .current_version
+                                .as_deref()
+                                .filter(|v| !v.is_empty())
+                                .map(|v| format!(" {v}"))
+                                .unwrap_or_default(),
+                            if m.enabled { "" } else { "(disabled)" }
+                        )
+                        .trim_end()
+                        .to_string()
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            });
+        }
+        command @ ("enable" | "disable") => {
+            let name = args.arg(1, "mod name")?;
+            let enabled = command == "enable";
+            mod_manager::set_mod_enabled(&db, name, enabled)?;
+            out.print(&json!({ "name": name, "enabled": enabled }), |_| {
+                format!("{} {name}", if enabled { "Enabled" } else { "Disabled" })
+            });
+        }
+        "profile" => {
+            if args.arg(1, "profile subcommand")? != "apply" {
+                return Err(CliError::Usage(format!(
+                    "unknown profile subcommand: {}",
+                    args.positional[1]
+                )));
+            }
+            let profile = profile::load(Path::new(args.arg(2, "profile file")?))?;
+            let catalog = load_catalog(&db).await?;
+            let plan = profile::apply(&db, &catalog, &profile).await?;
+            out.print(&plan, |plan| {
+                format!(
+                    "Applied profile {}: {} installed, {} enabled, {} disabled",
+                    profile.name,
+                    plan.install.len(),
+                    plan.enable.len(),
+                    plan.disable.len()
+                )
+            });
+        }
+        "lovely" => {
+            if args.arg(1, "lovely subcommand")? != "install" {
+                return Err(CliError::Usage(format!(
+                    "unknown lovely subcommand: {}",
+                    args.positional[1]
+                )));
+            }
+            let target = lovely::LovelyTarget::from_db(&db)?;
+            let version = match args.positional.get(2) {
+                Some(tag) => {
+                    let tag = lovely::normalize_tag(tag);
+                    lovely::install_lovely_version(&target, &tag, args.flag("--allow-unverified"))
+                        .await?;
+                    tag.trim_start_matches('v').to_string()
+                }
+                None => lovely::update_lovely(&target, lovely::pinned_tag(&db)?.as_deref()).await?,
+            };
+            db.set_lovely_version(&version)?;
+            out.print(&json!({ "version": version }), |_| {
+                format!("Installed Lovely {version}")
+            });
+        }
+        "launch" => return launch(&db, args, out).await,
+        other => return Err(CliError::Usage(format!("unknown command: {other}"))),
+    }
+    Ok(0)
+}
+
+async fn launch(db: &Database, args: &Args, out: &Output) -> Result<u8, CliError> {
+    let variant = match (args.flag("--vanilla"), args.flag("--safe-mode")) {
+        (true, true) => {
+            return Err(CliError::Usage(
+                "--vanilla and --safe-mode can't be combined".to_string(),
+            ))
+        }
+        (true, false) => LaunchVariant::Vanilla,
+        (false, true) => LaunchVariant::SafeMode,
+        (false, false) => LaunchVariant::Modded,
+    };
+    let options = LaunchOptions::from_db(db, variant)?;
+    let mods_dir = paths::mods_dir()?;
+    let (launch_mode, started, process) = launcher::spawn(&options).await?;
+
+    let launch_mode = variant.session_label(launch_mode);
+    let session_id = db
+        .start_launch_session(&launch_mode, unix_millis(started))
+        .map_err(|e| log::warn!("Failed to record launch session: {e}"))
+        .ok();
+    let session = LaunchSession::new(session_id, &launch_mode, started, process, &mods_dir);
+
+    // Safe mode has to wait so the other mods can be switched back on afterwards
+    if !args.flag("--wait") && variant != LaunchVariant::SafeMode {
+        out.print(&session.started_event(), |s| {
+            format!("Started Balatro ({})", s.launch_mode)
+        });
+        return Ok(0);
+    }
+
+    let Some(outcome) = session.wait() else {
+        return Err(AppError::ProcessExecution(
+            "Balatro didn't show up after the launch, so its exit can't be reported".to_string(),
+        )
+        .into());
+    };
+    launcher::finish(variant)?;
+    if let Some(id) = outcome.session_id {
+        db.finish_launch_session(
+            id,
+            outcome.ended_at,
+            outcome.exit_code,
+            outcome.crashed,
+            outcome.crash.as_ref().map(|c| c.message.as_str()),
+        )?;
+    }
+    let crashed = outcome.crashed;
+    out.print(&outcome, |o| match &o.crash {
+        Some(crash) => format!("Balatro crashed: {}", crash.message),
+        None if o.crashed => format!("Balatro exited with code {:?}", o.exit_code),
+        None => format!("Balatro exited after {}s", o.duration_ms / 1000),
+    });
+    Ok(if crashed { EXIT_GAME_CRASHED } else { 0 })
+}
+
+/// The catalog from every enabled mod source, falling back to the manager's cached
+/// catalog when offline.
+async fn load_catalog(db: &Database) -> Result<Vec<Mod>, AppError> {
+    let sources = mod_sources::configured(db)?;
+    match mod_sources::fetch_all(&sources).await {
+        Ok(entries) => Ok(entries.into_iter().map(Mod::from).collect()),
+        Err(e) => match cache::load_cache()? {
+            Some((mods, _)) if !mods.is_empty() => {
+                log::warn!("Using the cached catalog, fetching the index failed: {e}");
+                Ok(mods)
+            }
+            _ => Err(e),
+        },
+    }
+}
+
+fn not_found(name: &str) -> AppError {
+    AppError::ModNotFound {
+        mod_name: name.to_string(),
+        version: String::new(),
+    }
+}
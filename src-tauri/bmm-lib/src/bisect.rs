@@ -0,0 +1,393 @@
+use crate::errors::AppError;
+use crate::mod_manager;
+use crate::safe_mode::is_steamodded_dir;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BisectResult {
+    /// The game started fine with the tested mods.
+    Pass,
+    /// The game crashed with the tested mods.
+    Fail,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BisectStep {
+    pub enabled: Vec<String>,
+    pub result: BisectResult,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BisectOutcome {
+    /// The crash needs exactly these mods (one, or two that clash).
+    Found(Vec<String>),
+    /// Every half passed on its own even with a partner fixed; more than two mods are involved.
+    Inconclusive(Vec<String>),
+}
+
+/// A crash bisection over the mods that were enabled when it started.
+///
+/// Each step enables `base` plus half of `candidates` (via `.lovelyignore`), the user launches
+/// the game and reports whether it crashed. When both halves pass on their own the crash is an
+/// interaction: one half is kept enabled as `base` while the other is narrowed down to a single
+/// mod, which then becomes the base for narrowing down its partner.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BisectSession {
+    mods_dir: PathBuf,
+    /// Mod folders this session toggles; all of them are re-enabled at the end.
+    managed: Vec<String>,
+    /// Markers of bundled mods the user had disabled inside `managed`; left alone.
+    #[serde(default)]
+    kept_markers: Vec<PathBuf>,
+    base: Vec<String>,
+    candidates: Vec<String>,
+    /// The other side of a detected interaction, narrowed down after the first culprit.
+    pending: Option<Vec<String>>,
+    found: Vec<String>,
+    /// Half of `candidates` enabled for the current test.
+    testing: Vec<String>,
+    second_half: bool,
+    pub steps: Vec<BisectStep>,
+    pub outcome: Option<BisectOutcome>,
+}
+
+/// Snapshot for the UI.
+#[derive(Serialize, Debug, Clone)]
+pub struct BisectStatus {
+    /// Mods enabled for the launch the user should run next (Steamodded always is).
+    pub enabled: Vec<String>,
+    pub remaining_candidates: usize,
+    pub steps: Vec<BisectStep>,
+    pub outcome: Option<BisectOutcome>,
+}
+
+pub fn default_state_file() -> Result<PathBuf, AppError> {
+    Ok(crate::paths::manager_dir()?.join("bisect.json"))
+}
+
+pub fn load(state_file: &Path) -> Result<Option<BisectSession>, AppError> {
+    if !state_file.exists() {
+        return Ok(None);
+    }
+    let data = fs::read_to_string(state_file).map_err(|e| AppError::FileRead {
+        path: state_file.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    let session = serde_json::from_str(&data).map_err(|e| AppError::JsonParse {
+        path: state_file.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    Ok(Some(session))
+}
+
+/// Start bisecting the currently enabled mods, assuming the game crashes with all of them.
+pub fn start(mods_dir: &Path, state_file: &Path) -> Result<BisectSession, AppError> {
+    if state_file.exists() {
+        return Err(AppError::InvalidState(
+            "A crash bisection is already in progress".to_string(),
+        ));
+    }
+
+    let entries = fs::read_dir(mods_dir).map_err(|e| AppError::FileRead {
+        path: mods_dir.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    let mut managed: Vec<String> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir() && !is_steamodded_dir(p) && mod_manager::is_enabled(p))
+        .filter_map(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .filter(|n| n != "lovely" && n != ".lovely")
+        .collect();
+    managed.sort_by_key(|n| n.to_lowercase());
+    if managed.is_empty() {
+        return Err(AppError::InvalidState(
+            "No enabled mods to bisect besides Steamodded".to_string(),
+        ));
+    }
+
+    let mut kept_markers = Vec::new();
+    for name in &managed {
+        kept_markers.extend(
+            mod_manager::ignore_markers(&mods_dir.join(name))?
+                .into_iter()
+                .filter(|m| m.exists()),
+        );
+    }
+
+    let mut session = BisectSession {
+        mods_dir: mods_dir.to_path_buf(),
+        managed: managed.clone(),
+        kept_markers,
+        base: Vec::new(),
+        candidates: managed,
+        pending: None,
+        found: Vec::new(),
+        testing: Vec::new(),
+        second_half: false,
+        steps: Vec::new(),
+        outcome: None,
+    };
+    session.narrow();
+    // With a single enabled mod there's nothing to test
+    if !session.is_finished() {
+        session.save(state_file)?;
+        session.apply()?;
+    }
+    Ok(session)
+}
+
+/// Give up on the bisection and re-enable every mod it touched.
+pub fn abort(state_file: &Path) -> Result<(), AppError> {
+    if let Some(session) = load(state_file)? {
+        session.restore()?;
+    }
+    remove_state(state_file)
+}
+
+impl BisectSession {
+    pub fn status(&self) -> BisectStatus {
+        BisectStatus {
+            enabled: self.enabled(),
+            remaining_candidates: self.candidates.len(),
+            steps: self.steps.clone(),
+            outcome: self.outcome.clone(),
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.outcome.is_some()
+    }
+
+    /// Record how the last launch went and set up the next one. Once the culprit is
+    /// known the original enabled state is restored and the state file removed.
+    pub fn record(&mut self, result: BisectResult, state_file: &Path) -> Result<(), AppError> {
+        if self.is_finished() {
+            return Err(AppError::InvalidState(
+                "The crash bisection has already finished".to_string(),
+            ));
+        }
+        self.steps.push(BisectStep {
+            enabled: self.enabled(),
+            result,
+        });
+
+        match (result, self.second_half) {
+            (BisectResult::Fail, _) => {
+                self.candidates = std::mem::take(&mut self.testing);
+                self.narrow();
+            }
+            (BisectResult::Pass, false) => {
+                let half = self.candidates.len() / 2;
+                self.testing = self.candidates[half..].to_vec();
+                self.second_half = true;
+            }
+            (BisectResult::Pass, true) if self.base.is_empty() => {
+                // Both halves are fine alone: a mod in one half clashes with one in the other
+                let half = self.candidates.len() / 2;
+                let (left, right) = self.candidates.split_at(half);
+                let (left, right) = (left.to_vec(), right.to_vec());
+                self.base = right.clone();
+                self.pending = Some(right);
+                self.candidates = left;
+                self.narrow();
+            }
+            (BisectResult::Pass, true) => {
+                let mut involved = self.found.clone();
+                involved.extend(self.base.iter().cloned());
+                involved.extend(self.candidates.iter().cloned());
+                involved.sort();
+                involved.dedup();
+                self.outcome = Some(BisectOutcome::Inconclusive(involved));
+            }
+        }
+
+        if self.is_finished() {
+            self.restore()?;
+            remove_state(state_file)
+        } else {
+            self.save(state_file)?;
+            self.apply()
+        }
+    }
+
+    // Split the candidates for the next test, or settle on a culprit
+    fn narrow(&mut self) {
+        self.second_half = false;
+        if self.candidates.len() > 1 {
+            let half = self.candidates.len() / 2;
+            self.testing = self.candidates[..half].to_vec();
+            return;
+        }
+
+        self.found.append(&mut self.candidates);
+        match self.pending.take() {
+            Some(partner_side) => {
+                self.base = self.found.clone();
+                self.candidates = partner_side;
+                self.narrow();
+            }
+            None => {
+                self.testing.clear();
+                self.outcome = Some(BisectOutcome::Found(self.found.clone()));
+            }
+        }
+    }
+
+    fn enabled(&self) -> Vec<String> {
+        let mut enabled: Vec<String> = self
+            .base
+            .iter()
+            .chain(self.testing.iter())
+            .cloned()
+            .collect();
+        enabled.sort_by_key(|n| n.to_lowercase());
+        enabled.dedup();
+        enabled
+    }
+
+    fn apply(&self) -> Result<(), AppError> {
+        let enabled = self.enabled();
+        for name in &self.managed {
+            self.set_enabled(name, enabled.contains(name))?;
+        }
+        Ok(())
+    }
+
+    fn restore(&self) -> Result<(), AppError> {
+        for name in &self.managed {
+            self.set_enabled(name, true)?;
+        }
+        Ok(())
+    }
+
+    fn set_enabled(&self, name: &str, enabled: bool) -> Result<(), AppError> {
+        let mod_dir = self.mods_dir.join(name);
+        // The mod may have been uninstalled mid-bisection
+        if !mod_dir.is_dir() {
+            return Ok(());
+        }
+        mod_manager::set_enabled_keeping(&mod_dir, enabled, &self.kept_markers)
+    }
+
+    fn save(&self, state_file: &Path) -> Result<(), AppError> {
+        if let Some(parent) = state_file.parent() {
+            fs::create_dir_all(parent).map_err(|e| AppError::DirCreate {
+                path: parent.to_path_buf(),
+                source: e.to_string(),
+            })?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| AppError::InvalidState(format!("Failed to serialize bisection: {e}")))?;
+        fs::write(state_file, json).map_err(|e| AppError::FileWrite {
+            path: state_file.to_path_buf(),
+            source: e.to_string(),
+        })
+    }
+}
+
+fn remove_state(state_file: &Path) -> Result<(), AppError> {
+    if state_file.exists() {
+        fs::remove_file(state_file).map_err(|e| AppError::FileWrite {
+            path: state_file.to_path_buf(),
+            source: e.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mod_manager::IGNORE_FILE;
+
+    fn setup(names: &[&str]) -> (tempfile::TempDir, PathBuf, PathBuf) {
+        let tmp = tempfile::tempdir().unwrap();
+        let mods = tmp.path().join("Mods");
+        for name in names {
+            fs::create_dir_all(mods.join(name)).unwrap();
+        }
+        let state = tmp.path().join("bisect.json");
+        (tmp, mods, state)
+    }
+
+    // Drive a bisection with a crash rule evaluated on the enabled mods
+    fn run(mods: &Path, state: &Path, crashes: impl Fn(&[String]) -> bool) -> BisectOutcome {
+        let mut session = start(mods, state).unwrap();
+        for _ in 0..32 {
+            let enabled = session.status().enabled;
+            let result = if crashes(&enabled) {
+                BisectResult::Fail
+            } else {
+                BisectResult::Pass
+            };
+            session.record(result, state).unwrap();
+            if let Some(outcome) = session.outcome.clone() {
+                return outcome;
+            }
+            // The session on disk matches the one in memory
+            assert_eq!(
+                load(state).unwrap().unwrap().status().enabled,
+                session.enabled()
+            );
+        }
+        panic!("bisection did not finish");
+    }
+
+    #[test]
+    fn finds_single_culprit_and_restores_mods() {
+        let names = ["A", "B", "C", "D", "E", "Steamodded"];
+        let (_tmp, mods, state) = setup(&names);
+        fs::write(mods.join("E").join(IGNORE_FILE), "").unwrap();
+        // Bundled mods inside a pack follow it, except those the user turned off
+        fs::create_dir_all(mods.join("D").join("On")).unwrap();
+        fs::create_dir_all(mods.join("D").join("Off")).unwrap();
+        fs::write(mods.join("D").join("Off").join(IGNORE_FILE), "").unwrap();
+
+        let outcome = run(&mods, &state, |enabled| {
+            // Whatever the step, a bundled mod is loaded exactly when its pack is
+            assert_eq!(
+                mod_manager::is_enabled(&mods.join("D").join("On")),
+                enabled.contains(&"D".to_string())
+            );
+            enabled.contains(&"C".to_string())
+        });
+        assert_eq!(outcome, BisectOutcome::Found(vec!["C".to_string()]));
+        assert!(!state.exists());
+        for name in ["A", "B", "C", "D", "Steamodded"] {
+            assert!(!mods.join(name).join(IGNORE_FILE).exists(), "{name}");
+        }
+        assert!(mod_manager::is_enabled(&mods.join("D").join("On")));
+        // Mods disabled before the bisection stay disabled
+        assert!(mods.join("E").join(IGNORE_FILE).exists());
+        assert!(mods.join("D").join("Off").join(IGNORE_FILE).exists());
+    }
+
+    #[test]
+    fn finds_clashing_pair() {
+        let (_tmp, mods, state) = setup(&["A", "B", "C", "D", "E", "F"]);
+        let outcome = run(&mods, &state, |enabled| {
+            enabled.contains(&"B".to_string()) && enabled.contains(&"E".to_string())
+        });
+        assert_eq!(
+            outcome,
+            BisectOutcome::Found(vec!["B".to_string(), "E".to_string()])
+        );
+    }
+
+    #[test]
+    fn abort_restores_original_state() {
+        let (_tmp, mods, state) = setup(&["A", "B", "C"]);
+        start(&mods, &state).unwrap();
+        assert!(start(&mods, &state).is_err());
+        abort(&state).unwrap();
+        assert!(!state.exists());
+        for name in ["A", "B", "C"] {
+            assert!(!mods.join(name).join(IGNORE_FILE).exists());
+        }
+    }
+}
@@ -64,18 +64,25 @@ pub enum Category {
     API = 6,
 }
 
+impl Category {
+    /// The category for its name in the mod index, if it is one we know.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Content" => Some(Category::Content),
+            "Joker" => Some(Category::Joker),
+            "Quality of Life" => Some(Category::QualityOfLife),
+            "Technical" => Some(Category::Technical),
+            "Miscellaneous" => Some(Category::Miscellaneous),
+            "Resource Packs" => Some(Category::ResourcePacks),
+            "API" => Some(Category::API),
+            _ => None,
+        }
+    }
+}
+
 impl From<std::string::String> for Category {
     fn from(value: std::string::String) -> Self {
-        match value.as_str() {
-            "Content" => Category::Content,
-            "Joker" => Category::Joker,
-            "Quality of Life" => Category::QualityOfLife,
-            "Technical" => Category::Technical,
-            "Miscellaneous" => Category::Miscellaneous,
-            "Resource Packs" => Category::ResourcePacks,
-            "API" => Category::API,
-            _ => panic!("Invalid category: {value}"),
-        }
+        Category::from_name(&value).unwrap_or_else(|| panic!("Invalid category: {value}"))
     }
 }
 
@@ -95,9 +102,7 @@ impl From<i32> for Category {
 }
 
 pub fn clear_cache() -> Result<(), AppError> {
-    let cache_dir = dirs::cache_dir()
-        .ok_or_else(|| AppError::DirNotFound(PathBuf::from("cache directory")))?
-        .join("balatro-mod-manager");
+    let cache_dir = crate::paths::cache_dir()?;
 
     // Delete mods cache
     let mods_cache = cache_dir.join("mods.cache.bin.gz");
@@ -128,9 +133,7 @@ pub fn clear_cache() -> Result<(), AppError> {
 }
 
 pub fn save_versions_cache(mod_type: &str, versions: &[String]) -> Result<(), AppError> {
-    let mut path = dirs::cache_dir()
-        .ok_or_else(|| AppError::DirNotFound(PathBuf::from("cache directory")))?
-        .join("balatro-mod-manager");
+    let mut path = crate::paths::cache_dir()?;
 
     std::fs::create_dir_all(&path).map_err(|e| AppError::DirCreate {
         path: path.clone(),
@@ -187,10 +190,7 @@ pub fn save_versions_cache(mod_type: &str, versions: &[String]) -> Result<(), Ap
 }
 
 pub fn load_versions_cache(mod_type: &str) -> Result<Option<Vec<String>>, AppError> {
-    let path = dirs::cache_dir()
-        .ok_or_else(|| AppError::DirNotFound(PathBuf::from("cache directory")))?
-        .join("balatro-mod-manager")
-        .join(format!("versions-{mod_type}.cache.bin.gz"));
+    let path = crate::paths::cache_dir()?.join(format!("versions-{mod_type}.cache.bin.gz"));
 
     let mut file = match File::open(&path) {
         Ok(f) => f,
@@ -242,9 +242,7 @@ struct VersionCache {
 }
 
 pub fn get_cache_path() -> Result<PathBuf, AppError> {
-    let mut path = dirs::cache_dir()
-        .ok_or_else(|| AppError::DirNotFound(PathBuf::from("cache directory")))?
-        .join("balatro-mod-manager");
+    let mut path = crate::paths::cache_dir()?;
 
     std::fs::create_dir_all(&path).map_err(|e| AppError::DirCreate {
         path: path.clone(),
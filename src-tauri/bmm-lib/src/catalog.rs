@@ -0,0 +1,1034 @@
+//! The community mod index on GitLab: the mod list, metadata, descriptions and thumbnails.
+
+use crate::cache::{Category, ColorPair, Mod};
+use crate::errors::AppError;
+use crate::paths;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// The community mod index every install starts with.
+pub const DEFAULT_INDEX_URL: &str = "https://gitlab.com/balatro-mod-index/repo";
+const BRANCHES: [&str; 2] = ["main", "master"];
+const USER_AGENT: &str = "balatro-mod-manager/1.0";
+
+/// `meta.json` of a mod in the index.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ModMeta {
+    #[serde(rename = "requires-steamodded")]
+    pub requires_steamodded: bool,
+    #[serde(rename = "requires-talisman")]
+    pub requires_talisman: bool,
+    pub categories: Vec<String>,
+    pub author: String,
+    pub repo: String,
+    pub title: String,
+    #[serde(rename = "downloadURL")]
+    pub download_url: Option<String>,
+    #[serde(rename = "folderName", default)]
+    pub folder_name: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(rename = "automatic-version-check", default)]
+    pub automatic_version_check: bool,
+    #[serde(rename = "last-updated", default)]
+    pub last_updated: u64,
+}
+
+/// One mod of the index: its folder under `mods/`, metadata, description and thumbnail URL.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CatalogEntry {
+    pub dir_name: String,
+    pub meta: ModMeta,
+    pub description: String,
+    pub image_url: String,
+    /// Name of the mod source this entry came from.
+    #[serde(default)]
+    pub source: String,
+}
+
+impl From<CatalogEntry> for Mod {
+    fn from(entry: CatalogEntry) -> Self {
+        let meta = entry.meta;
+        Mod {
+            title: meta.title,
+            description: entry.description,
+            image: entry.image_url,
+            categories: meta
+                .categories
+                .iter()
+                .filter_map(|c| Category::from_name(c))
+                .collect(),
+            colors: ColorPair {
+                color1: String::new(),
+                color2: String::new(),
+            },
+            installed: false,
+            requires_steamodded: meta.requires_steamodded,
+            requires_talisman: meta.requires_talisman,
+            publisher: meta.author,
+            repo: meta.repo,
+            download_url: meta.download_url.unwrap_or_default(),
+            folderName: Some(meta.folder_name).filter(|f| !f.is_empty()),
+            version: Some(meta.version).filter(|v| !v.is_empty()),
+        }
+    }
+}
+
+/// A mod whose thumbnail should be stored under its title.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThumbnailRequest {
+    pub title: String,
+    pub dir_name: String,
+}
+
+/// The prebuilt `index.json` format.
+#[derive(Serialize, Deserialize)]
+pub(crate) struct IndexFileV1 {
+    pub(crate) version: Option<u32>,
+    pub(crate) mods: Vec<CatalogEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ArchiveCache {
+    etag: Option<String>,
+    branch: String,
+    items: Vec<CatalogEntry>,
+}
+
+#[derive(Deserialize)]
+struct GitLabTreeEntry {
+    name: String,
+    r#type: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabFileContent {
+    content: String,
+    encoding: String,
+}
+
+#[derive(Serialize)]
+struct LfsBatchReq<'a> {
+    operation: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transfers: Option<&'a [&'a str]>,
+    objects: Vec<LfsObjectSpec>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct LfsObjectSpec {
+    oid: String,
+    size: u64,
+}
+
+#[derive(Deserialize)]
+struct LfsBatchResp {
+    objects: Vec<LfsObjectResp>,
+}
+
+#[derive(Deserialize)]
+struct LfsObjectResp {
+    oid: String,
+    #[serde(default)]
+    actions: Option<LfsObjActions>,
+}
+
+#[derive(Deserialize, Clone)]
+struct LfsObjActions {
+    download: LfsAction,
+}
+
+#[derive(Deserialize, Clone)]
+struct LfsAction {
+    href: String,
+    #[serde(default)]
+    header: Option<HashMap<String, String>>,
+}
+
+/// Client for the mod index. Index responses are cached with their ETags so
+/// unchanged indexes are not downloaded again.
+pub struct CatalogClient {
+    http: reqwest::Client,
+    base: String,
+    api: String,
+    project: String,
+    cache_dir: PathBuf,
+}
+
+impl CatalogClient {
+    pub fn new() -> Result<Self, AppError> {
+        Self::for_repository(
+            DEFAULT_INDEX_URL,
+            paths::manager_dir()?.join("mod_index_cache"),
+        )
+    }
+
+    /// A client for the GitLab repository at `url` (`https://host/group/project`).
+    pub fn for_repository(url: &str, cache_dir: PathBuf) -> Result<Self, AppError> {
+        let parsed = reqwest::Url::parse(url).map_err(|e| AppError::InvalidConfig {
+            key: "mod source".to_string(),
+            value: format!("{url}: {e}"),
+        })?;
+        let project = parsed.path().trim_matches('/').trim_end_matches(".git");
+        if project.is_empty() {
+            return Err(AppError::InvalidConfig {
+                key: "mod source".to_string(),
+                value: format!("{url}: no project path"),
+            });
+        }
+        let api = format!("{}/api/v4", parsed.origin().ascii_serialization());
+        Self::with_endpoints(
+            &format!("{}/{project}", parsed.origin().ascii_serialization()),
+            &api,
+            project,
+            cache_dir,
+        )
+    }
+
+    /// A client for another GitLab instance or project, caching under `cache_dir`.
+    pub fn with_endpoints(
+        base: &str,
+        api: &str,
+        project: &str,
+        cache_dir: PathBuf,
+    ) -> Result<Self, AppError> {
+        let http = reqwest::Client::builder()
+            .user_agent(USER_AGENT)
+            .build()
+            .map_err(|e| AppError::NetworkRequest {
+                url: base.to_string(),
+                source: e.to_string(),
+            })?;
+        Ok(Self {
+            http,
+            base: base.trim_end_matches('/').to_string(),
+            api: api.trim_end_matches('/').to_string(),
+            project: project.to_string(),
+            cache_dir,
+        })
+    }
+
+    fn raw_url(&self, branch: &str, path: &str) -> String {
+        format!("{}/-/raw/{branch}/{path}", self.base)
+    }
+
+    fn project_api(&self) -> String {
+        format!(
+            "{}/projects/{}",
+            self.api,
+            urlencoding::encode(&self.project)
+        )
+    }
+
+    /// Raw URL of a mod's thumbnail on the default branch.
+    pub fn thumbnail_url(&self, dir_name: &str) -> String {
+        self.raw_url(
+            BRANCHES[0],
+            &format!("mods/{}/thumbnail.jpg", urlencoding::encode(dir_name)),
+        )
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response, AppError> {
+        self.http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| network_error(url, e))
+    }
+
+    /// The index, preferring the prebuilt `index.json`. Without it, falls back to
+    /// fetching each `meta.json`, then to the archive of the whole `mods` folder.
+    pub async fn fetch_mods(&self) -> Result<Vec<CatalogEntry>, AppError> {
+        use reqwest::header::{ETAG, IF_NONE_MATCH};
+
+        let cache_file = self.cache_dir.join("index_v1.json");
+        let etag_file = self.cache_dir.join("index_v1.etag");
+        let mut etag: Option<String> = std::fs::read_to_string(&etag_file).ok();
+
+        for branch in BRANCHES {
+            let url = self.raw_url(branch, "index.json");
+            let mut req = self.http.get(&url);
+            if let Some(ref v) = etag {
+                req = req.header(IF_NONE_MATCH, v);
+            }
+            let Ok(resp) = req.send().await else {
+                continue;
+            };
+            if resp.status().as_u16() == 304 {
+                if let Some(parsed) = read_json::<IndexFileV1>(&cache_file) {
+                    return Ok(parsed.mods);
+                }
+                // No cache to use; try next URL without etag
+                etag = None;
+                continue;
+            }
+            if resp.status().is_success() {
+                let new_etag = resp
+                    .headers()
+                    .get(ETAG)
+                    .and_then(|v| v.to_str().ok())
+                    .map(|s| s.to_string());
+                let bytes = resp.bytes().await.map_err(|e| network_error(&url, e))?;
+                let parsed: IndexFileV1 = serde_json::from_slice(&bytes).map_err(|e| {
+                    AppError::InvalidApiResponse(format!("Failed to parse index.json: {e}"))
+                })?;
+                let _ = std::fs::create_dir_all(&self.cache_dir);
+                write_json(&cache_file, &parsed);
+                if let Some(et) = new_etag {
+                    let _ = std::fs::write(&etag_file, et);
+                }
+                return Ok(parsed.mods);
+            }
+        }
+
+        // Fallback: meta-only (fast, multi-request) then archive (single, heavy)
+        match self.fetch_meta_only().await {
+            Ok(items) if !items.is_empty() => Ok(items),
+            _ => self.fetch_archive().await,
+        }
+    }
+
+    async fn mod_dirs(&self) -> Result<(&'static str, Vec<String>), AppError> {
+        let mut last_error = None;
+        for branch in BRANCHES {
+            let url = format!(
+                "{}/repository/tree?path=mods&ref={branch}&per_page=500",
+                self.project_api()
+            );
+            match self.get(&url).await {
+                Ok(resp) if resp.status().is_success() => {
+                    let entries: Vec<GitLabTreeEntry> = resp.json().await.map_err(|e| {
+                        AppError::InvalidApiResponse(format!("Parse GitLab tree failed: {e}"))
+                    })?;
+                    let dirs = entries
+                        .into_iter()
+                        .filter(|e| e.r#type == "tree")
+                        .map(|e| e.name)
+                        .collect();
+                    return Ok((branch, dirs));
+                }
+                Ok(resp) => {
+                    last_error = Some(AppError::NetworkRequest {
+                        url,
+                        source: format!("GitLab API status: {}", resp.status()),
+                    })
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            AppError::InvalidApiResponse("No mod directories found".to_string())
+        }))
+    }
+
+    /// Folder names under `mods/` in the index.
+    pub async fn list_mod_dirs(&self) -> Result<Vec<String>, AppError> {
+        Ok(self.mod_dirs().await?.1)
+    }
+
+    /// A file of the index repository, retrying while GitLab rate limits or fails.
+    pub async fn fetch_file(&self, path: &str) -> Result<String, AppError> {
+        // Encode path by segments so slashes remain
+        let encoded: String = path
+            .split('/')
+            .map(urlencoding::encode)
+            .collect::<Vec<_>>()
+            .join("/");
+        let urls = BRANCHES.map(|b| self.raw_url(b, &encoded));
+
+        let mut delay = Duration::from_millis(250);
+        for attempt in 0..4 {
+            for url in &urls {
+                let resp = self.get(url).await?;
+                if resp.status().is_success() {
+                    return resp.text().await.map_err(|e| network_error(url, e));
+                }
+                // 404/410 won't change on retry; 429/5xx might
+            }
+            if attempt < 3 {
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2);
+            }
+        }
+        Err(AppError::NetworkRequest {
+            url: urls[0].clone(),
+            source: format!("Failed to fetch {path} after retries"),
+        })
+    }
+
+    /// The first thumbnail URL that exists for a mod, probed with a one-byte range request.
+    pub async fn find_thumbnail_url(&self, dir_name: &str) -> Result<Option<String>, AppError> {
+        use reqwest::header::RANGE;
+
+        // Try unencoded then encoded, on main then master
+        let enc = urlencoding::encode(dir_name);
+        let candidates = BRANCHES.iter().flat_map(|b| {
+            [
+                self.raw_url(b, &format!("mods/{dir_name}/thumbnail.jpg")),
+                self.raw_url(b, &format!("mods/{enc}/thumbnail.jpg")),
+            ]
+        });
+        for url in candidates {
+            let resp = self
+                .http
+                .get(&url)
+                .header(RANGE, "bytes=0-0")
+                .send()
+                .await
+                .map_err(|e| network_error(&url, e))?;
+            if resp.status().is_success() {
+                return Ok(Some(url));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Metadata of every mod, one `meta.json` request each. Descriptions are left empty.
+    pub async fn fetch_meta_only(&self) -> Result<Vec<CatalogEntry>, AppError> {
+        let t0 = Instant::now();
+        let (branch, mod_dirs) = self.mod_dirs().await?;
+        if mod_dirs.is_empty() {
+            return Err(AppError::InvalidApiResponse(
+                "No mod directories found from GitLab API".to_string(),
+            ));
+        }
+
+        let results = stream::iter(mod_dirs)
+            .map(|dir| async move {
+                let enc = urlencoding::encode(&dir).into_owned();
+                for b in BRANCHES {
+                    let url = self.raw_url(b, &format!("mods/{enc}/meta.json"));
+                    let Ok(resp) = self.get(&url).await else {
+                        continue;
+                    };
+                    if !resp.status().is_success() {
+                        continue;
+                    }
+                    let Ok(text) = resp.text().await else {
+                        continue;
+                    };
+                    if let Ok(meta) = serde_json::from_str::<ModMeta>(&text) {
+                        return Some(CatalogEntry {
+                            image_url: self.raw_url(branch, &format!("mods/{enc}/thumbnail.jpg")),
+                            dir_name: dir,
+                            meta,
+                            description: String::new(),
+                            source: String::new(),
+                        });
+                    }
+                }
+                None
+            })
+            .buffer_unordered(12)
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut items: Vec<CatalogEntry> = results.into_iter().flatten().collect();
+        sort_by_title(&mut items);
+        log::info!(
+            "Fetched meta for {} mods via API in {} ms",
+            items.len(),
+            t0.elapsed().as_millis()
+        );
+        Ok(items)
+    }
+
+    /// Metadata and descriptions of every mod from one archive of the `mods` folder.
+    /// Thumbnails are LFS pointers in the archive, so only their URLs are filled in.
+    pub async fn fetch_archive(&self) -> Result<Vec<CatalogEntry>, AppError> {
+        use reqwest::header::{ETAG, IF_NONE_MATCH};
+
+        let cache_file = self.cache_dir.join("mod_index_archive.json");
+        let mut existing_cache: Option<ArchiveCache> = read_json(&cache_file);
+
+        let mut downloaded = None;
+        let fetch_start = Instant::now();
+        for branch in BRANCHES {
+            let url = self.archive_url(branch);
+            let mut req = self.http.get(&url);
+            if let Some(et) = existing_cache.as_ref().and_then(|c| c.etag.as_ref()) {
+                req = req.header(IF_NONE_MATCH, et);
+            }
+            let resp = match req.send().await {
+                Ok(resp) => resp,
+                Err(e) => {
+                    log::debug!("GitLab archive request error for {url}: {e}");
+                    continue;
+                }
+            };
+            if resp.status().as_u16() == 304 {
+                if let Some(c) = existing_cache {
+                    log::info!(
+                        "GitLab archive 304 Not Modified (branch: {}), using cached items: {}",
+                        c.branch,
+                        c.items.len()
+                    );
+                    return Ok(c.items);
+                }
+                // No cache to use; try next URL branch without ETag
+                existing_cache = None;
+                continue;
+            }
+            if !resp.status().is_success() {
+                log::debug!("GitLab archive status {} for {url}", resp.status());
+                continue;
+            }
+            let etag = resp
+                .headers()
+                .get(ETAG)
+                .and_then(|v| v.to_str().ok())
+                .map(|s| s.to_string());
+            let bytes = resp.bytes().await.map_err(|e| network_error(&url, e))?;
+            log::info!(
+                "GitLab archive downloaded: {} bytes in {} ms (branch: {branch})",
+                bytes.len(),
+                fetch_start.elapsed().as_millis(),
+            );
+            downloaded = Some((branch, etag, bytes));
+            break;
+        }
+
+        let (branch, etag, bytes) = downloaded.ok_or_else(|| AppError::NetworkRequest {
+            url: self.archive_url(BRANCHES[0]),
+            source: "Failed to fetch GitLab archive for main/master".to_string(),
+        })?;
+
+        let parse_start = Instant::now();
+        let items = self.parse_archive(&bytes, branch)?;
+        log::info!(
+            "Parsed {} mods from archive in {} ms",
+            items.len(),
+            parse_start.elapsed().as_millis()
+        );
+
+        let _ = std::fs::create_dir_all(&self.cache_dir);
+        write_json(
+            &cache_file,
+            &ArchiveCache {
+                etag,
+                branch: branch.to_string(),
+                items: items.clone(),
+            },
+        );
+        Ok(items)
+    }
+
+    fn archive_url(&self, branch: &str) -> String {
+        // Limit archive to the `mods` directory only to reduce size significantly
+        format!(
+            "{}/repository/archive.tar.gz?sha={branch}&path=mods",
+            self.project_api()
+        )
+    }
+
+    fn parse_archive(&self, bytes: &[u8], branch: &str) -> Result<Vec<CatalogEntry>, AppError> {
+        #[derive(Default)]
+        struct Parts {
+            meta: Option<ModMeta>,
+            desc: Option<String>,
+        }
+        let mut map: HashMap<String, Parts> = HashMap::new();
+
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+        let entries = archive.entries().map_err(archive_error)?;
+        for entry in entries {
+            let mut entry = entry.map_err(archive_error)?;
+            let Some((dir_name, filename)) = entry.path().ok().and_then(|p| mod_file(&p)) else {
+                continue;
+            };
+            // Skip other files (including images/LFS pointers)
+            if filename != "meta.json" && filename != "description.md" {
+                continue;
+            }
+            let mut buf = String::new();
+            entry.read_to_string(&mut buf).map_err(archive_error)?;
+            let parts = map.entry(dir_name).or_default();
+            if filename == "description.md" {
+                parts.desc = Some(buf);
+            } else if let Ok(meta) = serde_json::from_str::<ModMeta>(&buf) {
+                parts.meta = Some(meta);
+            }
+        }
+
+        let mut out: Vec<CatalogEntry> = map
+            .into_iter()
+            .filter_map(|(dir, parts)| {
+                Some(CatalogEntry {
+                    image_url: self.raw_url(branch, &format!("mods/{dir}/thumbnail.jpg")),
+                    meta: parts.meta?,
+                    description: parts.desc.unwrap_or_default(),
+                    dir_name: dir,
+                    source: String::new(),
+                })
+            })
+            .collect();
+        sort_by_title(&mut out);
+        Ok(out)
+    }
+
+    /// A mod's `description.md`, or `None` if the index has none.
+    pub async fn fetch_description(&self, dir_name: &str) -> Result<Option<String>, AppError> {
+        let enc = urlencoding::encode(dir_name);
+        for b in BRANCHES {
+            let url = self.raw_url(b, &format!("mods/{enc}/description.md"));
+            if let Ok(resp) = self.get(&url).await {
+                if resp.status().is_success() {
+                    if let Ok(text) = resp.text().await {
+                        return Ok(Some(text));
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// A mod's thumbnail image. Fails with `ApiLimitExceeded` when GitLab rate limited
+    /// every attempt, so callers can retry later.
+    pub async fn fetch_thumbnail(&self, dir_name: &str) -> Result<Option<Vec<u8>>, AppError> {
+        let enc = urlencoding::encode(dir_name);
+        let mut rate_limited = false;
+        for b in BRANCHES {
+            let url = self.raw_url(b, &format!("mods/{enc}/thumbnail.jpg"));
+            let Ok(resp) = self.get(&url).await else {
+                continue;
+            };
+            if resp.status().is_success() {
+                if let Ok(bytes) = resp.bytes().await {
+                    return Ok(Some(bytes.to_vec()));
+                }
+            } else if resp.status().as_u16() == 429 {
+                rate_limited = true;
+            }
+        }
+        if rate_limited {
+            Err(AppError::ApiLimitExceeded)
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Download thumbnails through the Git LFS batch API into `dest`, named by
+    /// [`asset_slug`] of each title. Titles that already have a file are skipped.
+    /// Returns how many files were written.
+    pub async fn download_thumbnails(
+        &self,
+        inputs: Vec<ThumbnailRequest>,
+        dest: &Path,
+    ) -> Result<u32, AppError> {
+        let thumb_path = |title: &str| dest.join(format!("{}.jpg", asset_slug(title)));
+        let pending: Vec<ThumbnailRequest> = inputs
+            .into_iter()
+            .filter(|m| !thumb_path(&m.title).exists())
+            .collect();
+        if pending.is_empty() {
+            return Ok(0);
+        }
+
+        let mut dir_to_titles: HashMap<String, Vec<String>> = HashMap::new();
+        for m in &pending {
+            dir_to_titles
+                .entry(m.dir_name.clone())
+                .or_default()
+                .push(m.title.clone());
+        }
+
+        // 1) Read all pointers from a single archive of `mods/`, as (oid, size, titles)
+        let mut pointers: Vec<(String, u64, Vec<String>)> = Vec::new();
+        for branch in BRANCHES {
+            let found = self.archive_pointers(branch, &dir_to_titles).await;
+            if !found.is_empty() {
+                pointers = found
+                    .into_iter()
+                    .map(|(dir, oid, size)| {
+                        let titles = dir_to_titles.get(&dir).cloned().unwrap_or_default();
+                        (oid, size, titles)
+                    })
+                    .collect();
+                break;
+            }
+        }
+
+        // Fallback to per-file pointer fetch if the archive yielded nothing
+        if pointers.is_empty() {
+            pointers = stream::iter(pending)
+                .map(|m| async move {
+                    self.fetch_pointer(&m.dir_name)
+                        .await
+                        .map(|(oid, size)| (oid, size, vec![m.title]))
+                })
+                .buffer_unordered(12)
+                .filter_map(|p| async move { p })
+                .collect()
+                .await;
+        }
+        if pointers.is_empty() {
+            return Ok(0);
+        }
+
+        let mut oid_to_titles: HashMap<String, Vec<String>> = HashMap::new();
+        let mut objects: Vec<LfsObjectSpec> = Vec::new();
+        let mut seen_oid: HashSet<String> = HashSet::new();
+        for (oid, size, titles) in pointers {
+            oid_to_titles.entry(oid.clone()).or_default().extend(titles);
+            if seen_oid.insert(oid.clone()) {
+                objects.push(LfsObjectSpec { oid, size });
+            }
+        }
+
+        // 2) Batch requests of 50 objects, then download each object for its titles
+        let batch_url = format!("{}.git/info/lfs/objects/batch", self.base);
+        let mut total_saved = 0u32;
+        for chunk in objects.chunks(50) {
+            let body = LfsBatchReq {
+                operation: "download",
+                transfers: Some(&["basic"]),
+                objects: chunk.to_vec(),
+            };
+            let resp = self
+                .http
+                .post(&batch_url)
+                .header(
+                    reqwest::header::ACCEPT,
+                    "application/vnd.git-lfs+json; charset=utf-8",
+                )
+                .header(
+                    reqwest::header::CONTENT_TYPE,
+                    "application/vnd.git-lfs+json; charset=utf-8",
+                )
+                .json(&body)
+                .send()
+                .await
+                .map_err(|e| network_error(&batch_url, e))?;
+            if !resp.status().is_success() {
+                return Err(AppError::NetworkRequest {
+                    url: batch_url,
+                    source: format!("LFS batch status: {}", resp.status()),
+                });
+            }
+            let batch: LfsBatchResp = resp.json().await.map_err(|e| {
+                AppError::InvalidApiResponse(format!("Parse LFS batch response failed: {e}"))
+            })?;
+
+            let downloads = batch.objects.into_iter().filter_map(|o| {
+                let action = o.actions?.download;
+                Some((action, oid_to_titles.get(&o.oid)?.clone()))
+            });
+            total_saved += stream::iter(downloads)
+                .map(|(action, titles)| async move {
+                    let mut req = self.http.get(&action.href);
+                    for (k, v) in action.header.unwrap_or_default() {
+                        req = req.header(k, v);
+                    }
+                    let Ok(resp) = req.send().await else {
+                        return 0;
+                    };
+                    if !resp.status().is_success() {
+                        return 0;
+                    }
+                    let Ok(bytes) = resp.bytes().await else {
+                        return 0;
+                    };
+                    titles
+                        .iter()
+                        .filter(|t| std::fs::write(thumb_path(t), &bytes).is_ok())
+                        .count() as u32
+                })
+                .buffer_unordered(8)
+                .fold(0u32, |acc, n| async move { acc + n })
+                .await;
+        }
+        Ok(total_saved)
+    }
+
+    /// LFS pointers of the wanted thumbnails in the branch's `mods/` archive,
+    /// as `(dir, oid, size)`.
+    async fn archive_pointers(
+        &self,
+        branch: &str,
+        wanted: &HashMap<String, Vec<String>>,
+    ) -> Vec<(String, String, u64)> {
+        let url = self.archive_url(branch);
+        let bytes = match self.get(&url).await {
+            Ok(resp) if resp.status().is_success() => match resp.bytes().await {
+                Ok(b) => b,
+                Err(_) => return Vec::new(),
+            },
+            _ => return Vec::new(),
+        };
+
+        let mut found = Vec::new();
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes.as_ref()));
+        let Ok(entries) = archive.entries() else {
+            return found;
+        };
+        for entry in entries {
+            let Ok(mut entry) = entry else {
+                continue;
+            };
+            let Some((dir, filename)) = entry.path().ok().and_then(|p| mod_file(&p)) else {
+                continue;
+            };
+            if filename != "thumbnail.jpg" || !wanted.contains_key(&dir) {
+                continue;
+            }
+            let mut s = String::new();
+            if entry.read_to_string(&mut s).is_err() {
+                continue;
+            }
+            if let Some((oid, size)) = parse_lfs_pointer(&s) {
+                found.push((dir, oid, size));
+                if found.len() >= wanted.len() {
+                    break;
+                }
+            }
+        }
+        found
+    }
+
+    async fn fetch_pointer(&self, dir_name: &str) -> Option<(String, u64)> {
+        // file_path must encode slashes (i.e., use one-shot encode of full path)
+        let file_path = format!("mods/{dir_name}/thumbnail.jpg");
+        let file_enc = urlencoding::encode(&file_path);
+        for b in BRANCHES {
+            let url = format!(
+                "{}/repository/files/{file_enc}/?ref={b}",
+                self.project_api()
+            );
+            let Ok(resp) = self.get(&url).await else {
+                continue;
+            };
+            if !resp.status().is_success() {
+                continue;
+            }
+            let Ok(meta) = resp.json::<GitLabFileContent>().await else {
+                continue;
+            };
+            if !meta.encoding.eq_ignore_ascii_case("base64") {
+                continue;
+            }
+            let text = STANDARD
+                .decode(meta.content)
+                .ok()
+                .and_then(|bytes| String::from_utf8(bytes).ok());
+            if let Some(pointer) = text.as_deref().and_then(parse_lfs_pointer) {
+                return Some(pointer);
+            }
+        }
+        None
+    }
+}
+
+/// File name for a title in the local thumbnail and description caches.
+pub fn asset_slug(input: &str) -> String {
+    let mut s: String = input
+        .trim()
+        .to_lowercase()
+        .chars()
+        .map(|c| if is_legal_char(c) { c } else { '-' })
+        .collect();
+    while s.contains("--") {
+        s = s.replace("--", "-");
+    }
+    s.trim_matches('-').to_string()
+}
+
+fn is_legal_char(c: char) -> bool {
+    c.is_ascii_alphanumeric()
+        || matches!(
+            c,
+            '!' | '#'
+                | '$'
+                | '%'
+                | '&'
+                | '\''
+                | '('
+                | ')'
+                | '+'
+                | ','
+                | '-'
+                | '='
+                | ';'
+                | '@'
+                | '['
+                | ']'
+                | '^'
+                | '_'
+                | '`'
+                | '{'
+                | '}'
+                | '~'
+        )
+}
+
+/// `(oid, size)` of a Git LFS pointer file.
+fn parse_lfs_pointer(ptr_text: &str) -> Option<(String, u64)> {
+    let mut oid = None;
+    let mut size = None;
+    for line in ptr_text.lines() {
+        let line = line.trim();
+        if let Some(rest) = line.strip_prefix("oid ") {
+            // Accept formats like: "sha256:<hex>" or already just the hex
+            let val = rest.trim();
+            oid = Some(val.strip_prefix("sha256:").unwrap_or(val).to_string());
+        } else if let Some(rest) = line.strip_prefix("size ") {
+            size = rest.trim().parse::<u64>().ok();
+        }
+    }
+    Some((oid?, size?))
+}
+
+/// `(dir, file)` for archive paths like `<top>/mods/<dir>/<file>`.
+fn mod_file(path: &Path) -> Option<(String, String)> {
+    let mut comps = path.components().skip(1);
+    if comps.next()?.as_os_str() != "mods" {
+        return None;
+    }
+    let (Some(Component::Normal(dir)), Some(Component::Normal(file))) =
+        (comps.next(), comps.next())
+    else {
+        return None;
+    };
+    Some((
+        dir.to_str()?.to_string(),
+        file.to_string_lossy().into_owned(),
+    ))
+}
+
+fn sort_by_title(items: &mut [CatalogEntry]) {
+    items.sort_by_cached_key(|i| i.meta.title.to_lowercase());
+}
+
+fn read_json<T: for<'de> Deserialize<'de>>(path: &Path) -> Option<T> {
+    let file = std::fs::File::open(path).ok()?;
+    serde_json::from_reader(file).ok()
+}
+
+fn write_json<T: Serialize>(path: &Path, value: &T) {
+    if let Ok(f) = std::fs::File::create(path) {
+        let _ = serde_json::to_writer_pretty(f, value);
+    }
+}
+
+fn network_error(url: &str, e: reqwest::Error) -> AppError {
+    AppError::NetworkRequest {
+        url: url.to_string(),
+        source: e.to_string(),
+    }
+}
+
+fn archive_error(e: std::io::Error) -> AppError {
+    AppError::InvalidApiResponse(format!("Failed to read mod index archive: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tempfile::tempdir;
+
+    const META: &str = r#"{
+        "requires-steamodded": true,
+        "requires-talisman": false,
+        "categories": ["Joker", "Unknown"],
+        "author": "someone",
+        "repo": "https://github.com/someone/cool",
+        "title": "Cool Mod",
+        "downloadURL": "https://example.com/cool.zip",
+        "version": "1.2.0"
+    }"#;
+
+    fn archive(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::fast()));
+        for (path, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, path, data.as_bytes())
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn falls_back_to_archive_and_caches_by_etag() {
+        let td = tempdir().unwrap();
+        let mut server = mockito::Server::new_async().await;
+        let client = CatalogClient::with_endpoints(
+            &format!("{}/index", server.url()),
+            &format!("{}/api", server.url()),
+            "index",
+            td.path().to_path_buf(),
+        )
+        .unwrap();
+
+        let _no_index = server
+            .mock("GET", mockito::Matcher::Regex("index.json$".into()))
+            .with_status(404)
+            .create_async()
+            .await;
+        let _no_tree = server
+            .mock("GET", mockito::Matcher::Regex("/repository/tree".into()))
+            .with_status(500)
+            .create_async()
+            .await;
+        let download = server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex("/repository/archive.tar.gz\\?sha=main".into()),
+            )
+            .with_status(200)
+            .with_header("etag", "\"v1\"")
+            .with_body(archive(&[
+                ("repo-main/mods/Cool/meta.json", META),
+                ("repo-main/mods/Cool/description.md", "# Cool"),
+                ("repo-main/mods/Broken/description.md", "no meta"),
+            ]))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let mods = client.fetch_mods().await.unwrap();
+        assert_eq!(mods.len(), 1);
+        assert_eq!(mods[0].dir_name, "Cool");
+        assert_eq!(mods[0].description, "# Cool");
+        assert!(mods[0]
+            .image_url
+            .ends_with("/-/raw/main/mods/Cool/thumbnail.jpg"));
+        download.assert_async().await;
+        download.remove_async().await;
+
+        let _unchanged = server
+            .mock(
+                "GET",
+                mockito::Matcher::Regex("/repository/archive.tar.gz\\?sha=main".into()),
+            )
+            .match_header("if-none-match", "\"v1\"")
+            .with_status(304)
+            .create_async()
+            .await;
+        let cached = client.fetch_archive().await.unwrap();
+        assert_eq!(cached[0].meta.title, "Cool Mod");
+
+        let m: Mod = cached[0].clone().into();
+        assert_eq!(m.categories, [Category::Joker]);
+        assert_eq!(m.download_url, "https://example.com/cool.zip");
+        assert_eq!(m.version.as_deref(), Some("1.2.0"));
+        assert_eq!(m.folderName, None);
+    }
+
+    #[test]
+    fn parses_lfs_pointers_and_slugs() {
+        let pointer = "version https://git-lfs.github.com/spec/v1\noid sha256:abc123\nsize 42\n";
+        assert_eq!(parse_lfs_pointer(pointer), Some(("abc123".to_string(), 42)));
+        assert_eq!(parse_lfs_pointer("size 42"), None);
+        assert_eq!(asset_slug("  Cool  Mod: Deluxe! "), "cool-mod-deluxe!");
+    }
+}
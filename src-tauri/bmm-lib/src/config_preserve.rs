@@ -0,0 +1,755 @@
+use crate::errors::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File names treated as user configuration when a mod doesn't declare its own list.
+const CONFIG_FILE_NAMES: &[&str] = &[
+    "config.lua",
+    "config.json",
+    "settings.lua",
+    "settings.json",
+    "user_config.lua",
+    "user_config.json",
+];
+
+/// Top-level folders whose whole contents are treated as user configuration.
+const CONFIG_DIR_NAMES: &[&str] = &["config", "configs", "settings"];
+
+/// Manifest key a mod can use to declare its config files explicitly, e.g.
+/// `"config_files": ["config.lua", "presets/user.json"]` in its `*.json` manifest.
+const DECLARED_CONFIG_KEY: &str = "config_files";
+
+/// Files larger than this are never treated as config (and never merged).
+const MAX_CONFIG_FILE_SIZE: u64 = 1024 * 1024;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigResolution {
+    /// The user's file was carried over unchanged.
+    Kept,
+    /// The user never edited the file, so the new default was taken.
+    UpdatedDefault,
+    /// User edits and the new default were merged without conflicts.
+    Merged,
+    /// The user's file was kept; a merge is waiting for the user to resolve.
+    Conflict,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigOutcome {
+    pub path: String,
+    pub resolution: ConfigResolution,
+}
+
+/// A merge the user still has to review. `merged` contains conflict markers.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PendingMerge {
+    pub mod_name: String,
+    pub path: String,
+    /// Default shipped by the previously installed version, if it was recorded.
+    pub base: Option<String>,
+    pub ours: String,
+    pub theirs: String,
+    pub merged: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+struct ModConfigRecord {
+    /// Pristine config files as shipped by the currently installed version.
+    defaults: BTreeMap<String, String>,
+    pending: Vec<PendingMerge>,
+}
+
+/// User config files read from a mod folder before it gets replaced.
+#[derive(Debug, Clone, Default)]
+pub struct PreservedConfigs {
+    files: BTreeMap<String, Vec<u8>>,
+}
+
+impl PreservedConfigs {
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+/// Read every config file of an installed mod so it can be carried across a reinstall.
+pub fn capture(mod_dir: &Path) -> Result<PreservedConfigs, AppError> {
+    let mut files = BTreeMap::new();
+    for rel in discover_config_files(mod_dir) {
+        let path = mod_dir.join(&rel);
+        let data = fs::read(&path).map_err(|e| AppError::FileRead {
+            path: path.clone(),
+            source: e.to_string(),
+        })?;
+        files.insert(rel, data);
+    }
+    Ok(PreservedConfigs { files })
+}
+
+/// The folder of an installed mod named by its install folder name, which must be a
+/// single path component.
+pub fn installed_mod_dir(mods_dir: &Path, mod_name: &str) -> Result<PathBuf, AppError> {
+    if mod_name.is_empty()
+        || mod_name.contains(['/', '\\', ':'])
+        || mod_name == "."
+        || mod_name == ".."
+    {
+        return Err(AppError::invalid_path(mod_name, "Invalid mod folder name"));
+    }
+    Ok(mods_dir.join(mod_name))
+}
+
+/// Relative ('/'-separated) paths of the config files in a mod folder: the
+/// mod's declared list plus anything matching the common patterns.
+pub fn discover_config_files(mod_dir: &Path) -> Vec<String> {
+    let mut found = Vec::new();
+
+    for rel in declared_config_files(mod_dir) {
+        if is_config_candidate(&mod_dir.join(&rel)) {
+            found.push(rel);
+        }
+    }
+
+    for name in CONFIG_FILE_NAMES {
+        if is_config_candidate(&mod_dir.join(name)) && !is_mod_manifest(&mod_dir.join(name)) {
+            found.push(name.to_string());
+        }
+    }
+
+    for dir in CONFIG_DIR_NAMES {
+        let path = mod_dir.join(dir);
+        if path.is_dir() {
+            collect_files(&path, dir, &mut found);
+        }
+    }
+
+    found.sort();
+    found.dedup();
+    found
+}
+
+fn declared_config_files(mod_dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(mod_dir) else {
+        return Vec::new();
+    };
+
+    let mut declared = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Ok(content) = fs::read_to_string(&path) else {
+            continue;
+        };
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&content) else {
+            continue;
+        };
+        if let Some(list) = value.get(DECLARED_CONFIG_KEY).and_then(|v| v.as_array()) {
+            declared.extend(
+                list.iter()
+                    .filter_map(|v| v.as_str())
+                    .map(|s| s.trim_start_matches("./").replace('\\', "/"))
+                    .filter(|s| is_safe_relative(s)),
+            );
+        }
+    }
+    declared
+}
+
+// A `config.json` can also be the mod's own metadata file; that one is not user config.
+fn is_mod_manifest(path: &Path) -> bool {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|c| serde_json::from_str::<serde_json::Value>(&c).ok())
+        .is_some_and(|v| v.get("id").is_some() && v.get("main_file").is_some())
+}
+
+fn is_config_candidate(path: &Path) -> bool {
+    path.metadata()
+        .is_ok_and(|m| m.is_file() && m.len() <= MAX_CONFIG_FILE_SIZE)
+}
+
+fn is_safe_relative(rel: &str) -> bool {
+    !rel.is_empty()
+        && !rel.starts_with('/')
+        && !rel.contains(':')
+        && rel
+            .split('/')
+            .all(|p| !p.is_empty() && p != "." && p != "..")
+}
+
+fn collect_files(dir: &Path, rel: &str, out: &mut Vec<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let child_rel = format!("{rel}/{}", entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            collect_files(&path, &child_rel, out);
+        } else if is_config_candidate(&path) {
+            out.push(child_rel);
+        }
+    }
+}
+
+/// Remembers the config defaults each mod shipped with and the merges still
+/// waiting for the user, one JSON file per mod.
+pub struct ConfigStore {
+    root: PathBuf,
+}
+
+impl ConfigStore {
+    pub fn new() -> Result<Self, AppError> {
+        let root = crate::paths::manager_dir()?.join("config_store");
+        Ok(Self::with_root(root))
+    }
+
+    pub fn with_root(root: PathBuf) -> Self {
+        Self { root }
+    }
+
+    /// Remember the config files a freshly installed mod shipped with, to use as
+    /// the merge base on its next update.
+    pub fn record_defaults(&self, mod_name: &str, mod_dir: &Path) -> Result<(), AppError> {
+        let mut record = self.load(mod_name)?;
+        record.defaults = read_text_files(mod_dir, &discover_config_files(mod_dir));
+        self.save(mod_name, &record)
+    }
+
+    /// Put the user's config files back into a freshly installed mod folder.
+    ///
+    /// Each file is resolved against the default shipped by the previous version:
+    /// untouched files take the new default, files whose default didn't change keep
+    /// the user's copy, and everything else goes through a three-way merge. Merges
+    /// that conflict keep the user's file and are stored as pending. Without a recorded
+    /// default the user's copy is kept as is.
+    pub fn carry_over(
+        &self,
+        mod_name: &str,
+        mod_dir: &Path,
+        preserved: PreservedConfigs,
+    ) -> Result<Vec<ConfigOutcome>, AppError> {
+        let mut record = self.load(mod_name)?;
+        let old_defaults = std::mem::take(&mut record.defaults);
+
+        // Snapshot the new defaults before any user file is written back
+        let mut new_files = discover_config_files(mod_dir);
+        new_files.extend(preserved.files.keys().cloned());
+        new_files.sort();
+        new_files.dedup();
+        record.defaults = read_text_files(mod_dir, &new_files);
+
+        let mut outcomes = Vec::new();
+        for (rel, ours_bytes) in preserved.files {
+            record.pending.retain(|p| p.path != rel);
+            let dest = mod_dir.join(&rel);
+            let theirs_bytes = fs::read(&dest).ok();
+
+            let resolution = match theirs_bytes {
+                Some(theirs_bytes) if theirs_bytes == ours_bytes => ConfigResolution::Kept,
+                None => {
+                    write_file(&dest, &ours_bytes)?;
+                    ConfigResolution::Kept
+                }
+                Some(theirs_bytes) => {
+                    let base = old_defaults.get(&rel);
+                    let texts = (
+                        String::from_utf8(ours_bytes.clone()),
+                        String::from_utf8(theirs_bytes),
+                    );
+                    match texts {
+                        (Ok(ours), Ok(theirs)) => {
+                            let (resolution, content) =
+                                resolve_text(&mut record, mod_name, &rel, base, ours, theirs);
+                            write_file(&dest, content.as_bytes())?;
+                            resolution
+                        }
+                        // Binary config: there's nothing to merge, the user's copy wins
+                        _ => {
+                            write_file(&dest, &ours_bytes)?;
+                            ConfigResolution::Kept
+                        }
+                    }
+                }
+            };
+
+            log::info!("Config file {rel} of {mod_name}: {resolution:?}");
+            outcomes.push(ConfigOutcome {
+                path: rel,
+                resolution,
+            });
+        }
+
+        self.save(mod_name, &record)?;
+        Ok(outcomes)
+    }
+
+    /// Merges waiting for review, across all mods.
+    pub fn pending(&self) -> Result<Vec<PendingMerge>, AppError> {
+        let Ok(entries) = fs::read_dir(&self.root) else {
+            return Ok(Vec::new());
+        };
+        let mut pending = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) == Some("json") {
+                pending.extend(read_record(&path)?.pending);
+            }
+        }
+        pending.sort_by(|a, b| (&a.mod_name, &a.path).cmp(&(&b.mod_name, &b.path)));
+        Ok(pending)
+    }
+
+    /// Write the user's chosen content for a pending merge and drop it from the queue.
+    pub fn resolve(
+        &self,
+        mod_name: &str,
+        mod_dir: &Path,
+        path: &str,
+        content: &str,
+    ) -> Result<(), AppError> {
+        if !is_safe_relative(path) {
+            return Err(AppError::invalid_path(
+                path,
+                "Path traversal attempt detected",
+            ));
+        }
+        let mut record = self.load(mod_name)?;
+        let before = record.pending.len();
+        record.pending.retain(|p| p.path != path);
+        if record.pending.len() == before {
+            return Err(AppError::InvalidState(format!(
+                "No pending config merge for {path} in {mod_name}"
+            )));
+        }
+        write_file(&mod_dir.join(path), content.as_bytes())?;
+        self.save(mod_name, &record)
+    }
+
+    /// Forget everything about a mod, e.g. after it was uninstalled.
+    pub fn forget(&self, mod_name: &str) -> Result<(), AppError> {
+        let path = self.record_path(mod_name);
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| AppError::FileWrite {
+                path,
+                source: e.to_string(),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn record_path(&self, mod_name: &str) -> PathBuf {
+        let safe: String = mod_name
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' || c == '_' || c == '.' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.root.join(format!("{safe}.json"))
+    }
+
+    fn load(&self, mod_name: &str) -> Result<ModConfigRecord, AppError> {
+        let path = self.record_path(mod_name);
+        if !path.exists() {
+            return Ok(ModConfigRecord::default());
+        }
+        read_record(&path)
+    }
+
+    fn save(&self, mod_name: &str, record: &ModConfigRecord) -> Result<(), AppError> {
+        fs::create_dir_all(&self.root).map_err(|e| AppError::DirCreate {
+            path: self.root.clone(),
+            source: e.to_string(),
+        })?;
+        let path = self.record_path(mod_name);
+        fs::write(&path, serde_json::to_vec_pretty(record)?).map_err(|e| AppError::FileWrite {
+            path,
+            source: e.to_string(),
+        })
+    }
+}
+
+// Returns the resolution and the content that should end up on disk.
+fn resolve_text(
+    record: &mut ModConfigRecord,
+    mod_name: &str,
+    rel: &str,
+    base: Option<&String>,
+    ours: String,
+    theirs: String,
+) -> (ConfigResolution, String) {
+    match base {
+        Some(base) if *base == ours => (ConfigResolution::UpdatedDefault, theirs),
+        Some(base) if *base == theirs => (ConfigResolution::Kept, ours),
+        Some(base) => {
+            let merge = merge3(base, &ours, &theirs);
+            if merge.conflicts == 0 {
+                (ConfigResolution::Merged, merge.text)
+            } else {
+                record.pending.push(PendingMerge {
+                    mod_name: mod_name.to_string(),
+                    path: rel.to_string(),
+                    base: Some(base.clone()),
+                    merged: merge.text,
+                    ours: ours.clone(),
+                    theirs,
+                });
+                (ConfigResolution::Conflict, ours)
+            }
+        }
+        // Installed before defaults were recorded: we can't tell what the user changed,
+        // so their file stays and the new default becomes the base for the next update
+        None => (ConfigResolution::Kept, ours),
+    }
+}
+
+fn read_record(path: &Path) -> Result<ModConfigRecord, AppError> {
+    let content = fs::read_to_string(path).map_err(|e| AppError::FileRead {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    serde_json::from_str(&content).map_err(|e| AppError::JsonParse {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })
+}
+
+fn read_text_files(mod_dir: &Path, files: &[String]) -> BTreeMap<String, String> {
+    files
+        .iter()
+        .filter_map(|rel| {
+            let content = fs::read_to_string(mod_dir.join(rel)).ok()?;
+            Some((rel.clone(), content))
+        })
+        .collect()
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::DirCreate {
+            path: parent.to_path_buf(),
+            source: e.to_string(),
+        })?;
+    }
+    fs::write(path, data).map_err(|e| AppError::FileWrite {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeResult {
+    pub text: String,
+    pub conflicts: usize,
+}
+
+/// Line-based three-way merge (diff3). Conflicting hunks are written with
+/// git-style markers, the installed copy first.
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> MergeResult {
+    let base: Vec<&str> = base.split_inclusive('\n').collect();
+    let ours: Vec<&str> = ours.split_inclusive('\n').collect();
+    let theirs: Vec<&str> = theirs.split_inclusive('\n').collect();
+    let to_ours = lcs_matches(&base, &ours);
+    let to_theirs = lcs_matches(&base, &theirs);
+
+    let mut text = String::new();
+    let mut conflicts = 0;
+    let (mut b, mut o, mut t) = (0, 0, 0);
+
+    while b < base.len() || o < ours.len() || t < theirs.len() {
+        if b < base.len() && to_ours[b] == Some(o) && to_theirs[b] == Some(t) {
+            text.push_str(base[b]);
+            b += 1;
+            o += 1;
+            t += 1;
+            continue;
+        }
+
+        // Next base line both sides still agree on ends the unstable hunk
+        let next = (b..base.len()).find(|&k| to_ours[k].is_some() && to_theirs[k].is_some());
+        let (b_end, o_end, t_end) = match next {
+            Some(k) => (k, to_ours[k].unwrap_or(o), to_theirs[k].unwrap_or(t)),
+            None => (base.len(), ours.len(), theirs.len()),
+        };
+
+        let base_hunk = &base[b..b_end];
+        let ours_hunk = &ours[o..o_end];
+        let theirs_hunk = &theirs[t..t_end];
+
+        if ours_hunk == base_hunk {
+            text.extend(theirs_hunk.iter().copied());
+        } else if theirs_hunk == base_hunk || ours_hunk == theirs_hunk {
+            text.extend(ours_hunk.iter().copied());
+        } else {
+            conflicts += 1;
+            push_marker_block(&mut text, "<<<<<<< installed", ours_hunk);
+            push_marker_block(&mut text, "||||||| previous default", base_hunk);
+            push_marker_block(&mut text, "=======", theirs_hunk);
+            text.push_str(">>>>>>> new default\n");
+        }
+
+        b = b_end;
+        o = o_end;
+        t = t_end;
+    }
+
+    MergeResult { text, conflicts }
+}
+
+fn push_marker_block(text: &mut String, marker: &str, lines: &[&str]) {
+    text.push_str(marker);
+    text.push('\n');
+    for line in lines {
+        text.push_str(line);
+        if !line.ends_with('\n') {
+            text.push('\n');
+        }
+    }
+}
+
+// For every line of `a`, the index of the line of `b` it's paired with in a longest
+// common subsequence. Hirschberg's divide and conquer keeps memory linear, so even
+// files at the size limit stay cheap to diff.
+fn lcs_matches(a: &[&str], b: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; a.len()];
+    match_range(a, b, 0, 0, &mut matches);
+    matches
+}
+
+fn match_range(a: &[&str], b: &[&str], a_start: usize, b_start: usize, out: &mut [Option<usize>]) {
+    // Lines shared at either end are always part of a longest common subsequence
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    for k in 0..prefix {
+        out[a_start + k] = Some(b_start + k);
+    }
+    let (a, b) = (&a[prefix..], &b[prefix..]);
+    let (a_start, b_start) = (a_start + prefix, b_start + prefix);
+    let suffix = a
+        .iter()
+        .rev()
+        .zip(b.iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    for k in 1..=suffix {
+        out[a_start + a.len() - k] = Some(b_start + b.len() - k);
+    }
+    let (a, b) = (&a[..a.len() - suffix], &b[..b.len() - suffix]);
+
+    if a.is_empty() || b.is_empty() {
+        return;
+    }
+    if a.len() == 1 {
+        if let Some(j) = b.iter().position(|line| *line == a[0]) {
+            out[a_start] = Some(b_start + j);
+        }
+        return;
+    }
+
+    // Split `b` where the halves of `a` together keep the longest common subsequence
+    let mid = a.len() / 2;
+    let forward = lcs_lengths(a[..mid].iter(), b.iter());
+    let backward = lcs_lengths(a[mid..].iter().rev(), b.iter().rev());
+    let split = (0..=b.len())
+        .max_by_key(|&j| (forward[j] + backward[b.len() - j], std::cmp::Reverse(j)))
+        .unwrap_or(0);
+
+    match_range(&a[..mid], &b[..split], a_start, b_start, out);
+    match_range(&a[mid..], &b[split..], a_start + mid, b_start + split, out);
+}
+
+// Last row of the LCS table: entry `j` is the LCS length of `a` and the first `j` lines of `b`
+fn lcs_lengths<'a>(
+    a: impl Iterator<Item = &'a &'a str>,
+    b: impl Iterator<Item = &'a &'a str> + Clone,
+) -> Vec<u32> {
+    let mut row = vec![0u32; b.clone().count() + 1];
+    for x in a {
+        let mut diagonal = 0;
+        for (j, y) in b.clone().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if x == y {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn merge3_combines_independent_edits() {
+        let base = "a = 1\nb = 2\nc = 3\n";
+        let ours = "a = 10\nb = 2\nc = 3\n";
+        let theirs = "a = 1\nb = 2\nc = 3\nd = 4\n";
+        let merged = merge3(base, ours, theirs);
+        assert_eq!(merged.conflicts, 0);
+        assert_eq!(merged.text, "a = 10\nb = 2\nc = 3\nd = 4\n");
+    }
+
+    #[test]
+    fn installed_mod_dir_stays_inside_the_mods_folder() {
+        let mods = Path::new("/mods");
+        assert_eq!(
+            installed_mod_dir(mods, "Steamodded").unwrap(),
+            mods.join("Steamodded")
+        );
+        for name in ["", "..", ".", "../Saves", "a/b", "a\\b", "C:x"] {
+            assert!(installed_mod_dir(mods, name).is_err(), "{name:?}");
+        }
+    }
+
+    #[test]
+    fn merge3_marks_conflicting_edits() {
+        let merged = merge3("x = 1\n", "x = 2\n", "x = 3\n");
+        assert_eq!(merged.conflicts, 1);
+        assert!(merged.text.contains("<<<<<<< installed\nx = 2\n"));
+        assert!(merged
+            .text
+            .contains("=======\nx = 3\n>>>>>>> new default\n"));
+    }
+
+    #[test]
+    fn lcs_matches_find_a_longest_subsequence() {
+        let a = ["a", "b", "c", "a", "b", "b", "a"];
+        let b = ["c", "b", "a", "b", "a", "c"];
+        let matches = lcs_matches(&a, &b);
+        let pairs: Vec<_> = matches
+            .iter()
+            .enumerate()
+            .filter_map(|(i, j)| Some((i, (*j)?)))
+            .collect();
+        assert_eq!(pairs.len(), 4);
+        assert!(pairs.iter().all(|&(i, j)| a[i] == b[j]));
+        assert!(pairs.windows(2).all(|w| w[0].1 < w[1].1));
+
+        // Large files merge without a quadratic table
+        let base: String = (0..20_000).map(|i| format!("key_{i} = {i}\n")).collect();
+        let ours = base.replacen("key_10 = 10\n", "key_10 = 11\n", 1);
+        let theirs = format!("{base}key_new = 1\n");
+        let merged = merge3(&base, &ours, &theirs);
+        assert_eq!(merged.conflicts, 0);
+        assert!(merged.text.contains("key_10 = 11\n"));
+        assert!(merged.text.ends_with("key_new = 1\n"));
+    }
+
+    #[test]
+    fn discovers_declared_and_common_config_files() {
+        let td = tempdir().unwrap();
+        let mod_dir = td.path();
+        fs::write(
+            mod_dir.join("mod.json"),
+            r#"{"id": "m", "main_file": "m.lua", "config_files": ["presets/user.txt", "../evil"]}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(mod_dir.join("presets")).unwrap();
+        fs::write(mod_dir.join("presets/user.txt"), "u").unwrap();
+        fs::write(mod_dir.join("config.lua"), "return {}").unwrap();
+        fs::create_dir_all(mod_dir.join("config")).unwrap();
+        fs::write(mod_dir.join("config/keys.json"), "{}").unwrap();
+        fs::write(mod_dir.join("main.lua"), "").unwrap();
+
+        assert_eq!(
+            discover_config_files(mod_dir),
+            vec!["config.lua", "config/keys.json", "presets/user.txt"]
+        );
+    }
+
+    #[test]
+    fn carry_over_resolves_against_previous_default() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let store = ConfigStore::with_root(td.path().join("store"));
+        let mod_dir = td.path().join("Mods").join("CoolMod");
+        fs::create_dir_all(mod_dir.join("config")).unwrap();
+
+        // v1 ships its defaults
+        fs::write(mod_dir.join("config.lua"), "a = 1\nb = 2\n").unwrap();
+        fs::write(mod_dir.join("config/untouched.json"), "{}\n").unwrap();
+        fs::write(mod_dir.join("config/clash.lua"), "x = 1\n").unwrap();
+        store.record_defaults("CoolMod", &mod_dir)?;
+
+        // The user edits two files
+        fs::write(mod_dir.join("config.lua"), "a = 5\nb = 2\n").unwrap();
+        fs::write(mod_dir.join("config/clash.lua"), "x = 2\n").unwrap();
+        let preserved = capture(&mod_dir)?;
+
+        // v2 replaces the folder
+        fs::remove_dir_all(&mod_dir).unwrap();
+        fs::create_dir_all(mod_dir.join("config")).unwrap();
+        fs::write(mod_dir.join("config.lua"), "a = 1\nb = 2\nc = 3\n").unwrap();
+        fs::write(mod_dir.join("config/untouched.json"), "{\"v\": 2}\n").unwrap();
+        fs::write(mod_dir.join("config/clash.lua"), "x = 3\n").unwrap();
+
+        let outcomes = store.carry_over("CoolMod", &mod_dir, preserved)?;
+        let resolution_of = |p: &str| outcomes.iter().find(|o| o.path == p).map(|o| o.resolution);
+        assert_eq!(resolution_of("config.lua"), Some(ConfigResolution::Merged));
+        assert_eq!(
+            resolution_of("config/untouched.json"),
+            Some(ConfigResolution::UpdatedDefault)
+        );
+        assert_eq!(
+            resolution_of("config/clash.lua"),
+            Some(ConfigResolution::Conflict)
+        );
+
+        let read = |p: &str| fs::read_to_string(mod_dir.join(p)).unwrap();
+        assert_eq!(read("config.lua"), "a = 5\nb = 2\nc = 3\n");
+        assert_eq!(read("config/untouched.json"), "{\"v\": 2}\n");
+        assert_eq!(read("config/clash.lua"), "x = 2\n");
+
+        let pending = store.pending()?;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].theirs, "x = 3\n");
+
+        store.resolve("CoolMod", &mod_dir, "config/clash.lua", "x = 3\n")?;
+        assert_eq!(read("config/clash.lua"), "x = 3\n");
+        assert!(store.pending()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn carry_over_without_recorded_defaults_keeps_user_files() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let store = ConfigStore::with_root(td.path().join("store"));
+        let mod_dir = td.path().join("Mods").join("OldMod");
+        fs::create_dir_all(&mod_dir).unwrap();
+
+        // Installed before defaults were recorded, then edited by the user
+        fs::write(mod_dir.join("config.lua"), "a = 5\nb = 2\n").unwrap();
+        let preserved = capture(&mod_dir)?;
+        fs::write(mod_dir.join("config.lua"), "a = 1\nb = 2\n").unwrap();
+
+        let outcomes = store.carry_over("OldMod", &mod_dir, preserved)?;
+        assert_eq!(outcomes[0].resolution, ConfigResolution::Kept);
+        assert_eq!(
+            fs::read_to_string(mod_dir.join("config.lua")).unwrap(),
+            "a = 5\nb = 2\n"
+        );
+        assert!(store.pending()?.is_empty());
+
+        // The new default is the base next time, so the user's edit merges cleanly
+        let preserved = capture(&mod_dir)?;
+        fs::write(mod_dir.join("config.lua"), "a = 1\nb = 2\nc = 3\n").unwrap();
+        let outcomes = store.carry_over("OldMod", &mod_dir, preserved)?;
+        assert_eq!(outcomes[0].resolution, ConfigResolution::Merged);
+        assert_eq!(
+            fs::read_to_string(mod_dir.join("config.lua")).unwrap(),
+            "a = 5\nb = 2\nc = 3\n"
+        );
+        Ok(())
+    }
+}
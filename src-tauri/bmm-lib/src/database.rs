@@ -1,8 +1,10 @@
 // use crate::cache::Mod;
 use crate::errors::AppError;
-use rusqlite::Connection;
-use serde::Serialize;
-use std::path::PathBuf;
+use crate::lovely::LinuxLaunchMode;
+use crate::stores::LaunchMethod;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
 
 pub struct Database {
     conn: Connection,
@@ -16,13 +18,167 @@ pub struct InstalledMod {
     pub current_version: Option<String>,
 }
 
+#[derive(Serialize, Debug, Clone)]
+pub struct ModBackup {
+    pub id: i64,
+    pub mod_name: String,
+    pub version: Option<String>,
+    pub original_path: String,
+    pub backup_path: String,
+    pub size_bytes: u64,
+    pub reason: String,
+    pub created_at: u64,
+}
+
+/// Where a registered Balatro install comes from.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallPlatform {
+    Steam,
+    SteamBeta,
+    DrmFree,
+    Gog,
+    GamePass,
+    Itch,
+    Other,
+}
+
+impl InstallPlatform {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            InstallPlatform::Steam => "steam",
+            InstallPlatform::SteamBeta => "steam_beta",
+            InstallPlatform::DrmFree => "drm_free",
+            InstallPlatform::Gog => "gog",
+            InstallPlatform::GamePass => "game_pass",
+            InstallPlatform::Itch => "itch",
+            InstallPlatform::Other => "other",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "steam" => Some(InstallPlatform::Steam),
+            "steam_beta" => Some(InstallPlatform::SteamBeta),
+            "drm_free" => Some(InstallPlatform::DrmFree),
+            "gog" => Some(InstallPlatform::Gog),
+            "game_pass" => Some(InstallPlatform::GamePass),
+            "itch" => Some(InstallPlatform::Itch),
+            "other" => Some(InstallPlatform::Other),
+            _ => None,
+        }
+    }
+
+    fn guess(path: &str) -> Self {
+        if path.to_lowercase().contains("steamapps") {
+            InstallPlatform::Steam
+        } else {
+            InstallPlatform::Other
+        }
+    }
+}
+
+/// A registered Balatro install. Launch settings are tracked per install, and so is Lovely
+/// unless the install shares the one Lovely library in the bins dir (see
+/// `shares_lovely_binary`); the active one is what every path lookup resolves to.
+#[derive(Serialize, Debug, Clone)]
+pub struct Installation {
+    pub id: i64,
+    pub label: String,
+    pub path: String,
+    pub platform: InstallPlatform,
+    pub active: bool,
+    pub lovely_version: Option<String>,
+    pub lovely_pinned: bool,
+    pub linux_launch_mode: Option<String>,
+    /// How the store the install came from wants it started; `None` until known.
+    pub launch_method: Option<LaunchMethod>,
+}
+
+/// What kind of mod index a configured source points at.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SourceKind {
+    /// A GitLab repository laid out like the community index; location is its URL.
+    Gitlab,
+    /// An `index.json` served over HTTP; location is its URL.
+    IndexUrl,
+    /// A folder holding an `index.json` or a `mods/<name>/meta.json` tree.
+    LocalDir,
+    /// A Thunderstore community; location is its URL (`https://thunderstore.io/c/balatro`).
+    Thunderstore,
+}
+
+impl SourceKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SourceKind::Gitlab => "gitlab",
+            SourceKind::IndexUrl => "index_url",
+            SourceKind::LocalDir => "local_dir",
+            SourceKind::Thunderstore => "thunderstore",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "gitlab" => Some(SourceKind::Gitlab),
+            "index_url" => Some(SourceKind::IndexUrl),
+            "local_dir" => Some(SourceKind::LocalDir),
+            "thunderstore" => Some(SourceKind::Thunderstore),
+            _ => None,
+        }
+    }
+}
+
+/// A mod index the catalog is built from. Sources are consulted by ascending priority;
+/// when two list the same mod, the earlier one wins.
+#[derive(Serialize, Debug, Clone)]
+pub struct ModSourceConfig {
+    pub id: i64,
+    pub name: String,
+    pub kind: SourceKind,
+    pub location: String,
+    pub priority: i64,
+    pub enabled: bool,
+}
+
+/// Where a mod installed from a GitHub or GitLab repository came from, so updates
+/// can be checked against the repository later.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RepoOrigin {
+    pub mod_name: String,
+    pub repo_url: String,
+    /// Branch, tag or commit that was installed.
+    pub git_ref: String,
+    /// Release asset installed instead of the source archive.
+    pub asset: Option<String>,
+}
+
+/// The Thunderstore package a mod was installed from. The name it was installed
+/// under is kept even if another namespace publishes the same package name later.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct ThunderstoreOrigin {
+    pub mod_name: String,
+    pub namespace: String,
+    pub package: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct LaunchRecord {
+    pub id: i64,
+    pub launch_mode: String,
+    pub started_at: u64,
+    pub ended_at: Option<u64>,
+    pub exit_code: Option<i32>,
+    pub crashed: bool,
+    pub crash_message: Option<String>,
+}
+
 impl Database {
-    const CURRENT_DB_VERSION: &'static str = "1.2"; // Update this when schema changes
+    const CURRENT_DB_VERSION: &'static str = "1.8"; // Update this when schema changes
 
     pub fn new() -> Result<Self, AppError> {
-        let config_dir = dirs::config_dir()
-            .ok_or_else(|| AppError::DirNotFound(PathBuf::from("config directory")))?;
-        let balatro_dir = config_dir.join("Balatro");
+        let balatro_dir = crate::paths::manager_dir()?;
         let storage_path = balatro_dir.join("bmm_storage.db");
 
         // Create directory if it doesn't exist
@@ -164,6 +320,13 @@ impl Database {
             // Migrate data
             Self::migrate_settings(&old_conn, &new_conn)?;
             Self::migrate_installed_mods(&old_conn, &new_conn)?;
+            Self::migrate_mod_backups(&old_conn, &new_conn)?;
+            Self::migrate_launch_sessions(&old_conn, &new_conn)?;
+            Self::migrate_installations(&old_conn, &new_conn)?;
+            Self::migrate_mod_sources(&old_conn, &new_conn)?;
+            Self::migrate_repo_origins(&old_conn, &new_conn)?;
+            Self::migrate_thunderstore_origins(&old_conn, &new_conn)?;
+            Self::seed_installations_from_settings(&new_conn)?;
 
             // IMPORTANT: Explicitly close connections before file operations
             drop(old_conn);
@@ -298,6 +461,332 @@ impl Database {
         Ok(())
     }
 
+    fn migrate_mod_backups(old_conn: &Connection, new_conn: &Connection) -> Result<(), AppError> {
+        let has_mod_backups = match old_conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='mod_backups'",
+            [],
+            |row| row.get::<_, i64>(0),
+        ) {
+            Ok(count) => count > 0,
+            Err(_) => false,
+        };
+
+        if !has_mod_backups {
+            return Ok(());
+        }
+
+        let mut stmt = match old_conn.prepare(
+            "SELECT id, mod_name, version, original_path, backup_path, size_bytes, reason, created_at FROM mod_backups",
+        ) {
+            Ok(stmt) => stmt,
+            Err(_) => return Ok(()),
+        };
+
+        for backup in stmt.query_map([], Self::row_to_mod_backup)?.flatten() {
+            new_conn.execute(
+                "INSERT INTO mod_backups (id, mod_name, version, original_path, backup_path, size_bytes, reason, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    backup.id,
+                    backup.mod_name,
+                    backup.version,
+                    backup.original_path,
+                    backup.backup_path,
+                    backup.size_bytes as i64,
+                    backup.reason,
+                    backup.created_at as i64
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn migrate_launch_sessions(
+        old_conn: &Connection,
+        new_conn: &Connection,
+    ) -> Result<(), AppError> {
+        let has_launch_sessions = match old_conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='launch_sessions'",
+            [],
+            |row| row.get::<_, i64>(0),
+        ) {
+            Ok(count) => count > 0,
+            Err(_) => false,
+        };
+
+        if !has_launch_sessions {
+            return Ok(());
+        }
+
+        let mut stmt = match old_conn.prepare(
+            "SELECT id, launch_mode, started_at, ended_at, exit_code, crashed, crash_message FROM launch_sessions",
+        ) {
+            Ok(stmt) => stmt,
+            Err(_) => return Ok(()),
+        };
+
+        for session in stmt.query_map([], Self::row_to_launch_record)?.flatten() {
+            new_conn.execute(
+                "INSERT INTO launch_sessions (id, launch_mode, started_at, ended_at, exit_code, crashed, crash_message)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    session.id,
+                    session.launch_mode,
+                    session.started_at as i64,
+                    session.ended_at.map(|t| t as i64),
+                    session.exit_code,
+                    session.crashed,
+                    session.crash_message
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn migrate_installations(old_conn: &Connection, new_conn: &Connection) -> Result<(), AppError> {
+        let has_installations = match old_conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='installations'",
+            [],
+            |row| row.get::<_, i64>(0),
+        ) {
+            Ok(count) => count > 0,
+            Err(_) => false,
+        };
+
+        if !has_installations {
+            return Ok(());
+        }
+
+        // Databases before 1.8 have no launch_method column
+        let launch_column = if old_conn
+            .prepare("SELECT launch_method FROM installations LIMIT 0")
+            .is_ok()
+        {
+            "launch_method"
+        } else {
+            "NULL"
+        };
+        let mut stmt = match old_conn.prepare(&format!(
+            "SELECT id, label, path, platform, active, lovely_version, lovely_pinned, linux_launch_mode, {launch_column}
+             FROM installations"
+        )) {
+            Ok(stmt) => stmt,
+            Err(_) => return Ok(()),
+        };
+
+        for install in stmt.query_map([], Self::row_to_installation)?.flatten() {
+            new_conn.execute(
+                "INSERT INTO installations (id, label, path, platform, active, lovely_version, lovely_pinned, linux_launch_mode, launch_method)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    install.id,
+                    install.label,
+                    install.path,
+                    install.platform.as_str(),
+                    install.active,
+                    install.lovely_version,
+                    install.lovely_pinned,
+                    install.linux_launch_mode,
+                    Self::launch_method_json(install.launch_method.as_ref())?
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn migrate_mod_sources(old_conn: &Connection, new_conn: &Connection) -> Result<(), AppError> {
+        let has_sources = match old_conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='mod_sources'",
+            [],
+            |row| row.get::<_, i64>(0),
+        ) {
+            Ok(count) => count > 0,
+            Err(_) => false,
+        };
+
+        if !has_sources {
+            return Ok(());
+        }
+
+        let mut stmt = match old_conn.prepare(&format!(
+            "SELECT {} FROM mod_sources",
+            Self::MOD_SOURCE_COLUMNS
+        )) {
+            Ok(stmt) => stmt,
+            Err(_) => return Ok(()),
+        };
+
+        // Replaces the default source seeded into the new database
+        new_conn.execute("DELETE FROM mod_sources", [])?;
+        for source in stmt.query_map([], Self::row_to_mod_source)?.flatten() {
+            new_conn.execute(
+                "INSERT INTO mod_sources (id, name, kind, location, priority, enabled)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    source.id,
+                    source.name,
+                    source.kind.as_str(),
+                    source.location,
+                    source.priority,
+                    source.enabled
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn migrate_repo_origins(old_conn: &Connection, new_conn: &Connection) -> Result<(), AppError> {
+        let has_origins = match old_conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='repo_origins'",
+            [],
+            |row| row.get::<_, i64>(0),
+        ) {
+            Ok(count) => count > 0,
+            Err(_) => false,
+        };
+
+        if !has_origins {
+            return Ok(());
+        }
+
+        let mut stmt = match old_conn.prepare(&format!(
+            "SELECT {} FROM repo_origins",
+            Self::REPO_ORIGIN_COLUMNS
+        )) {
+            Ok(stmt) => stmt,
+            Err(_) => return Ok(()),
+        };
+
+        for origin in stmt.query_map([], Self::row_to_repo_origin)?.flatten() {
+            new_conn.execute(
+                "INSERT INTO repo_origins (mod_name, repo_url, git_ref, asset) VALUES (?1, ?2, ?3, ?4)",
+                params![origin.mod_name, origin.repo_url, origin.git_ref, origin.asset],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    fn migrate_thunderstore_origins(
+        old_conn: &Connection,
+        new_conn: &Connection,
+    ) -> Result<(), AppError> {
+        let has_origins = match old_conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type='table' AND name='thunderstore_origins'",
+            [],
+            |row| row.get::<_, i64>(0),
+        ) {
+            Ok(count) => count > 0,
+            Err(_) => false,
+        };
+
+        if !has_origins {
+            return Ok(());
+        }
+
+        let mut stmt = match old_conn.prepare(&format!(
+            "SELECT {} FROM thunderstore_origins",
+            Self::THUNDERSTORE_ORIGIN_COLUMNS
+        )) {
+            Ok(stmt) => stmt,
+            Err(_) => return Ok(()),
+        };
+
+        for origin in stmt
+            .query_map([], Self::row_to_thunderstore_origin)?
+            .flatten()
+        {
+            new_conn.execute(
+                "INSERT INTO thunderstore_origins (mod_name, namespace, package) VALUES (?1, ?2, ?3)",
+                params![origin.mod_name, origin.namespace, origin.package],
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Older databases kept one install in the `installation_path` setting (with Lovely
+    /// state and launch mode next to it); turn that into the active registered install.
+    fn seed_installations_from_settings(conn: &Connection) -> Result<(), AppError> {
+        let count: i64 =
+            conn.query_row("SELECT COUNT(*) FROM installations", [], |row| row.get(0))?;
+        if count > 0 {
+            return Ok(());
+        }
+
+        let setting = |key: &str| -> Result<Option<String>, AppError> {
+            Ok(conn
+                .query_row(
+                    "SELECT value FROM settings WHERE setting = ?1",
+                    [key],
+                    |row| row.get(0),
+                )
+                .optional()?)
+        };
+        let Some(path) = setting("installation_path")? else {
+            return Ok(());
+        };
+
+        let platform = InstallPlatform::guess(&path);
+        let linux_launch_mode = setting("linux_launch_mode")?;
+        conn.execute(
+            "INSERT INTO installations (label, path, platform, active, linux_launch_mode)
+             VALUES (?1, ?2, ?3, 1, ?4)",
+            params![
+                Self::default_label(&path, platform),
+                path,
+                platform.as_str(),
+                linux_launch_mode
+            ],
+        )?;
+        conn.execute(
+            "DELETE FROM settings WHERE setting IN ('installation_path', 'linux_launch_mode')",
+            [],
+        )?;
+        if !Self::shares_lovely_binary(&path, linux_launch_mode.as_deref()) {
+            conn.execute(
+                "UPDATE installations SET lovely_version = ?1, lovely_pinned = ?2 WHERE path = ?3",
+                params![
+                    setting("lovely_version")?,
+                    setting("lovely_pinned")?.as_deref() == Some("enabled"),
+                    path
+                ],
+            )?;
+            conn.execute(
+                "DELETE FROM settings WHERE setting IN ('lovely_version', 'lovely_pinned')",
+                [],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// On macOS, and for native Linux launches, every install loads the same Lovely
+    /// library from the bins dir, so its version and pin are kept in the settings
+    /// instead of on the install.
+    fn shares_lovely_binary(path: &str, linux_launch_mode: Option<&str>) -> bool {
+        if cfg!(target_os = "macos") {
+            return true;
+        }
+        if cfg!(target_os = "linux") {
+            let mode = linux_launch_mode
+                .and_then(LinuxLaunchMode::parse)
+                .unwrap_or_else(|| crate::lovely::detect_linux_launch_mode(Path::new(path)));
+            return mode == LinuxLaunchMode::Native;
+        }
+        false
+    }
+
+    // The active install, if it keeps its own Lovely state
+    fn lovely_installation(&self) -> Result<Option<Installation>, AppError> {
+        Ok(self.get_active_installation()?.filter(|install| {
+            !Self::shares_lovely_binary(&install.path, install.linux_launch_mode.as_deref())
+        }))
+    }
+
     fn initialize_database(conn: &Connection) -> Result<(), AppError> {
         conn.execute(
             "CREATE TABLE IF NOT EXISTS settings (
@@ -319,6 +808,95 @@ impl Database {
         )
         .map_err(|e| AppError::DatabaseInit(e.to_string()))?;
 
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS mod_backups (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                mod_name TEXT NOT NULL,
+                version TEXT,
+                original_path TEXT NOT NULL,
+                backup_path TEXT NOT NULL,
+                size_bytes INTEGER NOT NULL DEFAULT 0,
+                reason TEXT NOT NULL,
+                created_at INTEGER NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| AppError::DatabaseInit(e.to_string()))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS installations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                label TEXT NOT NULL,
+                path TEXT NOT NULL UNIQUE,
+                platform TEXT NOT NULL,
+                active INTEGER NOT NULL DEFAULT 0,
+                lovely_version TEXT,
+                lovely_pinned INTEGER NOT NULL DEFAULT 0,
+                linux_launch_mode TEXT,
+                launch_method TEXT
+            )",
+            [],
+        )
+        .map_err(|e| AppError::DatabaseInit(e.to_string()))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS launch_sessions (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                launch_mode TEXT NOT NULL,
+                started_at INTEGER NOT NULL,
+                ended_at INTEGER,
+                exit_code INTEGER,
+                crashed INTEGER NOT NULL DEFAULT 0,
+                crash_message TEXT
+            )",
+            [],
+        )
+        .map_err(|e| AppError::DatabaseInit(e.to_string()))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS mod_sources (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                name TEXT NOT NULL UNIQUE,
+                kind TEXT NOT NULL,
+                location TEXT NOT NULL,
+                priority INTEGER NOT NULL DEFAULT 0,
+                enabled INTEGER NOT NULL DEFAULT 1
+            )",
+            [],
+        )
+        .map_err(|e| AppError::DatabaseInit(e.to_string()))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS repo_origins (
+                mod_name TEXT PRIMARY KEY,
+                repo_url TEXT NOT NULL,
+                git_ref TEXT NOT NULL,
+                asset TEXT
+            )",
+            [],
+        )
+        .map_err(|e| AppError::DatabaseInit(e.to_string()))?;
+
+        conn.execute(
+            "CREATE TABLE IF NOT EXISTS thunderstore_origins (
+                mod_name TEXT PRIMARY KEY,
+                namespace TEXT NOT NULL,
+                package TEXT NOT NULL
+            )",
+            [],
+        )
+        .map_err(|e| AppError::DatabaseInit(e.to_string()))?;
+
+        conn.execute(
+            "INSERT OR IGNORE INTO mod_sources (name, kind, location, priority) VALUES (?1, ?2, ?3, 0)",
+            params![
+                Self::DEFAULT_SOURCE,
+                SourceKind::Gitlab.as_str(),
+                crate::catalog::DEFAULT_INDEX_URL
+            ],
+        )
+        .map_err(|e| AppError::DatabaseInit(e.to_string()))?;
+
         // Set the database version
         conn.execute(
             "INSERT OR REPLACE INTO settings (setting, value) VALUES ('db_version', ?1)",
@@ -457,59 +1035,445 @@ impl Database {
     pub fn remove_installed_mod(&self, name: &str) -> Result<(), AppError> {
         self.conn
             .execute("DELETE FROM installed_mods WHERE name = ?1", [name])?;
+        self.conn
+            .execute("DELETE FROM repo_origins WHERE mod_name = ?1", [name])?;
+        self.conn.execute(
+            "DELETE FROM thunderstore_origins WHERE mod_name = ?1",
+            [name],
+        )?;
         Ok(())
     }
 
-    pub fn get_installation_path(&self) -> Result<Option<String>, AppError> {
-        let mut stmt = self
+    const REPO_ORIGIN_COLUMNS: &'static str = "mod_name, repo_url, git_ref, asset";
+
+    fn row_to_repo_origin(row: &rusqlite::Row<'_>) -> rusqlite::Result<RepoOrigin> {
+        Ok(RepoOrigin {
+            mod_name: row.get(0)?,
+            repo_url: row.get(1)?,
+            git_ref: row.get(2)?,
+            asset: row.get(3)?,
+        })
+    }
+
+    pub fn set_repo_origin(&self, origin: &RepoOrigin) -> Result<(), AppError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO repo_origins (mod_name, repo_url, git_ref, asset) VALUES (?1, ?2, ?3, ?4)",
+            params![origin.mod_name, origin.repo_url, origin.git_ref, origin.asset],
+        )?;
+        Ok(())
+    }
+
+    pub fn get_repo_origin(&self, mod_name: &str) -> Result<Option<RepoOrigin>, AppError> {
+        let origin = self
             .conn
-            .prepare("SELECT value FROM settings WHERE setting = 'installation_path'")?;
-        let mut rows = stmt.query([])?;
+            .query_row(
+                &format!(
+                    "SELECT {} FROM repo_origins WHERE mod_name = ?1",
+                    Self::REPO_ORIGIN_COLUMNS
+                ),
+                [mod_name],
+                Self::row_to_repo_origin,
+            )
+            .optional()?;
+        Ok(origin)
+    }
 
-        if let Some(row) = rows.next()? {
-            Ok(Some(row.get(0)?))
-        } else {
-            Ok(None)
+    /// Mods installed from a repository.
+    pub fn get_repo_origins(&self) -> Result<Vec<RepoOrigin>, AppError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM repo_origins ORDER BY mod_name",
+            Self::REPO_ORIGIN_COLUMNS
+        ))?;
+        let origins = stmt
+            .query_map([], Self::row_to_repo_origin)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(origins)
+    }
+
+    const THUNDERSTORE_ORIGIN_COLUMNS: &'static str = "mod_name, namespace, package";
+
+    fn row_to_thunderstore_origin(row: &rusqlite::Row<'_>) -> rusqlite::Result<ThunderstoreOrigin> {
+        Ok(ThunderstoreOrigin {
+            mod_name: row.get(0)?,
+            namespace: row.get(1)?,
+            package: row.get(2)?,
+        })
+    }
+
+    pub fn set_thunderstore_origin(&self, origin: &ThunderstoreOrigin) -> Result<(), AppError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO thunderstore_origins (mod_name, namespace, package) VALUES (?1, ?2, ?3)",
+            params![origin.mod_name, origin.namespace, origin.package],
+        )?;
+        Ok(())
+    }
+
+    /// Mods installed from Thunderstore.
+    pub fn get_thunderstore_origins(&self) -> Result<Vec<ThunderstoreOrigin>, AppError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM thunderstore_origins ORDER BY mod_name",
+            Self::THUNDERSTORE_ORIGIN_COLUMNS
+        ))?;
+        let origins = stmt
+            .query_map([], Self::row_to_thunderstore_origin)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(origins)
+    }
+
+    const INSTALLATION_COLUMNS: &'static str =
+        "id, label, path, platform, active, lovely_version, lovely_pinned, linux_launch_mode, launch_method";
+
+    fn row_to_installation(row: &rusqlite::Row<'_>) -> rusqlite::Result<Installation> {
+        let platform: String = row.get(3)?;
+        Ok(Installation {
+            id: row.get(0)?,
+            label: row.get(1)?,
+            path: row.get(2)?,
+            platform: InstallPlatform::parse(&platform).unwrap_or(InstallPlatform::Other),
+            active: row.get(4)?,
+            lovely_version: row.get(5)?,
+            lovely_pinned: row.get(6)?,
+            linux_launch_mode: row.get(7)?,
+            launch_method: row
+                .get::<_, Option<String>>(8)?
+                .and_then(|json| serde_json::from_str(&json).ok()),
+        })
+    }
+
+    fn launch_method_json(method: Option<&LaunchMethod>) -> Result<Option<String>, AppError> {
+        method
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| AppError::Serialization {
+                format: "json".into(),
+                source: e.to_string(),
+            })
+    }
+
+    fn default_label(path: &str, platform: InstallPlatform) -> String {
+        match platform {
+            InstallPlatform::Steam => "Steam".to_string(),
+            InstallPlatform::SteamBeta => "Steam (beta)".to_string(),
+            _ => Path::new(path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "Balatro".to_string()),
         }
     }
 
-    pub fn set_installation_path(&self, path: &str) -> Result<(), AppError> {
+    pub fn get_installations(&self) -> Result<Vec<Installation>, AppError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM installations ORDER BY id",
+            Self::INSTALLATION_COLUMNS
+        ))?;
+        let installs = stmt
+            .query_map([], Self::row_to_installation)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(installs)
+    }
+
+    pub fn get_active_installation(&self) -> Result<Option<Installation>, AppError> {
+        let install = self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT {} FROM installations WHERE active = 1",
+                    Self::INSTALLATION_COLUMNS
+                ),
+                [],
+                Self::row_to_installation,
+            )
+            .optional()?;
+        Ok(install)
+    }
+
+    /// Register an install, or update the label, platform and (when given) launch method
+    /// of an already registered path. The first install registered becomes the active one.
+    pub fn add_installation(
+        &self,
+        label: &str,
+        path: &str,
+        platform: InstallPlatform,
+        launch: Option<&LaunchMethod>,
+    ) -> Result<i64, AppError> {
         self.conn.execute(
-            "INSERT OR REPLACE INTO settings (setting, value) VALUES (?1, ?2)",
-            ["installation_path", path],
+            "INSERT INTO installations (label, path, platform, launch_method) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(path) DO UPDATE SET label = excluded.label, platform = excluded.platform,
+                launch_method = COALESCE(excluded.launch_method, installations.launch_method)",
+            params![label, path, platform.as_str(), Self::launch_method_json(launch)?],
+        )?;
+        let id: i64 = self.conn.query_row(
+            "SELECT id FROM installations WHERE path = ?1",
+            [path],
+            |row| row.get(0),
         )?;
+        if self.get_active_installation()?.is_none() {
+            self.set_active_installation(id)?;
+            // Launch and Lovely state recorded before any install was registered belongs
+            // to this one, unless its Lovely is the shared library
+            let linux_launch_mode = self.get_setting("linux_launch_mode")?;
+            self.conn.execute(
+                "UPDATE installations SET linux_launch_mode = ?1 WHERE id = ?2",
+                params![linux_launch_mode, id],
+            )?;
+            self.set_setting("linux_launch_mode", None)?;
+            if !Self::shares_lovely_binary(path, linux_launch_mode.as_deref()) {
+                let lovely_version = self.get_setting("lovely_version")?;
+                let lovely_pinned =
+                    self.get_setting("lovely_pinned")?.as_deref() == Some("enabled");
+                self.conn.execute(
+                    "UPDATE installations SET lovely_version = ?1, lovely_pinned = ?2 WHERE id = ?3",
+                    params![lovely_version, lovely_pinned, id],
+                )?;
+                for setting in ["lovely_version", "lovely_pinned"] {
+                    self.set_setting(setting, None)?;
+                }
+            }
+        }
+        Ok(id)
+    }
+
+    pub fn set_active_installation(&self, id: i64) -> Result<(), AppError> {
+        let exists: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM installations WHERE id = ?1",
+            [id],
+            |row| row.get(0),
+        )?;
+        if exists == 0 {
+            return Err(AppError::InvalidState(format!(
+                "No installation with id {id}"
+            )));
+        }
+        self.conn
+            .execute("UPDATE installations SET active = (id = ?1)", [id])?;
         Ok(())
     }
 
-    pub fn remove_installation_path(&self) -> Result<(), AppError> {
+    /// Unregister an install. If it was active, the oldest remaining install takes over.
+    pub fn remove_installation(&self, id: i64) -> Result<(), AppError> {
+        self.conn
+            .execute("DELETE FROM installations WHERE id = ?1", [id])?;
+        if self.get_active_installation()?.is_none() {
+            let next: Option<i64> =
+                self.conn
+                    .query_row("SELECT MIN(id) FROM installations", [], |row| row.get(0))?;
+            if let Some(next) = next {
+                self.set_active_installation(next)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Name of the community index source every database starts with.
+    pub const DEFAULT_SOURCE: &'static str = "balatro-mod-index";
+
+    const MOD_SOURCE_COLUMNS: &'static str = "id, name, kind, location, priority, enabled";
+
+    fn row_to_mod_source(row: &rusqlite::Row<'_>) -> rusqlite::Result<ModSourceConfig> {
+        let kind: String = row.get(2)?;
+        Ok(ModSourceConfig {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            kind: SourceKind::parse(&kind).unwrap_or(SourceKind::IndexUrl),
+            location: row.get(3)?,
+            priority: row.get(4)?,
+            enabled: row.get(5)?,
+        })
+    }
+
+    /// Configured mod sources in the order they are consulted.
+    pub fn get_mod_sources(&self) -> Result<Vec<ModSourceConfig>, AppError> {
+        let mut stmt = self.conn.prepare(&format!(
+            "SELECT {} FROM mod_sources ORDER BY priority, id",
+            Self::MOD_SOURCE_COLUMNS
+        ))?;
+        let sources = stmt
+            .query_map([], Self::row_to_mod_source)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(sources)
+    }
+
+    /// Add a source, or update the kind, location and priority of one with the same name.
+    pub fn add_mod_source(
+        &self,
+        name: &str,
+        kind: SourceKind,
+        location: &str,
+        priority: i64,
+    ) -> Result<i64, AppError> {
         self.conn.execute(
-            "DELETE FROM settings WHERE setting = 'installation_path'",
-            [],
+            "INSERT INTO mod_sources (name, kind, location, priority) VALUES (?1, ?2, ?3, ?4)
+             ON CONFLICT(name) DO UPDATE SET kind = excluded.kind, location = excluded.location,
+                 priority = excluded.priority",
+            params![name, kind.as_str(), location, priority],
+        )?;
+        let id = self.conn.query_row(
+            "SELECT id FROM mod_sources WHERE name = ?1",
+            [name],
+            |row| row.get(0),
+        )?;
+        Ok(id)
+    }
+
+    pub fn set_mod_source_enabled(&self, id: i64, enabled: bool) -> Result<(), AppError> {
+        self.conn.execute(
+            "UPDATE mod_sources SET enabled = ?1 WHERE id = ?2",
+            params![enabled, id],
         )?;
         Ok(())
     }
 
-    pub fn get_lovely_version(&self) -> Result<Option<String>, AppError> {
-        let mut stmt = self
+    pub fn remove_mod_source(&self, id: i64) -> Result<(), AppError> {
+        self.conn
+            .execute("DELETE FROM mod_sources WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    // Write one per-install column of the active install; false if there is none
+    fn update_active_installation<T: rusqlite::ToSql>(
+        &self,
+        column: &str,
+        value: T,
+    ) -> Result<bool, AppError> {
+        let active = self.get_active_installation()?;
+        self.update_installation(active, column, value)
+    }
+
+    fn update_installation<T: rusqlite::ToSql>(
+        &self,
+        install: Option<Installation>,
+        column: &str,
+        value: T,
+    ) -> Result<bool, AppError> {
+        let Some(install) = install else {
+            return Ok(false);
+        };
+        self.conn.execute(
+            &format!("UPDATE installations SET {column} = ?1 WHERE id = ?2"),
+            params![value, install.id],
+        )?;
+        Ok(true)
+    }
+
+    // Global settings, and Lovely state set before any install was registered
+    fn get_setting(&self, setting: &str) -> Result<Option<String>, AppError> {
+        Ok(self
             .conn
-            .prepare("SELECT value FROM settings WHERE setting = 'lovely_version'")?;
-        let mut rows = stmt.query([])?;
+            .query_row(
+                "SELECT value FROM settings WHERE setting = ?1",
+                [setting],
+                |row| row.get(0),
+            )
+            .optional()?)
+    }
 
-        if let Some(row) = rows.next()? {
-            Ok(Some(row.get(0)?))
-        } else {
-            Ok(None)
+    fn set_setting(&self, setting: &str, value: Option<&str>) -> Result<(), AppError> {
+        match value {
+            Some(value) => self.conn.execute(
+                "INSERT OR REPLACE INTO settings (setting, value) VALUES (?1, ?2)",
+                [setting, value],
+            )?,
+            None => self
+                .conn
+                .execute("DELETE FROM settings WHERE setting = ?1", [setting])?,
+        };
+        Ok(())
+    }
+
+    /// Path of the active install.
+    pub fn get_installation_path(&self) -> Result<Option<String>, AppError> {
+        Ok(self.get_active_installation()?.map(|i| i.path))
+    }
+
+    /// Register `path` (if needed) and make it the active install.
+    pub fn set_installation_path(&self, path: &str) -> Result<(), AppError> {
+        let platform = InstallPlatform::guess(path);
+        let existing = self
+            .get_installations()?
+            .into_iter()
+            .find(|i| i.path == path);
+        let id = match existing {
+            Some(install) => install.id,
+            None => {
+                self.add_installation(&Self::default_label(path, platform), path, platform, None)?
+            }
+        };
+        self.set_active_installation(id)
+    }
+
+    /// Unregister the active install.
+    pub fn remove_installation_path(&self) -> Result<(), AppError> {
+        if let Some(active) = self.get_active_installation()? {
+            self.remove_installation(active.id)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_lovely_version(&self) -> Result<Option<String>, AppError> {
+        match self.lovely_installation()? {
+            Some(install) => Ok(install.lovely_version),
+            None => self.get_setting("lovely_version"),
         }
     }
 
     pub fn set_lovely_version(&self, version: &str) -> Result<(), AppError> {
+        if !self.update_installation(self.lovely_installation()?, "lovely_version", version)? {
+            self.set_setting("lovely_version", Some(version))?;
+        }
+        Ok(())
+    }
+
+    /// Record how an install has to be started, once it has been worked out.
+    pub fn set_launch_method(&self, id: i64, method: &LaunchMethod) -> Result<(), AppError> {
         self.conn.execute(
-            "INSERT OR REPLACE INTO settings (setting, value) VALUES ('lovely_version', ?1)",
-            [version],
+            "UPDATE installations SET launch_method = ?1 WHERE id = ?2",
+            params![Self::launch_method_json(Some(method))?, id],
         )?;
         Ok(())
     }
 
+    /// Explicit Linux launch mode; `None` means detect it from the installation.
+    pub fn get_linux_launch_mode(&self) -> Result<Option<LinuxLaunchMode>, AppError> {
+        let value = match self.get_active_installation()? {
+            Some(install) => install.linux_launch_mode,
+            None => self.get_setting("linux_launch_mode")?,
+        };
+        Ok(value.as_deref().and_then(LinuxLaunchMode::parse))
+    }
+
+    pub fn set_linux_launch_mode(&self, mode: Option<LinuxLaunchMode>) -> Result<(), AppError> {
+        let value = mode.map(|m| m.as_str());
+        if !self.update_active_installation("linux_launch_mode", value)? {
+            self.set_setting("linux_launch_mode", value)?;
+        }
+        Ok(())
+    }
+
+    /// Mods directory chosen by the user; `None` means derive it from the active install.
+    pub fn get_mods_dir(&self) -> Result<Option<String>, AppError> {
+        self.get_setting("mods_dir")
+    }
+
+    pub fn set_mods_dir(&self, path: Option<&str>) -> Result<(), AppError> {
+        self.set_setting("mods_dir", path)
+    }
+
+    /// A pinned Lovely version is never offered as outdated.
+    pub fn set_lovely_pinned(&self, pinned: bool) -> Result<(), AppError> {
+        if !self.update_installation(self.lovely_installation()?, "lovely_pinned", pinned)? {
+            let pinned: &str = if pinned { "enabled" } else { "disabled" };
+            self.set_setting("lovely_pinned", Some(pinned))?;
+        }
+        Ok(())
+    }
+
+    pub fn is_lovely_pinned(&self) -> Result<bool, AppError> {
+        match self.lovely_installation()? {
+            Some(install) => Ok(install.lovely_pinned),
+            None => Ok(self.get_setting("lovely_pinned")?.as_deref() == Some("enabled")),
+        }
+    }
+
     pub fn get_last_installed_version(&self, mod_name: &str) -> Result<String, AppError> {
         let mut stmt = self
             .conn
@@ -594,6 +1558,187 @@ impl Database {
         }
     }
 
+    pub fn get_save_backup_retention(&self) -> Result<usize, AppError> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT value FROM settings WHERE setting = 'save_backup_retention'")?;
+        let mut rows = stmt.query([])?;
+
+        if let Some(row) = rows.next()? {
+            let val: String = row.get(0)?;
+            val.parse()
+                .map_err(|_| AppError::InvalidState("Invalid save backup retention".to_string()))
+        } else {
+            Ok(crate::save_backup::DEFAULT_SAVE_BACKUP_RETENTION)
+        }
+    }
+
+    pub fn set_save_backup_retention(&self, retention: usize) -> Result<(), AppError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO settings (setting, value) VALUES ('save_backup_retention', ?1)",
+            [retention.to_string()],
+        )?;
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn add_mod_backup(
+        &self,
+        mod_name: &str,
+        version: Option<&str>,
+        original_path: &str,
+        backup_path: &str,
+        size_bytes: u64,
+        reason: &str,
+        created_at: u64,
+    ) -> Result<i64, AppError> {
+        self.conn.execute(
+            "INSERT INTO mod_backups (mod_name, version, original_path, backup_path, size_bytes, reason, created_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![
+                mod_name,
+                version,
+                original_path,
+                backup_path,
+                size_bytes as i64,
+                reason,
+                created_at as i64
+            ],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    fn row_to_mod_backup(row: &rusqlite::Row<'_>) -> rusqlite::Result<ModBackup> {
+        Ok(ModBackup {
+            id: row.get(0)?,
+            mod_name: row.get(1)?,
+            version: row.get(2)?,
+            original_path: row.get(3)?,
+            backup_path: row.get(4)?,
+            size_bytes: row.get::<_, i64>(5)? as u64,
+            reason: row.get(6)?,
+            created_at: row.get::<_, i64>(7)? as u64,
+        })
+    }
+
+    /// Backups newest first, optionally restricted to one original mod path.
+    pub fn get_mod_backups(&self, original_path: Option<&str>) -> Result<Vec<ModBackup>, AppError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, mod_name, version, original_path, backup_path, size_bytes, reason, created_at
+             FROM mod_backups
+             WHERE ?1 IS NULL OR original_path = ?1
+             ORDER BY created_at DESC, id DESC",
+        )?;
+        let backups = stmt
+            .query_map([original_path], Self::row_to_mod_backup)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(backups)
+    }
+
+    pub fn get_mod_backup(&self, id: i64) -> Result<Option<ModBackup>, AppError> {
+        let backup = self
+            .conn
+            .query_row(
+                "SELECT id, mod_name, version, original_path, backup_path, size_bytes, reason, created_at
+                 FROM mod_backups WHERE id = ?1",
+                [id],
+                Self::row_to_mod_backup,
+            )
+            .optional()?;
+        Ok(backup)
+    }
+
+    pub fn remove_mod_backup(&self, id: i64) -> Result<(), AppError> {
+        self.conn
+            .execute("DELETE FROM mod_backups WHERE id = ?1", [id])?;
+        Ok(())
+    }
+
+    pub fn get_backup_limits(&self) -> Result<(usize, u64), AppError> {
+        let read = |setting: &str| -> Result<Option<String>, AppError> {
+            Ok(self
+                .conn
+                .query_row(
+                    "SELECT value FROM settings WHERE setting = ?1",
+                    [setting],
+                    |row| row.get::<_, String>(0),
+                )
+                .optional()?)
+        };
+
+        let max_per_mod = read("backup_max_per_mod")?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(crate::backup::DEFAULT_MAX_BACKUPS_PER_MOD);
+        let quota_bytes = read("backup_quota_bytes")?
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(crate::backup::DEFAULT_BACKUP_QUOTA_BYTES);
+        Ok((max_per_mod, quota_bytes))
+    }
+
+    pub fn set_backup_limits(&self, max_per_mod: usize, quota_bytes: u64) -> Result<(), AppError> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO settings (setting, value) VALUES ('backup_max_per_mod', ?1)",
+            [max_per_mod.to_string()],
+        )?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO settings (setting, value) VALUES ('backup_quota_bytes', ?1)",
+            [quota_bytes.to_string()],
+        )?;
+        Ok(())
+    }
+
+    pub fn start_launch_session(
+        &self,
+        launch_mode: &str,
+        started_at: u64,
+    ) -> Result<i64, AppError> {
+        self.conn.execute(
+            "INSERT INTO launch_sessions (launch_mode, started_at) VALUES (?1, ?2)",
+            params![launch_mode, started_at as i64],
+        )?;
+        Ok(self.conn.last_insert_rowid())
+    }
+
+    pub fn finish_launch_session(
+        &self,
+        id: i64,
+        ended_at: u64,
+        exit_code: Option<i32>,
+        crashed: bool,
+        crash_message: Option<&str>,
+    ) -> Result<(), AppError> {
+        self.conn.execute(
+            "UPDATE launch_sessions SET ended_at = ?2, exit_code = ?3, crashed = ?4, crash_message = ?5
+             WHERE id = ?1",
+            params![id, ended_at as i64, exit_code, crashed, crash_message],
+        )?;
+        Ok(())
+    }
+
+    fn row_to_launch_record(row: &rusqlite::Row<'_>) -> rusqlite::Result<LaunchRecord> {
+        Ok(LaunchRecord {
+            id: row.get(0)?,
+            launch_mode: row.get(1)?,
+            started_at: row.get::<_, i64>(2)? as u64,
+            ended_at: row.get::<_, Option<i64>>(3)?.map(|t| t as u64),
+            exit_code: row.get(4)?,
+            crashed: row.get(5)?,
+            crash_message: row.get(6)?,
+        })
+    }
+
+    /// Most recent launches first.
+    pub fn get_launch_sessions(&self, limit: usize) -> Result<Vec<LaunchRecord>, AppError> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, launch_mode, started_at, ended_at, exit_code, crashed, crash_message
+             FROM launch_sessions ORDER BY started_at DESC, id DESC LIMIT ?1",
+        )?;
+        let sessions = stmt
+            .query_map([limit as i64], Self::row_to_launch_record)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(sessions)
+    }
+
     pub fn set_security_warning_acknowledged(&self, acknowledged: bool) -> Result<(), AppError> {
         let value = if acknowledged { "yes" } else { "no" };
         self.conn.execute(
@@ -618,6 +1763,16 @@ impl Database {
     }
 }
 
+#[cfg(test)]
+impl Database {
+    pub(crate) fn open_in_memory() -> Result<Self, AppError> {
+        let conn =
+            Connection::open_in_memory().map_err(|e| AppError::DatabaseInit(e.to_string()))?;
+        Self::initialize_database(&conn)?;
+        Ok(Database { conn })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -654,6 +1809,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_launch_sessions() -> Result<(), AppError> {
+        let db = create_memory_db()?;
+
+        let first = db.start_launch_session("native", 1_000)?;
+        let second = db.start_launch_session("proton", 2_000)?;
+        db.finish_launch_session(first, 5_000, Some(0), false, None)?;
+        db.finish_launch_session(second, 9_000, None, true, Some("main.lua:1: boom"))?;
+
+        let sessions = db.get_launch_sessions(10)?;
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].id, second);
+        assert!(sessions[0].crashed);
+        assert_eq!(
+            sessions[0].crash_message.as_deref(),
+            Some("main.lua:1: boom")
+        );
+        assert_eq!(sessions[1].exit_code, Some(0));
+        assert_eq!(sessions[1].ended_at, Some(5_000));
+        assert_eq!(db.get_launch_sessions(1)?.len(), 1);
+        Ok(())
+    }
+
     #[test]
     fn test_installation_path_management() -> Result<(), AppError> {
         let db = create_memory_db()?;
@@ -668,6 +1846,214 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    #[cfg_attr(target_os = "macos", ignore = "every macOS install shares one Lovely")]
+    fn test_multiple_installations() -> Result<(), AppError> {
+        let db = create_memory_db()?;
+
+        // Proton installs keep Lovely in the game folder, so it is tracked per install
+        db.set_linux_launch_mode(Some(LinuxLaunchMode::Proton))?;
+        db.set_lovely_version("v0.7.0")?;
+        let steam = db.add_installation(
+            "Steam",
+            "/steam/steamapps/common/Balatro",
+            InstallPlatform::Steam,
+            Some(&LaunchMethod::Steam),
+        )?;
+        let drm_free =
+            db.add_installation("GOG", "/games/balatro", InstallPlatform::DrmFree, None)?;
+
+        // The first install is active and picks up the Lovely state recorded before it
+        assert_eq!(
+            db.get_installation_path()?,
+            Some("/steam/steamapps/common/Balatro".into())
+        );
+        assert_eq!(db.get_lovely_version()?, Some("v0.7.0".into()));
+
+        db.set_active_installation(drm_free)?;
+        db.set_linux_launch_mode(Some(LinuxLaunchMode::Proton))?;
+        assert_eq!(db.get_lovely_version()?, None);
+        db.set_lovely_version("v0.8.0")?;
+        db.set_lovely_pinned(true)?;
+        assert!(db.is_lovely_pinned()?);
+
+        db.set_active_installation(steam)?;
+        assert_eq!(db.get_lovely_version()?, Some("v0.7.0".into()));
+        assert!(!db.is_lovely_pinned()?);
+        assert!(db.set_active_installation(999).is_err());
+
+        let installs = db.get_installations()?;
+        assert_eq!(installs.len(), 2);
+        assert_eq!(installs[1].platform, InstallPlatform::DrmFree);
+        assert_eq!(installs[1].lovely_version.as_deref(), Some("v0.8.0"));
+        assert_eq!(installs[0].launch_method, Some(LaunchMethod::Steam));
+        assert_eq!(installs[1].launch_method, None);
+
+        // Re-registering from detection records the launch method, and a later
+        // registration without one keeps it
+        let direct = LaunchMethod::Direct {
+            executable: PathBuf::from("/games/balatro/Balatro.exe"),
+        };
+        db.add_installation("GOG", "/games/balatro", InstallPlatform::Gog, Some(&direct))?;
+        db.add_installation("GOG", "/games/balatro", InstallPlatform::Gog, None)?;
+        assert_eq!(db.get_installations()?[1].launch_method, Some(direct));
+        db.set_launch_method(
+            steam,
+            &LaunchMethod::Lutris {
+                slug: "balatro".into(),
+            },
+        )?;
+        assert_eq!(
+            db.get_installations()?[0].launch_method,
+            Some(LaunchMethod::Lutris {
+                slug: "balatro".into()
+            })
+        );
+
+        // Removing the active install hands over to the remaining one
+        db.remove_installation(steam)?;
+        assert_eq!(db.get_installation_path()?, Some("/games/balatro".into()));
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(any(target_os = "macos", target_os = "linux"))]
+    fn test_shared_lovely_state() -> Result<(), AppError> {
+        let db = create_memory_db()?;
+
+        db.set_lovely_version("v0.7.0")?;
+        let first = db.add_installation("Steam", "/games/one", InstallPlatform::Steam, None)?;
+        let second = db.add_installation("itch", "/games/two", InstallPlatform::Itch, None)?;
+        assert_eq!(db.get_lovely_version()?, Some("v0.7.0".into()));
+
+        // Both load the same library from the bins dir, so they see the same version
+        db.set_active_installation(second)?;
+        assert_eq!(db.get_lovely_version()?, Some("v0.7.0".into()));
+        db.set_lovely_version("v0.8.0")?;
+        db.set_lovely_pinned(true)?;
+        db.set_active_installation(first)?;
+        assert_eq!(db.get_lovely_version()?, Some("v0.8.0".into()));
+        assert!(db.is_lovely_pinned()?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mod_sources() -> Result<(), AppError> {
+        let db = create_memory_db()?;
+
+        let sources = db.get_mod_sources()?;
+        assert_eq!(sources.len(), 1);
+        assert_eq!(sources[0].name, Database::DEFAULT_SOURCE);
+        assert_eq!(sources[0].kind, SourceKind::Gitlab);
+
+        let local = db.add_mod_source("local", SourceKind::LocalDir, "/mods/index", -1)?;
+        db.add_mod_source(
+            "mirror",
+            SourceKind::IndexUrl,
+            "https://example.com/index.json",
+            5,
+        )?;
+        let names: Vec<_> = db.get_mod_sources()?.into_iter().map(|s| s.name).collect();
+        assert_eq!(names, ["local", Database::DEFAULT_SOURCE, "mirror"]);
+
+        // Re-adding by name updates in place
+        assert_eq!(
+            db.add_mod_source("local", SourceKind::LocalDir, "/other", 10)?,
+            local
+        );
+        db.set_mod_source_enabled(local, false)?;
+        let sources = db.get_mod_sources()?;
+        assert_eq!(sources[2].location, "/other");
+        assert!(!sources[2].enabled);
+
+        db.remove_mod_source(local)?;
+        assert_eq!(db.get_mod_sources()?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_repo_origins() -> Result<(), AppError> {
+        let db = create_memory_db()?;
+        let origin = RepoOrigin {
+            mod_name: "Talisman".to_string(),
+            repo_url: "https://github.com/MathIsFun0/Talisman".to_string(),
+            git_ref: "v2.0.0".to_string(),
+            asset: Some("Talisman.zip".to_string()),
+        };
+        db.add_installed_mod("Talisman", "/mods/Talisman", &[], Some("v2.0.0".into()))?;
+        db.set_repo_origin(&origin)?;
+        assert_eq!(db.get_repo_origin("Talisman")?, Some(origin.clone()));
+        assert_eq!(db.get_repo_origins()?, [origin]);
+
+        // Forgotten along with the mod
+        db.remove_installed_mod("Talisman")?;
+        assert_eq!(db.get_repo_origin("Talisman")?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_thunderstore_origins() -> Result<(), AppError> {
+        let db = create_memory_db()?;
+        let origin = ThunderstoreOrigin {
+            mod_name: "Cards".to_string(),
+            namespace: "Ace".to_string(),
+            package: "Cards".to_string(),
+        };
+        db.add_installed_mod("Cards", "/mods/Cards", &[], Some("1.2.0".into()))?;
+        db.set_thunderstore_origin(&origin)?;
+        assert_eq!(db.get_thunderstore_origins()?, [origin]);
+
+        db.remove_installed_mod("Cards")?;
+        assert!(db.get_thunderstore_origins()?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_migrate_installations_before_launch_method() -> Result<(), AppError> {
+        let old = Connection::open_in_memory()?;
+        old.execute(
+            "CREATE TABLE installations (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                label TEXT NOT NULL,
+                path TEXT NOT NULL UNIQUE,
+                platform TEXT NOT NULL,
+                active INTEGER NOT NULL DEFAULT 0,
+                lovely_version TEXT,
+                lovely_pinned INTEGER NOT NULL DEFAULT 0,
+                linux_launch_mode TEXT
+            )",
+            [],
+        )?;
+        old.execute(
+            "INSERT INTO installations (label, path, platform, active) VALUES ('GOG', '/games/balatro', 'gog', 1)",
+            [],
+        )?;
+        let db = create_memory_db()?;
+        Database::migrate_installations(&old, &db.conn)?;
+
+        let active = db.get_active_installation()?.unwrap();
+        assert_eq!(active.path, "/games/balatro");
+        assert_eq!(active.launch_method, None);
+        Ok(())
+    }
+
+    #[test]
+    fn test_seed_installation_from_legacy_settings() -> Result<(), AppError> {
+        let db = create_memory_db()?;
+        db.conn.execute(
+            "INSERT INTO settings (setting, value) VALUES ('installation_path', '/old/Balatro'), ('lovely_pinned', 'enabled')",
+            [],
+        )?;
+        Database::seed_installations_from_settings(&db.conn)?;
+
+        let active = db.get_active_installation()?.unwrap();
+        assert_eq!(active.path, "/old/Balatro");
+        assert_eq!(active.label, "Balatro");
+        assert!(db.is_lovely_pinned()?);
+        assert!(db.get_setting("installation_path")?.is_none());
+        Ok(())
+    }
+
     #[test]
     fn test_mod_details() -> Result<(), AppError> {
         let db = create_memory_db()?;
@@ -69,6 +69,16 @@ pub enum AppError {
     ApiLimitExceeded,
     InvalidApiResponse(String),
 
+    // Integrity
+    ChecksumMismatch {
+        asset: String,
+        expected: String,
+        actual: String,
+    },
+    ChecksumUnavailable {
+        asset: String,
+    },
+
     // Platform specific
     MacOsLibrary {
         lib_name: String,
@@ -123,11 +133,38 @@ impl fmt::Display for AppError {
                 write!(f, "Failed to install mod '{mod_name}': {source}")
             }
 
+            AppError::ModNotFound { mod_name, version } if version.is_empty() => {
+                write!(f, "Mod '{mod_name}' not found")
+            }
+            AppError::ModNotFound { mod_name, version } => {
+                write!(f, "Mod '{mod_name}' {version} not found")
+            }
+
+            AppError::ModConflict {
+                mod_name,
+                conflicts,
+            } => write!(f, "'{mod_name}' is required by: {}", conflicts.join(", ")),
+
             AppError::NetworkRequest { url: _, source } => {
                 // Show only the underlying message to keep UI errors concise
                 write!(f, "{source}")
             }
 
+            AppError::ChecksumMismatch {
+                asset,
+                expected,
+                actual,
+            } => write!(
+                f,
+                "Checksum mismatch for '{asset}': expected sha256 {expected}, got {actual}. \
+                 The download may be corrupted or tampered with and was not installed."
+            ),
+
+            AppError::ChecksumUnavailable { asset } => write!(
+                f,
+                "No published checksum found for '{asset}'; refusing to install an unverified download without confirmation"
+            ),
+
             AppError::MacOsLibrary { lib_name, source } => {
                 write!(f, "MacOS library '{lib_name}' error: {source}")
             }
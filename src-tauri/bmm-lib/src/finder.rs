@@ -132,6 +132,12 @@ pub fn get_balatro_paths() -> Vec<PathBuf> {
             }
         }
     }
+    // Every Steam library (secondary drives, Flatpak/Snap clients) via libraryfolders.vdf
+    paths.extend(
+        crate::steam::find_steam_installs()
+            .into_iter()
+            .map(|install| install.install_dir),
+    );
     match home::home_dir() {
         Some(path) => {
             let mut path = path;
@@ -158,6 +164,12 @@ pub fn get_balatro_paths() -> Vec<PathBuf> {
             }
         }
     }
+    // Every Steam library (secondary drives, Flatpak/Snap clients) via libraryfolders.vdf
+    paths.extend(
+        crate::steam::find_steam_installs()
+            .into_iter()
+            .map(|install| install.install_dir),
+    );
     match home::home_dir() {
         Some(path) => {
             let mut path = path;
@@ -203,10 +215,9 @@ pub fn is_steam_running() -> bool {
 pub fn get_installed_mods() -> Vec<String> {
     let mut installed_mods_paths: Vec<PathBuf> = vec![];
 
-    let Some(config_dir) = dirs::config_dir() else {
+    let Ok(mod_dir) = crate::paths::mods_dir() else {
         return vec![];
     };
-    let mod_dir = config_dir.join("Balatro").join("Mods");
 
     if !mod_dir.exists() {
         return vec![];
@@ -238,13 +249,18 @@ pub fn get_installed_mods() -> Vec<String> {
 }
 
 pub fn is_balatro_running() -> bool {
+    find_balatro_pid().is_some()
+}
+
+/// PID of a running Balatro (or LÖVE) process, matched by name.
+pub fn find_balatro_pid() -> Option<u32> {
     #[cfg(target_os = "windows")]
     {
         let system = System::new_all();
         let x = system
             .processes_by_exact_name(std::ffi::OsStr::new("Balatro.exe"))
             .next()
-            .is_some();
+            .map(|p| p.pid().as_u32());
         x
     }
 
@@ -260,12 +276,26 @@ pub fn is_balatro_running() -> bool {
                         && name.to_lowercase() != "balatro-mod-manager")
                         | (name == "love")
                     {
-                        return true;
+                        return Some(pid);
                     }
                 }
             }
         }
-        false
+        None
+    }
+}
+
+pub fn is_process_running(pid: u32) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        let system = System::new_all();
+        let x = system.process(sysinfo::Pid::from_u32(pid)).is_some();
+        x
+    }
+
+    #[cfg(target_family = "unix")]
+    {
+        libproc::proc_pid::name(pid as i32).is_ok()
     }
 }
 
@@ -0,0 +1,566 @@
+//! Mods installed straight from a GitHub or GitLab repository rather than an index.
+
+use crate::database::{Database, InstalledMod, RepoOrigin};
+use crate::errors::AppError;
+use crate::installer;
+use reqwest::header::{HeaderMap, ACCEPT, LINK, USER_AGENT};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const GITHUB_API: &str = "https://api.github.com";
+// Largest page both forges serve
+const PER_PAGE: u32 = 100;
+// Stop following a repository's pages after this many
+const MAX_PAGES: usize = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Forge {
+    GitHub,
+    /// gitlab.com or a self-hosted instance.
+    GitLab,
+}
+
+/// A repository and, optionally, the branch, tag or commit to install.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct RepoRef {
+    pub forge: Forge,
+    /// `https://host`
+    pub origin: String,
+    /// `owner/repo`, or `group/subgroup/repo` on GitLab.
+    pub project: String,
+    pub git_ref: Option<String>,
+}
+
+impl RepoRef {
+    /// Accepts `owner/repo[@ref]` (GitHub) or a repository URL. URLs pointing at a
+    /// branch or tag (`.../tree/<ref>`, `.../-/tree/<ref>`) or ending in `@ref`
+    /// select that ref. Hosts other than github.com are treated as GitLab.
+    pub fn parse(input: &str) -> Result<Self, AppError> {
+        let invalid = |reason: &str| AppError::InvalidConfig {
+            key: "repository".to_string(),
+            value: format!("{input}: {reason}"),
+        };
+        let input = input.trim();
+
+        let (origin, path) = match input.split_once("://") {
+            Some(_) => {
+                let url = reqwest::Url::parse(input).map_err(|e| invalid(&e.to_string()))?;
+                (
+                    url.origin().ascii_serialization(),
+                    url.path().trim_matches('/').to_string(),
+                )
+            }
+            None => ("https://github.com".to_string(), input.to_string()),
+        };
+        let forge = if origin.ends_with("://github.com") || origin.ends_with("://www.github.com") {
+            Forge::GitHub
+        } else {
+            Forge::GitLab
+        };
+
+        let (path, mut git_ref) = match path.rsplit_once('@') {
+            Some((path, git_ref)) if !git_ref.is_empty() => {
+                (path.to_string(), Some(git_ref.to_string()))
+            }
+            _ => (path, None),
+        };
+        let mut segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+        let marker = match forge {
+            Forge::GitHub => segments
+                .iter()
+                .position(|s| *s == "tree" || *s == "releases"),
+            Forge::GitLab => segments.iter().position(|s| *s == "-"),
+        };
+        if let Some(at) = marker {
+            let rest = &segments[at..];
+            let tail = match rest {
+                ["releases", "tag", tail @ ..] | ["tree", tail @ ..] => tail,
+                ["-", "tree" | "tags" | "releases", tail @ ..] => tail,
+                _ => &[],
+            };
+            if git_ref.is_none() && !tail.is_empty() {
+                git_ref = Some(tail.join("/"));
+            }
+            segments.truncate(at);
+        }
+        if forge == Forge::GitHub {
+            segments.truncate(2);
+        }
+        let project = segments.join("/");
+        let project = project.trim_end_matches(".git");
+        if project.split('/').filter(|s| !s.is_empty()).count() < 2 {
+            return Err(invalid("expected owner/repo"));
+        }
+
+        Ok(Self {
+            forge,
+            origin,
+            project: project.to_string(),
+            git_ref,
+        })
+    }
+
+    /// The repository name, which is also the folder it installs into.
+    pub fn name(&self) -> &str {
+        self.project.rsplit('/').next().unwrap_or(&self.project)
+    }
+
+    pub fn url(&self) -> String {
+        format!("{}/{}", self.origin, self.project)
+    }
+}
+
+/// A release, or a tag without one.
+#[derive(Serialize, Debug, Clone)]
+pub struct RepoVersion {
+    pub tag: String,
+    pub name: String,
+    /// False for plain tags.
+    pub release: bool,
+    pub prerelease: bool,
+    pub published_at: Option<String>,
+    pub assets: Vec<RepoAsset>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct RepoAsset {
+    pub name: String,
+    pub download_url: String,
+}
+
+/// What installing from a repository produced; `track` records it.
+#[derive(Serialize)]
+pub struct RepoInstall {
+    pub installed: InstalledMod,
+    pub origin: RepoOrigin,
+}
+
+#[derive(Deserialize)]
+struct GitHubRelease {
+    tag_name: String,
+    name: Option<String>,
+    #[serde(default)]
+    draft: bool,
+    #[serde(default)]
+    prerelease: bool,
+    published_at: Option<String>,
+    #[serde(default)]
+    assets: Vec<GitHubAsset>,
+}
+
+#[derive(Deserialize)]
+struct GitHubAsset {
+    name: String,
+    browser_download_url: String,
+}
+
+#[derive(Deserialize)]
+struct GitLabRelease {
+    tag_name: String,
+    name: Option<String>,
+    released_at: Option<String>,
+    #[serde(default)]
+    upcoming_release: bool,
+    #[serde(default)]
+    assets: GitLabAssets,
+}
+
+#[derive(Deserialize, Default)]
+struct GitLabAssets {
+    #[serde(default)]
+    links: Vec<GitLabLink>,
+}
+
+#[derive(Deserialize)]
+struct GitLabLink {
+    name: String,
+    url: String,
+    direct_asset_url: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Tag {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Project {
+    default_branch: Option<String>,
+}
+
+pub struct ForgeClient {
+    http: reqwest::Client,
+    github_api: String,
+}
+
+impl ForgeClient {
+    pub fn new() -> Self {
+        Self::with_github_api(GITHUB_API)
+    }
+
+    /// A client sending GitHub API requests to `github_api` instead of api.github.com.
+    pub fn with_github_api(github_api: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            github_api: github_api.trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn api_base(&self, repo: &RepoRef) -> String {
+        match repo.forge {
+            Forge::GitHub => format!("{}/repos/{}", self.github_api, repo.project),
+            Forge::GitLab => format!(
+                "{}/api/v4/projects/{}",
+                repo.origin,
+                urlencoding::encode(&repo.project)
+            ),
+        }
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response, AppError> {
+        let network_error = |source: String| AppError::NetworkRequest {
+            url: url.to_string(),
+            source,
+        };
+        let resp = self
+            .http
+            .get(url)
+            .header(USER_AGENT, "Balatro-Mod-Manager/1.0")
+            .header(ACCEPT, "application/vnd.github+json")
+            .send()
+            .await
+            .map_err(|e| network_error(e.to_string()))?;
+        match resp.status() {
+            reqwest::StatusCode::TOO_MANY_REQUESTS => Err(AppError::ApiLimitExceeded),
+            // GitHub answers 403 once the unauthenticated rate limit is used up
+            reqwest::StatusCode::FORBIDDEN
+                if resp
+                    .headers()
+                    .get("x-ratelimit-remaining")
+                    .is_some_and(|v| v == "0") =>
+            {
+                Err(AppError::ApiLimitExceeded)
+            }
+            status if !status.is_success() => {
+                Err(network_error(format!("Repository API error: {status}")))
+            }
+            _ => Ok(resp),
+        }
+    }
+
+    async fn get_json<T: DeserializeOwned>(&self, url: &str) -> Result<T, AppError> {
+        parse_json(self.get(url).await?, url).await
+    }
+
+    // Every page of a list endpoint, up to MAX_PAGES of them
+    async fn get_all<T: DeserializeOwned>(&self, url: &str) -> Result<Vec<T>, AppError> {
+        let mut items = Vec::new();
+        let mut next = Some(format!("{url}?per_page={PER_PAGE}"));
+        for _ in 0..MAX_PAGES {
+            let Some(url) = next.take() else { break };
+            let resp = self.get(&url).await?;
+            next = next_page_url(&url, resp.headers());
+            items.extend(parse_json::<Vec<T>>(resp, &url).await?);
+        }
+        Ok(items)
+    }
+
+    /// Releases, newest first, followed by tags that have no release. Drafts are left out.
+    pub async fn list_versions(&self, repo: &RepoRef) -> Result<Vec<RepoVersion>, AppError> {
+        let base = self.api_base(repo);
+        let mut versions: Vec<RepoVersion> = match repo.forge {
+            Forge::GitHub => self
+                .get_all::<GitHubRelease>(&format!("{base}/releases"))
+                .await?
+                .into_iter()
+                .filter(|r| !r.draft)
+                .map(|r| RepoVersion {
+                    name: r
+                        .name
+                        .filter(|n| !n.is_empty())
+                        .unwrap_or(r.tag_name.clone()),
+                    tag: r.tag_name,
+                    release: true,
+                    prerelease: r.prerelease,
+                    published_at: r.published_at,
+                    assets: r
+                        .assets
+                        .into_iter()
+                        .map(|a| RepoAsset {
+                            name: a.name,
+                            download_url: a.browser_download_url,
+                        })
+                        .collect(),
+                })
+                .collect(),
+            Forge::GitLab => self
+                .get_all::<GitLabRelease>(&format!("{base}/releases"))
+                .await?
+                .into_iter()
+                .map(|r| RepoVersion {
+                    name: r
+                        .name
+                        .filter(|n| !n.is_empty())
+                        .unwrap_or(r.tag_name.clone()),
+                    tag: r.tag_name,
+                    release: true,
+                    prerelease: r.upcoming_release,
+                    published_at: r.released_at,
+                    assets: r
+                        .assets
+                        .links
+                        .into_iter()
+                        .map(|l| RepoAsset {
+                            name: l.name,
+                            download_url: l.direct_asset_url.unwrap_or(l.url),
+                        })
+                        .collect(),
+                })
+                .collect(),
+        };
+
+        let tags_url = match repo.forge {
+            Forge::GitHub => format!("{base}/tags"),
+            Forge::GitLab => format!("{base}/repository/tags"),
+        };
+        for tag in self.get_all::<Tag>(&tags_url).await? {
+            if !versions.iter().any(|v| v.tag == tag.name) {
+                versions.push(RepoVersion {
+                    name: tag.name.clone(),
+                    tag: tag.name,
+                    release: false,
+                    prerelease: false,
+                    published_at: None,
+                    assets: Vec::new(),
+                });
+            }
+        }
+        Ok(versions)
+    }
+
+    pub async fn default_branch(&self, repo: &RepoRef) -> Result<String, AppError> {
+        self.get_json::<Project>(&self.api_base(repo))
+            .await?
+            .default_branch
+            .ok_or_else(|| {
+                AppError::InvalidApiResponse(format!("{} has no default branch", repo.url()))
+            })
+    }
+
+    /// Source archive of a branch, tag or commit.
+    pub fn archive_url(&self, repo: &RepoRef, git_ref: &str) -> String {
+        let base = self.api_base(repo);
+        match repo.forge {
+            Forge::GitHub => format!("{base}/zipball/{}", urlencoding::encode(git_ref)),
+            Forge::GitLab => format!(
+                "{base}/repository/archive.zip?sha={}",
+                urlencoding::encode(git_ref)
+            ),
+        }
+    }
+
+    /// Download `version` (or the ref in `repo`, or the default branch) into the Mods
+    /// folder. With `asset`, that file of the release is installed instead of the
+    /// source archive; without a version it comes from the newest stable release.
+    pub async fn install(
+        &self,
+        repo: &RepoRef,
+        version: Option<&str>,
+        asset: Option<&str>,
+    ) -> Result<RepoInstall, AppError> {
+        let version = version.or(repo.git_ref.as_deref());
+        let (git_ref, url) = match asset {
+            Some(asset) => {
+                let versions = self.list_versions(repo).await?;
+                let release = versions
+                    .iter()
+                    .filter(|v| v.release)
+                    .find(|v| match version {
+                        Some(tag) => v.tag == tag,
+                        None => !v.prerelease,
+                    })
+                    .ok_or_else(|| AppError::ModNotFound {
+                        mod_name: repo.url(),
+                        version: version.unwrap_or("latest release").to_string(),
+                    })?;
+                let file = release
+                    .assets
+                    .iter()
+                    .find(|a| a.name == asset)
+                    .ok_or_else(|| AppError::ModNotFound {
+                        mod_name: format!("{} asset {asset}", repo.url()),
+                        version: release.tag.clone(),
+                    })?;
+                (release.tag.clone(), file.download_url.clone())
+            }
+            None => {
+                let git_ref = match version {
+                    Some(v) => v.to_string(),
+                    None => self.default_branch(repo).await?,
+                };
+                let url = self.archive_url(repo, &git_ref);
+                (git_ref, url)
+            }
+        };
+
+        let name = repo.name().to_string();
+        let path = installer::install_mod(url, Some(name.clone())).await?;
+        Ok(RepoInstall {
+            installed: InstalledMod {
+                name: name.clone(),
+                path: path.to_string_lossy().into_owned(),
+                dependencies: Vec::new(),
+                current_version: Some(git_ref.clone()),
+            },
+            origin: RepoOrigin {
+                mod_name: name,
+                repo_url: repo.url(),
+                git_ref,
+                asset: asset.map(str::to_string),
+            },
+        })
+    }
+
+    /// The newest stable release when it is newer than the release or tag the mod was
+    /// installed from. Mods installed from a branch, or from something newer than the
+    /// latest stable release (a prerelease, say), return `None`.
+    pub async fn check_update(&self, origin: &RepoOrigin) -> Result<Option<String>, AppError> {
+        let repo = RepoRef::parse(&origin.repo_url)?;
+        let versions = self.list_versions(&repo).await?;
+        Ok(newer_stable_release(&versions, &origin.git_ref).map(|v| v.tag.clone()))
+    }
+}
+
+// Releases are listed newest first; plain tags follow them and only compare by date
+fn newer_stable_release<'a>(versions: &'a [RepoVersion], git_ref: &str) -> Option<&'a RepoVersion> {
+    let installed_at = versions.iter().position(|v| v.tag == git_ref)?;
+    let installed = &versions[installed_at];
+    let (latest_at, latest) = versions
+        .iter()
+        .enumerate()
+        .find(|(_, v)| v.release && !v.prerelease)?;
+    let newer = match (&latest.published_at, &installed.published_at) {
+        // RFC 3339 timestamps from one forge compare chronologically as strings
+        (Some(latest), Some(installed)) => latest > installed,
+        _ => installed.release && latest_at < installed_at,
+    };
+    newer.then_some(latest)
+}
+
+async fn parse_json<T: DeserializeOwned>(
+    resp: reqwest::Response,
+    url: &str,
+) -> Result<T, AppError> {
+    resp.json()
+        .await
+        .map_err(|e| AppError::InvalidApiResponse(format!("Failed to parse {url}: {e}")))
+}
+
+/// The next page of a list: both forges link it in `Link`, and GitLab also
+/// sends its number in `x-next-page`.
+fn next_page_url(url: &str, headers: &HeaderMap) -> Option<String> {
+    if let Some(link) = headers.get(LINK).and_then(|v| v.to_str().ok()) {
+        return link.split(',').find_map(|part| {
+            let (target, params) = part.split_once(';')?;
+            params
+                .split(';')
+                .any(|p| p.trim() == "rel=\"next\"")
+                .then(|| target.trim().trim_matches(['<', '>']).to_string())
+        });
+    }
+    let page = headers.get("x-next-page")?.to_str().ok()?.trim();
+    if page.is_empty() {
+        return None;
+    }
+    let mut next = reqwest::Url::parse(url).ok()?;
+    let query: Vec<(String, String)> = next
+        .query_pairs()
+        .filter(|(k, _)| k != "page")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    next.query_pairs_mut()
+        .clear()
+        .extend_pairs(query)
+        .append_pair("page", page);
+    Some(next.into())
+}
+
+impl Default for ForgeClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Record an install from a repository, including where it came from.
+pub fn track(db: &Database, install: &RepoInstall) -> Result<(), AppError> {
+    let installed = &install.installed;
+    db.add_installed_mod(
+        &installed.name,
+        &installed.path,
+        &installed.dependencies,
+        installed.current_version.clone(),
+    )?;
+    db.set_repo_origin(&install.origin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_shorthand_and_urls() {
+        let repo = RepoRef::parse("Steamodded/smods@1.0.0-beta").unwrap();
+        assert_eq!(repo.forge, Forge::GitHub);
+        assert_eq!(repo.project, "Steamodded/smods");
+        assert_eq!(repo.git_ref.as_deref(), Some("1.0.0-beta"));
+        assert_eq!(repo.url(), "https://github.com/Steamodded/smods");
+
+        let repo =
+            RepoRef::parse("https://github.com/MathIsFun0/Talisman/tree/dev/nested").unwrap();
+        assert_eq!(repo.project, "MathIsFun0/Talisman");
+        assert_eq!(repo.git_ref.as_deref(), Some("dev/nested"));
+        assert_eq!(
+            RepoRef::parse("https://github.com/a/b.git")
+                .unwrap()
+                .project,
+            "a/b"
+        );
+
+        let repo =
+            RepoRef::parse("https://gitlab.example.org/group/sub/Cool-Mod/-/tags/v2").unwrap();
+        assert_eq!(repo.forge, Forge::GitLab);
+        assert_eq!(repo.project, "group/sub/Cool-Mod");
+        assert_eq!(repo.name(), "Cool-Mod");
+        assert_eq!(repo.git_ref.as_deref(), Some("v2"));
+
+        assert!(RepoRef::parse("just-a-name").is_err());
+    }
+
+    #[test]
+    fn follows_link_and_gitlab_next_page_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            LINK,
+            "<https://api.github.com/repositories/1/releases?per_page=100&page=2>; rel=\"next\", \
+             <https://api.github.com/repositories/1/releases?per_page=100&page=3>; rel=\"last\""
+                .parse()
+                .unwrap(),
+        );
+        assert_eq!(
+            next_page_url("https://api.github.com/repos/a/b/releases", &headers).as_deref(),
+            Some("https://api.github.com/repositories/1/releases?per_page=100&page=2")
+        );
+
+        let url = "https://gitlab.com/api/v4/projects/a%2Fb/releases?per_page=100&page=2";
+        let mut headers = HeaderMap::new();
+        headers.insert("x-next-page", "3".parse().unwrap());
+        assert_eq!(
+            next_page_url(url, &headers).as_deref(),
+            Some("https://gitlab.com/api/v4/projects/a%2Fb/releases?per_page=100&page=3")
+        );
+        // The last page has an empty x-next-page
+        headers.insert("x-next-page", "".parse().unwrap());
+        assert_eq!(next_page_url(url, &headers), None);
+    }
+}
@@ -0,0 +1,312 @@
+use crate::errors::AppError;
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+lazy_static! {
+    // `INFO - [G] text`: Lovely's level and source prefix on every log line
+    static ref LOG_PREFIX: Regex =
+        Regex::new(r"^(?:TRACE|DEBUG|INFO|WARN|ERROR) - \[[^\]]*\] ?").unwrap();
+    // Steamodded's crash handler: `(3) Lua method 'update' at file 'cardarea.lua:123'`
+    static ref SMODS_FRAME: Regex = Regex::new(
+        r"^\(\d+\) (?:Lua|C) (?:\w+ )?(?:'(?P<func>[^']*)' )?at (?:[\w ]+ )?file '(?P<file>.+?):(?P<line>\d+)'"
+    )
+    .unwrap();
+    // Plain Lua traceback: `main.lua:45: in function 'x'`
+    static ref LUA_FRAME: Regex =
+        Regex::new(r"^(?P<file>.+?):(?P<line>\d+): in (?P<func>.+)$").unwrap();
+    static ref SMODS_CHUNK: Regex = Regex::new(r#"\[SMODS (?P<id>[^ \]"]+) ""#).unwrap();
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StackFrame {
+    pub raw: String,
+    pub file: Option<String>,
+    pub line: Option<u32>,
+    pub function: Option<String>,
+    /// Mod folder (or Steamodded mod id) the frame's code belongs to.
+    pub mod_name: Option<String>,
+}
+
+/// A Lua crash extracted from a Lovely game log.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct GameCrash {
+    pub log_file: PathBuf,
+    pub message: String,
+    /// Steamodded's "Additional Context" block (game/Lovely versions, loaded mods).
+    pub context: Vec<String>,
+    pub frames: Vec<StackFrame>,
+    /// Mods appearing in the message or stack, most relevant first.
+    pub suspected_mods: Vec<String>,
+}
+
+/// Lovely writes one log per game run to `Mods/lovely/log`.
+pub fn log_dir(mods_dir: &Path) -> PathBuf {
+    mods_dir.join("lovely").join("log")
+}
+
+/// The most recently modified Lovely log, if any.
+pub fn latest_log(log_dir: &Path) -> Option<PathBuf> {
+    let entries = fs::read_dir(log_dir).ok()?;
+    entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().is_some_and(|ext| ext == "log"))
+        .max_by_key(|p| p.metadata().and_then(|m| m.modified()).ok())
+}
+
+pub fn read_latest_log(mods_dir: &Path) -> Result<Option<(PathBuf, String)>, AppError> {
+    let Some(path) = latest_log(&log_dir(mods_dir)) else {
+        return Ok(None);
+    };
+    let data = fs::read(&path).map_err(|e| AppError::FileRead {
+        path: path.clone(),
+        source: e.to_string(),
+    })?;
+    Ok(Some((path, String::from_utf8_lossy(&data).into_owned())))
+}
+
+/// Parse the crash of the latest game run, if it crashed.
+pub fn latest_crash(mods_dir: &Path) -> Result<Option<GameCrash>, AppError> {
+    let Some((path, text)) = read_latest_log(mods_dir)? else {
+        return Ok(None);
+    };
+    let mod_folders = list_mod_folders(mods_dir);
+    Ok(parse_crash(&text, &mod_folders).map(|crash| GameCrash {
+        log_file: path,
+        ..crash
+    }))
+}
+
+fn list_mod_folders(mods_dir: &Path) -> Vec<String> {
+    fs::read_dir(mods_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|e| e.path().is_dir())
+                .map(|e| e.file_name().to_string_lossy().to_string())
+                .filter(|n| n != "lovely" && n != ".lovely")
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Extract the last crash in a game log. `mod_folders` are the folder names in
+/// the Mods directory, used to attribute stack frames to mods.
+pub fn parse_crash(text: &str, mod_folders: &[String]) -> Option<GameCrash> {
+    let stripped: Vec<String> = text
+        .lines()
+        .map(|l| LOG_PREFIX.replace(l, "").into_owned())
+        .collect();
+    let lines: Vec<&str> = stripped.iter().map(String::as_str).collect();
+    parse_crash_lines(&lines, mod_folders)
+}
+
+fn parse_crash_lines(lines: &[&str], mod_folders: &[String]) -> Option<GameCrash> {
+    let is_stack_header = |l: &str| {
+        let l = l.trim();
+        l.eq_ignore_ascii_case("stack traceback:")
+            || l.eq_ignore_ascii_case("stack traceback")
+            || l == "Traceback"
+    };
+
+    let (message_start, stack_start) = match lines
+        .iter()
+        .rposition(|l| l.contains("Oops! The game crashed"))
+    {
+        Some(i) => {
+            let stack = lines[i..]
+                .iter()
+                .position(|l| is_stack_header(l))
+                .map(|p| p + i);
+            (i + 1, stack)
+        }
+        None => {
+            // Stock LÖVE/Lua traceback: the message is the block right before it
+            let stack = lines.iter().rposition(|l| is_stack_header(l))?;
+            let mut start = stack;
+            while start > 0 && !lines[start - 1].trim().is_empty() {
+                start -= 1;
+            }
+            if start == stack && start > 0 {
+                // Blank line between message and traceback (LÖVE's error screen)
+                start -= 1;
+                while start > 0 && !lines[start - 1].trim().is_empty() {
+                    start -= 1;
+                }
+            }
+            (start, Some(stack))
+        }
+    };
+
+    let section_end = stack_start.unwrap_or(lines.len());
+    let context_start = lines[message_start..section_end]
+        .iter()
+        .position(|l| l.trim() == "Additional Context:")
+        .map(|p| p + message_start);
+
+    let message = lines[message_start..context_start.unwrap_or(section_end)]
+        .iter()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && *l != "Error")
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let context = context_start
+        .map(|start| {
+            lines[start + 1..section_end]
+                .iter()
+                .map(|l| l.trim_end().to_string())
+                .filter(|l| !l.trim().is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut frames = Vec::new();
+    if let Some(stack) = stack_start {
+        for line in &lines[stack + 1..] {
+            let trimmed = line.trim();
+            if trimmed.chars().all(|c| c == '=') && !trimmed.is_empty() {
+                continue;
+            }
+            if trimmed.is_empty() {
+                if frames.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            match parse_frame(trimmed, mod_folders) {
+                Some(frame) => frames.push(frame),
+                None if frames.is_empty() => continue,
+                None => break,
+            }
+        }
+    }
+
+    if message.is_empty() && frames.is_empty() {
+        return None;
+    }
+
+    let mut suspected_mods = Vec::new();
+    for name in attribute(&message, mod_folders)
+        .into_iter()
+        .chain(frames.iter().filter_map(|f| f.mod_name.clone()))
+    {
+        if !suspected_mods.contains(&name) {
+            suspected_mods.push(name);
+        }
+    }
+
+    Some(GameCrash {
+        log_file: PathBuf::new(),
+        message,
+        context,
+        frames,
+        suspected_mods,
+    })
+}
+
+fn parse_frame(line: &str, mod_folders: &[String]) -> Option<StackFrame> {
+    let mod_name = attribute(line, mod_folders);
+    if let Some(caps) = SMODS_FRAME
+        .captures(line)
+        .or_else(|| LUA_FRAME.captures(line))
+    {
+        return Some(StackFrame {
+            raw: line.to_string(),
+            file: caps.name("file").map(|m| m.as_str().to_string()),
+            line: caps.name("line").and_then(|m| m.as_str().parse().ok()),
+            function: caps.name("func").map(|m| m.as_str().to_string()),
+            mod_name,
+        });
+    }
+    // `[C]: in function 'error'` and similar frames without a line number
+    if line.starts_with("[C]") || line.starts_with('(') {
+        return Some(StackFrame {
+            raw: line.to_string(),
+            file: None,
+            line: None,
+            function: None,
+            mod_name,
+        });
+    }
+    None
+}
+
+// Which mod a piece of a log line points at, by chunk name or file path
+fn attribute(text: &str, mod_folders: &[String]) -> Option<String> {
+    if let Some(id) = SMODS_CHUNK.captures(text).map(|c| c["id"].to_string()) {
+        let folder = mod_folders.iter().find(|f| f.eq_ignore_ascii_case(&id));
+        return Some(folder.cloned().unwrap_or(id));
+    }
+
+    let normalized = text.replace('\\', "/");
+    mod_folders
+        .iter()
+        .find(|folder| {
+            normalized.contains(&format!("Mods/{folder}/"))
+                || normalized.contains(&format!("'{folder}/"))
+                || normalized.starts_with(&format!("{folder}/"))
+        })
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn folders() -> Vec<String> {
+        vec!["Cryptid".to_string(), "Talisman".to_string()]
+    }
+
+    #[test]
+    fn parses_steamodded_crash_report() {
+        let log = r#"INFO - [♥] Lovely 0.7.1
+INFO - [G] Loading mods
+ERROR - [G] Oops! The game crashed:
+ERROR - [G] [SMODS Cryptid "items/joker.lua"]:123: attempt to index a nil value (field 'x')
+ERROR - [G]
+ERROR - [G] Additional Context:
+ERROR - [G] Balatro Version: 1.0.1o-FULL
+ERROR - [G] Lovely Version: 0.7.1
+ERROR - [G]
+ERROR - [G] Stack Traceback
+ERROR - [G] ===============
+ERROR - [G] (3) Lua method 'calculate' at file 'Mods/Talisman/talisman.lua:88'
+ERROR - [G] (4) Lua function '?' at file 'card.lua:2000' (best guess)
+ERROR - [G] (5) C function 'pcall'
+"#;
+        let crash = parse_crash(log, &folders()).unwrap();
+        assert_eq!(
+            crash.message,
+            "[SMODS Cryptid \"items/joker.lua\"]:123: attempt to index a nil value (field 'x')"
+        );
+        assert_eq!(crash.context.len(), 2);
+        assert_eq!(crash.frames.len(), 3);
+        assert_eq!(
+            crash.frames[0].file.as_deref(),
+            Some("Mods/Talisman/talisman.lua")
+        );
+        assert_eq!(crash.frames[0].line, Some(88));
+        assert_eq!(crash.frames[0].function.as_deref(), Some("calculate"));
+        assert_eq!(crash.frames[1].mod_name, None);
+        assert_eq!(crash.suspected_mods, vec!["Cryptid", "Talisman"]);
+    }
+
+    #[test]
+    fn parses_plain_lua_traceback() {
+        let log = "Error\n\n[SMODS cryptid \"lib/misc.lua\"]:5: boom\n\nTraceback\n\n[C]: in function 'error'\n[SMODS cryptid \"lib/misc.lua\"]:5: in function 'f'\nmain.lua:10: in main chunk\n\nother output\n";
+        let crash = parse_crash(log, &folders()).unwrap();
+        assert_eq!(crash.message, "[SMODS cryptid \"lib/misc.lua\"]:5: boom");
+        assert_eq!(crash.frames.len(), 3);
+        assert_eq!(crash.frames[1].line, Some(5));
+        assert_eq!(crash.suspected_mods, vec!["Cryptid"]);
+    }
+
+    #[test]
+    fn clean_log_has_no_crash() {
+        assert!(parse_crash("INFO - [G] all good\n", &folders()).is_none());
+    }
+}
@@ -1,3 +1,4 @@
+use crate::config_preserve::{self, ConfigResolution, ConfigStore};
 use crate::errors::AppError;
 use flate2::read::GzDecoder;
 use reqwest::header::{CONTENT_DISPOSITION, CONTENT_TYPE};
@@ -61,10 +62,7 @@ pub async fn install_mod(url: String, folder_name: Option<String>) -> Result<Pat
         )
     })?;
 
-    let mod_dir = dirs::config_dir()
-        .ok_or_else(|| AppError::DirNotFound(PathBuf::from("config directory")))?
-        .join("Balatro")
-        .join("Mods");
+    let mod_dir = crate::paths::mods_dir()?;
 
     let mod_name = {
         if let Some(name) = folder_name.filter(|n| !n.is_empty()) {
@@ -93,9 +91,11 @@ pub async fn install_mod(url: String, folder_name: Option<String>) -> Result<Pat
         }
     };
 
-    // Uninstall old mod folder if it exists
+    // Uninstall old mod folder if it exists, keeping its user config files
     let target_dir = mod_dir.join(&mod_name);
+    let mut preserved = None;
     if target_dir.exists() {
+        preserved = Some(config_preserve::capture(&target_dir)?);
         log::info!("Uninstalling existing mod at: {target_dir:?}");
         uninstall_mod(target_dir.clone())?;
     }
@@ -108,10 +108,48 @@ pub async fn install_mod(url: String, folder_name: Option<String>) -> Result<Pat
         ArchiveKind::TarGz => handle_tar_gz(file, &mod_dir, &mod_name)?,
     };
 
+    restore_user_config(&mod_name, &installed_path, preserved);
+
     log::info!("Mod installed successfully at: {installed_path:?}");
     Ok(installed_path)
 }
 
+fn restore_user_config(
+    mod_name: &str,
+    installed_path: &Path,
+    preserved: Option<config_preserve::PreservedConfigs>,
+) {
+    let result = ConfigStore::new().and_then(|store| match preserved {
+        Some(preserved) if !preserved.is_empty() => store
+            .carry_over(mod_name, installed_path, preserved)
+            .map(|outcomes| {
+                let conflicts = outcomes
+                    .iter()
+                    .filter(|o| o.resolution == ConfigResolution::Conflict)
+                    .count();
+                if conflicts > 0 {
+                    log::warn!("{conflicts} config file(s) of {mod_name} need a manual merge");
+                }
+            }),
+        _ => store.record_defaults(mod_name, installed_path),
+    });
+    if let Err(e) = result {
+        log::error!("Failed to carry over config files for {mod_name}: {e}");
+    }
+}
+
+/// Drop the recorded config defaults and pending merges of the mod uninstalled from
+/// `installed_path`, so a later install of it starts fresh. Configs are recorded under
+/// the install folder name, not the catalog title.
+pub fn forget_user_config(installed_path: &Path) {
+    let Some(folder) = installed_path.file_name().and_then(|n| n.to_str()) else {
+        return;
+    };
+    if let Err(e) = ConfigStore::new().and_then(|store| store.forget(folder)) {
+        log::warn!("Failed to forget config files of {folder}: {e}");
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 enum ArchiveKind {
     Zip,
@@ -468,10 +506,7 @@ fn ensure_safe_path(base: &Path, path: &Path) -> Result<(), AppError> {
 pub fn uninstall_mod(path: PathBuf) -> Result<(), AppError> {
     log::info!("Uninstalling mod: {path:?}");
 
-    let mods_dir = dirs::config_dir()
-        .ok_or_else(|| AppError::DirNotFound(PathBuf::from("config directory")))?
-        .join("Balatro")
-        .join("Mods");
+    let mods_dir = crate::paths::mods_dir()?;
 
     validate_uninstall_path(&path, &mods_dir)?;
 
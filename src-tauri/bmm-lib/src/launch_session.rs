@@ -0,0 +1,257 @@
+use crate::finder::{find_balatro_pid, is_process_running};
+use crate::game_log::{self, GameCrash};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+// Steam can take a while to bring the game up after `-applaunch`
+const DISCOVERY_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// What gets loaded into the game for a launch.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchVariant {
+    /// Lovely and every enabled mod.
+    #[default]
+    Modded,
+    /// No Lovely injection at all.
+    Vanilla,
+    /// Lovely with only Steamodded; other mods are disabled until the game exits.
+    SafeMode,
+}
+
+impl LaunchVariant {
+    pub fn injects_lovely(self) -> bool {
+        self != LaunchVariant::Vanilla
+    }
+
+    /// Launch mode label stored with the session, e.g. `proton` or `native-vanilla`.
+    pub fn session_label(self, launch_mode: &str) -> String {
+        match self {
+            LaunchVariant::Modded => launch_mode.to_string(),
+            LaunchVariant::Vanilla => format!("{launch_mode}-vanilla"),
+            LaunchVariant::SafeMode => format!("{launch_mode}-safe"),
+        }
+    }
+}
+
+/// How the game process of a launch is reached.
+pub enum GameProcess {
+    /// We spawned the game (or a wrapper that lives as long as it) ourselves.
+    Child(Child),
+    /// Launched through Steam, a terminal or similar; found by name once it shows up.
+    Detached,
+}
+
+/// Emitted as `game-started`.
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionStarted {
+    pub session_id: Option<i64>,
+    pub launch_mode: String,
+    pub started_at: u64,
+    pub pid: Option<u32>,
+}
+
+/// Emitted as `game-exited`.
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionOutcome {
+    pub session_id: Option<i64>,
+    pub launch_mode: String,
+    pub started_at: u64,
+    pub ended_at: u64,
+    pub duration_ms: u64,
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    pub crashed: bool,
+    pub crash: Option<GameCrash>,
+}
+
+pub struct LaunchSession {
+    session_id: Option<i64>,
+    launch_mode: String,
+    started: SystemTime,
+    process: GameProcess,
+    mods_dir: PathBuf,
+    discovery_timeout: Duration,
+}
+
+impl LaunchSession {
+    /// `session_id` is the `launch_sessions` row, if it could be recorded.
+    pub fn new(
+        session_id: Option<i64>,
+        launch_mode: &str,
+        started: SystemTime,
+        process: GameProcess,
+        mods_dir: &Path,
+    ) -> Self {
+        Self {
+            session_id,
+            launch_mode: launch_mode.to_string(),
+            started,
+            process,
+            mods_dir: mods_dir.to_path_buf(),
+            discovery_timeout: DISCOVERY_TIMEOUT,
+        }
+    }
+
+    pub fn started_event(&self) -> SessionStarted {
+        SessionStarted {
+            session_id: self.session_id,
+            launch_mode: self.launch_mode.clone(),
+            started_at: unix_millis(self.started),
+            pid: match &self.process {
+                GameProcess::Child(child) => Some(child.id()),
+                GameProcess::Detached => None,
+            },
+        }
+    }
+
+    /// Wait for the game to exit on a background thread, then hand the outcome to `on_exit`.
+    pub fn watch<F>(self, on_exit: F) -> JoinHandle<()>
+    where
+        F: FnOnce(Option<SessionOutcome>) + Send + 'static,
+    {
+        thread::spawn(move || on_exit(self.wait()))
+    }
+
+    /// Block until the game exits. `None` when a detached launch never showed up:
+    /// the game may still be starting, so there is no exit to report.
+    pub fn wait(self) -> Option<SessionOutcome> {
+        let (pid, exit_code) = match self.process {
+            GameProcess::Child(mut child) => {
+                let pid = child.id();
+                let code = match child.wait() {
+                    Ok(status) => status.code(),
+                    Err(e) => {
+                        log::warn!("Failed to wait for game process {pid}: {e}");
+                        None
+                    }
+                };
+                (Some(pid), code)
+            }
+            GameProcess::Detached => {
+                let Some(pid) = wait_for_pid(self.discovery_timeout) else {
+                    log::warn!("Balatro process did not show up after launch");
+                    return None;
+                };
+                while is_process_running(pid) {
+                    thread::sleep(POLL_INTERVAL);
+                }
+                // The exit code of a process we didn't spawn isn't observable
+                (Some(pid), None)
+            }
+        };
+
+        let ended = SystemTime::now();
+        let crash = crash_since(&self.mods_dir, self.started);
+        Some(SessionOutcome {
+            session_id: self.session_id,
+            launch_mode: self.launch_mode,
+            started_at: unix_millis(self.started),
+            ended_at: unix_millis(ended),
+            duration_ms: ended
+                .duration_since(self.started)
+                .unwrap_or_default()
+                .as_millis() as u64,
+            pid,
+            exit_code,
+            crashed: crash.is_some() || exit_code.is_some_and(|c| c != 0),
+            crash,
+        })
+    }
+}
+
+fn wait_for_pid(timeout: Duration) -> Option<u32> {
+    let deadline = SystemTime::now() + timeout;
+    loop {
+        if let Some(pid) = find_balatro_pid() {
+            return Some(pid);
+        }
+        if SystemTime::now() >= deadline {
+            return None;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+// A crash in a Lovely log written during this session; older logs belong to earlier runs
+fn crash_since(mods_dir: &Path, started: SystemTime) -> Option<GameCrash> {
+    let log = game_log::latest_log(&game_log::log_dir(mods_dir))?;
+    let modified = log.metadata().and_then(|m| m.modified()).ok()?;
+    if modified < started {
+        return None;
+    }
+    match game_log::latest_crash(mods_dir) {
+        Ok(crash) => crash,
+        Err(e) => {
+            log::warn!("Failed to read game log after exit: {e}");
+            None
+        }
+    }
+}
+
+pub fn unix_millis(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process::Command;
+    use std::sync::mpsc;
+
+    #[cfg(unix)]
+    #[test]
+    fn child_exit_is_reported_with_crash_from_new_log() {
+        let tmp = tempfile::tempdir().unwrap();
+        let log_dir = game_log::log_dir(tmp.path());
+        std::fs::create_dir_all(&log_dir).unwrap();
+
+        let started = SystemTime::now() - Duration::from_secs(1);
+        let child = Command::new("sh").args(["-c", "exit 3"]).spawn().unwrap();
+        std::fs::write(
+            log_dir.join("lovely-run.log"),
+            "ERROR - [G] Oops! The game crashed:\nERROR - [G] main.lua:1: boom\n",
+        )
+        .unwrap();
+
+        let session = LaunchSession::new(
+            Some(7),
+            "native",
+            started,
+            GameProcess::Child(child),
+            tmp.path(),
+        );
+        assert!(session.started_event().pid.is_some());
+
+        let (tx, rx) = mpsc::channel();
+        session
+            .watch(move |outcome| tx.send(outcome).unwrap())
+            .join()
+            .unwrap();
+        let outcome = rx.recv().unwrap().unwrap();
+        assert_eq!(outcome.session_id, Some(7));
+        assert_eq!(outcome.exit_code, Some(3));
+        assert!(outcome.crashed);
+        assert_eq!(outcome.crash.unwrap().message, "main.lua:1: boom");
+    }
+
+    #[test]
+    fn detached_game_that_never_shows_up_has_no_outcome() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut session = LaunchSession::new(
+            Some(1),
+            "proton",
+            SystemTime::now(),
+            GameProcess::Detached,
+            tmp.path(),
+        );
+        session.discovery_timeout = Duration::ZERO;
+        assert!(session.wait().is_none());
+    }
+}
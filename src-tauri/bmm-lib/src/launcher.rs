@@ -0,0 +1,345 @@
+use crate::database::Database;
+use crate::errors::AppError;
+use crate::launch_session::{GameProcess, LaunchVariant};
+use crate::lovely::{LinuxLaunchMode, LovelyTarget};
+use crate::safe_mode;
+use crate::save_backup::{SaveBackupManager, SaveBackupReason};
+use crate::stores::{self, LaunchMethod};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Everything a launch needs, resolved up front so no database access happens
+/// while the game is being started.
+#[derive(Debug, Clone)]
+pub struct LaunchOptions {
+    pub game_dir: PathBuf,
+    pub method: LaunchMethod,
+    pub variant: LaunchVariant,
+    pub lovely_console: bool,
+    /// Only used on Linux; `None` detects the mode from the install.
+    pub linux_mode: Option<LinuxLaunchMode>,
+    /// Lovely release to install when it is missing; `None` means latest.
+    pub lovely_tag: Option<String>,
+    /// How many save snapshots to keep when one is taken before the launch.
+    pub save_retention: usize,
+}
+
+impl LaunchOptions {
+    /// Options for the active install, with the user's console and launch mode settings.
+    pub fn from_db(db: &Database, variant: LaunchVariant) -> Result<Self, AppError> {
+        let install = db
+            .get_active_installation()?
+            .ok_or_else(|| AppError::InvalidState("No installation path set".to_string()))?;
+        let game_dir = PathBuf::from(&install.path);
+        // Installs registered before their launch method was recorded are resolved once
+        let method = match install.launch_method {
+            Some(method) => method,
+            None => {
+                let method = stores::resolve_launch_method(&game_dir, install.platform);
+                db.set_launch_method(install.id, &method)?;
+                method
+            }
+        };
+        // A pinned version must survive a reinstall of the binary
+        let lovely_tag = crate::lovely::pinned_tag(db)?;
+        Ok(Self {
+            game_dir,
+            method,
+            variant,
+            lovely_console: db.is_lovely_console_enabled()?,
+            linux_mode: db.get_linux_launch_mode()?,
+            lovely_tag,
+            save_retention: db.get_save_backup_retention()?,
+        })
+    }
+
+    /// The install Lovely has to be set up in for this launch.
+    pub fn lovely_target(&self) -> LovelyTarget {
+        LovelyTarget {
+            game_dir: self.game_dir.clone(),
+            linux_mode: self.linux_mode,
+        }
+    }
+}
+
+/// Snapshot the saves, disable all but Steamodded for a safe-mode launch and start
+/// the game the way its install's launch method asks for. Returns the launch mode
+/// label, the start time and the process to watch; call [`finish`] once it exits.
+pub async fn spawn(
+    options: &LaunchOptions,
+) -> Result<(&'static str, SystemTime, GameProcess), AppError> {
+    match SaveBackupManager::new(options.save_retention) {
+        Ok(saves) => saves.snapshot_quietly(SaveBackupReason::Launch),
+        Err(e) => log::warn!(
+            "Failed to snapshot save files ({:?}): {e}",
+            SaveBackupReason::Launch
+        ),
+    }
+    let safe_mode_file = safe_mode::default_state_file()?;
+    if options.variant == LaunchVariant::SafeMode {
+        safe_mode::enter(&crate::paths::mods_dir()?, &safe_mode_file)?;
+    } else {
+        // A safe-mode game that was never seen leaves its mods disabled until now
+        safe_mode::restore(&safe_mode_file)?;
+    }
+
+    let spawned = start(options).await;
+    if spawned.is_err() {
+        if let Err(e) = finish(options.variant) {
+            log::error!("Failed to restore mods after safe mode: {e}");
+        }
+    }
+    spawned
+}
+
+/// Undo what [`spawn`] changed for the launch: mods disabled for safe mode are enabled again.
+pub fn finish(variant: LaunchVariant) -> Result<(), AppError> {
+    if variant == LaunchVariant::SafeMode {
+        safe_mode::restore(&safe_mode::default_state_file()?)?;
+    }
+    Ok(())
+}
+
+async fn start(
+    options: &LaunchOptions,
+) -> Result<(&'static str, SystemTime, GameProcess), AppError> {
+    let launch_mode = match &options.method {
+        LaunchMethod::Heroic { .. } => "heroic",
+        LaunchMethod::Lutris { .. } => "lutris",
+        LaunchMethod::Steam | LaunchMethod::Direct { .. } => return spawn_game(options).await,
+    };
+    // The launcher owns the Wine prefix and Lovely setup, so Lovely can't be left out
+    if !options.variant.injects_lovely() {
+        return Err(AppError::InvalidState(
+            "Vanilla launch isn't available for installs started through Heroic or Lutris"
+                .to_string(),
+        ));
+    }
+    let uri = options
+        .method
+        .launcher_uri()
+        .ok_or_else(|| AppError::InvalidState(format!("No launcher URI for {launch_mode}")))?;
+    let started = SystemTime::now();
+    open_uri(&uri)?;
+    Ok((launch_mode, started, GameProcess::Detached))
+}
+
+#[cfg(target_os = "macos")]
+async fn spawn_game(
+    options: &LaunchOptions,
+) -> Result<(&'static str, SystemTime, GameProcess), AppError> {
+    use std::process::Command;
+
+    let path = &options.game_dir;
+    let balatro_executable = path.join("Balatro.app/Contents/MacOS/love");
+    let inject = if options.variant.injects_lovely() {
+        let lovely_path =
+            crate::lovely::ensure_lovely(&options.lovely_target(), options.lovely_tag.as_deref())
+                .await?;
+        format!("DYLD_INSERT_LIBRARIES='{}' ", lovely_path.display())
+    } else {
+        String::new()
+    };
+
+    if options.lovely_console && options.variant.injects_lovely() {
+        let command_line = format!(
+            "cd '{}' && {}'{}'",
+            path.display(),
+            inject,
+            balatro_executable.display(),
+        );
+
+        let applescript = format!("tell application \"Terminal\" to do script \"{command_line}\"");
+
+        let started = SystemTime::now();
+        Command::new("osascript")
+            .arg("-e")
+            .arg(applescript)
+            .status()
+            .map_err(|e| AppError::ProcessExecution(e.to_string()))?;
+        Ok(("terminal", started, GameProcess::Detached))
+    } else {
+        let cmd = format!("{}'{}'", inject, balatro_executable.display());
+        // Spawn the process without waiting so the caller doesn't block
+        let started = SystemTime::now();
+        let child = Command::new("sh")
+            .arg("-c")
+            .arg(cmd)
+            .spawn()
+            .map_err(|e| AppError::ProcessExecution(e.to_string()))?;
+        Ok(("native", started, GameProcess::Child(child)))
+    }
+}
+
+#[cfg(target_os = "windows")]
+async fn spawn_game(
+    options: &LaunchOptions,
+) -> Result<(&'static str, SystemTime, GameProcess), AppError> {
+    use std::os::windows::process::CommandExt;
+    use std::process::Command;
+    const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+    let executable = match &options.method {
+        LaunchMethod::Direct { executable } => executable.clone(),
+        _ => options.game_dir.join("Balatro.exe"),
+    };
+    let mut cmd = Command::new(executable);
+
+    if !options.variant.injects_lovely() {
+        // version.dll is picked up from the game folder; Lovely steps aside when asked
+        cmd.arg("--vanilla");
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    } else if !options.lovely_console {
+        // Respect the "Enable Lovely Console" setting on Windows by hiding the console
+        // when disabled. If enabled, let the process manage its own console normally.
+        // Ask Lovely to suppress its console and also prevent a console window
+        // from being created for the process.
+        cmd.arg("--disable-console");
+        cmd.env("LOVELY_DISABLE_CONSOLE", "1");
+        cmd.env("LOVELY_NO_CONSOLE", "1");
+        cmd.env("LOVELY_CONSOLE", "0");
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+
+    let started = SystemTime::now();
+    let child = cmd
+        .spawn()
+        .map_err(|e| AppError::ProcessExecution(e.to_string()))?;
+    Ok(("native", started, GameProcess::Child(child)))
+}
+
+#[cfg(target_os = "linux")]
+async fn spawn_game(
+    options: &LaunchOptions,
+) -> Result<(&'static str, SystemTime, GameProcess), AppError> {
+    use crate::lovely::{self, BALATRO_STEAM_APP_ID, PROTON_DLL_OVERRIDES};
+    use std::process::Command;
+
+    let path = &options.game_dir;
+    let mode = match &options.method {
+        // Without Steam there is no Proton to hand the game to; LÖVE runs the game file
+        LaunchMethod::Direct { .. } if options.linux_mode == Some(LinuxLaunchMode::Proton) => {
+            return Err(AppError::InvalidState(
+                "Proton launches go through Steam, and this install isn't a Steam install. \
+                 Switch its Linux launch mode to native to run it with LÖVE."
+                    .to_string(),
+            ))
+        }
+        LaunchMethod::Direct { .. } => LinuxLaunchMode::Native,
+        _ => options
+            .linux_mode
+            .unwrap_or_else(|| lovely::detect_linux_launch_mode(path)),
+    };
+
+    let lovely_path = if options.variant.injects_lovely() {
+        let target = LovelyTarget {
+            linux_mode: Some(mode),
+            ..options.lovely_target()
+        };
+        Some(lovely::ensure_lovely(&target, options.lovely_tag.as_deref()).await?)
+    } else {
+        None
+    };
+
+    match mode {
+        LinuxLaunchMode::Native => {
+            let game_file =
+                lovely::native_game_file(path).ok_or_else(|| AppError::FileNotFound {
+                    path: path.join("Balatro.exe"),
+                    source: "No Balatro game file to run with LÖVE".to_string(),
+                })?;
+
+            let mut cmd = Command::new("love");
+            cmd.current_dir(path).arg(game_file);
+            if let Some(lovely_path) = &lovely_path {
+                cmd.env("LD_PRELOAD", lovely_path);
+                if !options.lovely_console {
+                    cmd.arg("--disable-console");
+                }
+            }
+            let started = SystemTime::now();
+            let child = cmd.spawn().map_err(|e| {
+                AppError::ProcessExecution(format!(
+                    "Failed to start LÖVE (is `love` installed?): {e}"
+                ))
+            })?;
+            Ok((mode.as_str(), started, GameProcess::Child(child)))
+        }
+        LinuxLaunchMode::Proton => {
+            // Steam only forwards our environment when this launch is what starts it.
+            // Otherwise the game's launch options have to carry the DLL override.
+            let needs_launch_options = lovely_path.is_some()
+                && !crate::steam::balatro_launch_options()
+                    .iter()
+                    .any(|options| options.contains(PROTON_DLL_OVERRIDES));
+            if needs_launch_options && crate::finder::is_steam_running() {
+                return Err(proton_launch_options_error("Steam is already running"));
+            }
+
+            let mut cmd = Command::new("steam");
+            if lovely_path.is_some() {
+                cmd.env("WINEDLLOVERRIDES", PROTON_DLL_OVERRIDES);
+            }
+            cmd.arg("-applaunch").arg(BALATRO_STEAM_APP_ID);
+            if lovely_path.is_none() {
+                cmd.arg("--vanilla");
+            } else if !options.lovely_console {
+                cmd.arg("--disable-console");
+            }
+            let started = SystemTime::now();
+            if let Err(e) = cmd.spawn() {
+                // A steam:// URL carries neither our environment nor our arguments
+                if needs_launch_options {
+                    return Err(proton_launch_options_error(&format!(
+                        "Failed to run steam ({e})"
+                    )));
+                }
+                log::warn!("Failed to run steam directly ({e}), falling back to steam:// URL");
+                Command::new("xdg-open")
+                    .arg(format!("steam://rungameid/{BALATRO_STEAM_APP_ID}"))
+                    .spawn()
+                    .map_err(|e| AppError::ProcessExecution(e.to_string()))?;
+            }
+            // `steam` hands off to the running client and returns; find the game by name
+            Ok((mode.as_str(), started, GameProcess::Detached))
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn proton_launch_options_error(reason: &str) -> AppError {
+    AppError::InvalidState(format!(
+        "{reason}, so Lovely can't be injected into Balatro under Proton. \
+         Set Balatro's launch options in Steam to \
+         WINEDLLOVERRIDES=\"{}\" %command% and launch again.",
+        crate::lovely::PROTON_DLL_OVERRIDES
+    ))
+}
+
+#[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+async fn spawn_game(
+    _options: &LaunchOptions,
+) -> Result<(&'static str, SystemTime, GameProcess), AppError> {
+    Err(AppError::InvalidState(
+        "Launching Balatro is not supported on this operating system".to_string(),
+    ))
+}
+
+/// Hand a launcher URI (`heroic://…`, `lutris:…`) to the desktop's URL handler.
+pub fn open_uri(uri: &str) -> Result<(), AppError> {
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut cmd = std::process::Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    };
+    #[cfg(target_os = "macos")]
+    let mut cmd = std::process::Command::new("open");
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let mut cmd = std::process::Command::new("xdg-open");
+
+    cmd.arg(uri)
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| AppError::ProcessExecution(format!("Failed to open {uri}: {e}")))
+}
@@ -1,12 +1,31 @@
+pub mod backup;
 pub mod balamod;
+pub mod bisect;
 pub mod cache;
+pub mod catalog;
+pub mod config_preserve;
 pub mod database;
 pub mod discord_rpc;
 pub mod errors;
 pub mod finder;
+pub mod forge;
+pub mod game_log;
 pub mod installer;
+pub mod launch_session;
+pub mod launcher;
 pub mod local_mod_detection;
 pub mod logging;
 pub mod lovely;
+pub mod lovely_patches;
 pub mod mod_collections;
+pub mod mod_manager;
+pub mod mod_sources;
+pub mod paths;
+pub mod profile;
+pub mod safe_mode;
+pub mod save_backup;
+pub mod smods_config;
 pub mod smods_installer;
+pub mod steam;
+pub mod stores;
+pub mod thunderstore;
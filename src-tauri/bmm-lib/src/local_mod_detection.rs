@@ -74,9 +74,7 @@ pub fn detect_manual_mods_cached(
     db: &Database,
     cached_catalog_mods: &[cache::Mod],
 ) -> Result<Vec<DetectedMod>, String> {
-    let config_dir =
-        dirs::config_dir().ok_or_else(|| "Could not find config directory".to_string())?;
-    let mods_dir = config_dir.join("Balatro").join("Mods");
+    let mods_dir = crate::paths::mods_dir().map_err(|e| e.to_string())?;
 
     let fp = compute_fingerprint(&mods_dir);
     if let Ok(mut guard) = DETECTION_CACHE.lock() {
@@ -210,11 +208,7 @@ pub fn detect_manual_mods(
     db: &Database,
     cached_catalog_mods: &[cache::Mod],
 ) -> Result<Vec<DetectedMod>, String> {
-    let config_dir =
-        dirs::config_dir().ok_or_else(|| "Could not find config directory".to_string())?;
-
-    let balatro_dir = config_dir.join("Balatro");
-    let mod_dir = balatro_dir.join("Mods");
+    let mod_dir = crate::paths::mods_dir().map_err(|e| e.to_string())?;
 
     if !mod_dir.exists() {
         return Ok(Vec::new());
@@ -15,9 +15,7 @@ pub fn init_logger() -> Result<(), AppError> {
     }
 
     // Create log directory in config dir
-    let config_dir = dirs::config_dir()
-        .ok_or_else(|| AppError::DirNotFound(PathBuf::from("config directory")))?;
-    let log_dir = config_dir.join("Balatro").join("logs");
+    let log_dir = crate::paths::logs_dir()?;
 
     fs::create_dir_all(&log_dir).map_err(|e| AppError::DirCreate {
         path: log_dir.clone(),
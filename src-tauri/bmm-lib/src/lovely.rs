@@ -1,21 +1,140 @@
+use crate::database::Database;
 use crate::errors::AppError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 #[cfg(target_os = "windows")]
 use std::fs::File;
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "linux"))]
 use std::fs::{self, File};
-#[cfg(target_os = "macos")]
+#[cfg(any(target_os = "macos", target_os = "linux"))]
 use std::os::unix::fs::PermissionsExt;
-#[cfg(target_os = "macos")]
-use std::path::Path;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Steam app id of Balatro, used for `steam -applaunch` and `steam://` URLs.
+pub const BALATRO_STEAM_APP_ID: &str = "2379780";
+
+/// Wine DLL override that makes Proton load Lovely's `version.dll` before its builtin one.
+pub const PROTON_DLL_OVERRIDES: &str = "version=n,b";
+
+/// How Balatro is run on Linux: the LÖVE runtime with `liblovely.so`
+/// preloaded, or the Windows build through Steam's Proton with `version.dll`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LinuxLaunchMode {
+    Native,
+    Proton,
+}
+
+impl LinuxLaunchMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LinuxLaunchMode::Native => "native",
+            LinuxLaunchMode::Proton => "proton",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "native" => Some(LinuxLaunchMode::Native),
+            "proton" => Some(LinuxLaunchMode::Proton),
+            _ => None,
+        }
+    }
+}
+
+/// Guess the launch mode for a game directory: a Steam copy that already has a
+/// Proton prefix runs through Proton, anything else through native LÖVE.
+pub fn detect_linux_launch_mode(game_dir: &Path) -> LinuxLaunchMode {
+    let compatdata = game_dir
+        .parent()
+        .and_then(Path::parent)
+        .map(|steamapps| steamapps.join("compatdata").join(BALATRO_STEAM_APP_ID));
+    if game_dir.join("Balatro.exe").exists() && compatdata.is_some_and(|p| p.exists()) {
+        LinuxLaunchMode::Proton
+    } else {
+        LinuxLaunchMode::Native
+    }
+}
+
+/// The game archive LÖVE should run for a native launch. The Windows build is
+/// a fused LÖVE executable, which `love` can open directly.
+pub fn native_game_file(game_dir: &Path) -> Option<PathBuf> {
+    ["Balatro.love", "Balatro.exe"]
+        .iter()
+        .map(|name| game_dir.join(name))
+        .find(|p| p.exists())
+}
+
+/// Where Lovely lives for a given Linux launch mode.
+pub fn linux_lovely_path(mode: LinuxLaunchMode, game_dir: &Path) -> Result<PathBuf, AppError> {
+    match mode {
+        LinuxLaunchMode::Native => Ok(crate::paths::bins_dir()?.join("liblovely.so")),
+        LinuxLaunchMode::Proton => Ok(game_dir.join("version.dll")),
+    }
+}
+
+/// The Balatro install Lovely is set up for, and on Linux how that install is launched.
+#[derive(Debug, Clone)]
+pub struct LovelyTarget {
+    pub game_dir: PathBuf,
+    /// The configured Linux launch mode; detected from `game_dir` when unset.
+    pub linux_mode: Option<LinuxLaunchMode>,
+}
+
+impl LovelyTarget {
+    /// The active install, the same one the game is launched from.
+    pub fn from_db(db: &Database) -> Result<Self, AppError> {
+        let game_dir = db
+            .get_installation_path()?
+            .map(PathBuf::from)
+            .ok_or_else(|| AppError::InvalidState("No installation path set".to_string()))?;
+        Ok(Self {
+            game_dir,
+            linux_mode: db.get_linux_launch_mode()?,
+        })
+    }
+
+    pub fn linux_mode(&self) -> LinuxLaunchMode {
+        self.linux_mode
+            .unwrap_or_else(|| detect_linux_launch_mode(&self.game_dir))
+    }
+
+    /// Where the Lovely binary lives for this install.
+    pub fn lovely_path(&self) -> Result<PathBuf, AppError> {
+        #[cfg(target_os = "macos")]
+        {
+            Ok(crate::paths::bins_dir()?.join("liblovely.dylib"))
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            Ok(self.game_dir.join("version.dll"))
+        }
+
+        #[cfg(target_os = "linux")]
+        {
+            linux_lovely_path(self.linux_mode(), &self.game_dir)
+        }
+
+        #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+        {
+            Err(AppError::InvalidState(
+                "Lovely injection is not supported on this platform.".into(),
+            ))
+        }
+    }
+}
 
 #[cfg(target_os = "windows")]
-pub async fn ensure_version_dll_exists(game_path: &PathBuf) -> Result<PathBuf, AppError> {
+pub async fn ensure_version_dll_exists(
+    game_path: &PathBuf,
+    tag: Option<&str>,
+) -> Result<PathBuf, AppError> {
     let dll_path = game_path.join("version.dll");
 
     // If the DLL doesn't exist, download it
     if !dll_path.exists() {
-        download_version_dll(&dll_path).await?;
+        download_version_dll(&dll_path, tag, false).await?;
     }
 
     Ok(dll_path)
@@ -72,22 +191,69 @@ fn detect_architecture() -> Result<&'static str, AppError> {
     }
 }
 
-pub async fn ensure_lovely_exists() -> Result<PathBuf, AppError> {
-    #[cfg(target_os = "macos")]
-    {
-        let config_dir = dirs::config_dir()
-            .ok_or_else(|| AppError::DirNotFound(PathBuf::from("config directory")))?;
+/// Make sure Lovely is installed. `tag` picks the release to download when it is
+/// missing; `None` means latest.
+pub async fn ensure_lovely(target: &LovelyTarget, tag: Option<&str>) -> Result<PathBuf, AppError> {
+    install_lovely(target, tag, false, false).await
+}
 
-        let bins_dir = config_dir.join("Balatro/bins");
-        fs::create_dir_all(&bins_dir).map_err(|e| AppError::DirCreate {
-            path: bins_dir.clone(),
-            source: e.to_string(),
-        })?;
+/// Replace the installed Lovely with a specific release tag (e.g. "v0.7.1").
+/// The current binary stays in place until the new one has been verified.
+///
+/// Older releases publish no checksum at all; those only install with
+/// `allow_unverified`, which the user has to confirm. The digest of what they
+/// accepted is remembered, so reinstalling that release later is checked against it.
+pub async fn install_lovely_version(
+    target: &LovelyTarget,
+    tag: &str,
+    allow_unverified: bool,
+) -> Result<PathBuf, AppError> {
+    let tag = normalize_tag(tag);
+    install_lovely(target, Some(&tag), true, allow_unverified).await
+}
+
+/// The release a pinned Lovely has to stay on, or `None` when it follows the latest.
+pub fn pinned_tag(db: &Database) -> Result<Option<String>, AppError> {
+    match db.get_lovely_version()? {
+        Some(version) if db.is_lovely_pinned()? => Ok(Some(normalize_tag(&version))),
+        _ => Ok(None),
+    }
+}
+
+/// Bring Lovely up to date: the latest release replaces the installed one, unless a
+/// version is pinned, in which case that release is only installed when missing.
+/// Returns the installed version as stored in the `lovely_version` setting.
+pub async fn update_lovely(
+    target: &LovelyTarget,
+    pinned: Option<&str>,
+) -> Result<String, AppError> {
+    let (tag, replace) = match pinned {
+        Some(tag) => (normalize_tag(tag), false),
+        None => (normalize_tag(&get_latest_lovely_version().await?), true),
+    };
+    install_lovely(target, Some(&tag), replace, false).await?;
+    Ok(tag.trim_start_matches('v').to_string())
+}
 
-        let lovely_path = bins_dir.join("liblovely.dylib");
+// With `replace` the release is downloaded even when Lovely is already installed.
+async fn install_lovely(
+    target: &LovelyTarget,
+    tag: Option<&str>,
+    replace: bool,
+    allow_unverified: bool,
+) -> Result<PathBuf, AppError> {
+    #[cfg(target_os = "macos")]
+    {
+        let lovely_path = target.lovely_path()?;
+        if let Some(bins_dir) = lovely_path.parent() {
+            fs::create_dir_all(bins_dir).map_err(|e| AppError::DirCreate {
+                path: bins_dir.to_path_buf(),
+                source: e.to_string(),
+            })?;
+        }
 
-        if !lovely_path.exists() {
-            download_and_install_lovely(&lovely_path).await?;
+        if replace || !lovely_path.exists() {
+            download_and_install_lovely(&lovely_path, tag, allow_unverified).await?;
         }
 
         Ok(lovely_path)
@@ -95,19 +261,40 @@ pub async fn ensure_lovely_exists() -> Result<PathBuf, AppError> {
 
     #[cfg(target_os = "windows")]
     {
-        let balatro_paths = crate::finder::get_balatro_paths();
-        if balatro_paths.is_empty() {
-            return Err(AppError::DirNotFound(PathBuf::from("Balatro installation")));
-        }
-
         // Ensure version.dll exists in the game directory
-        let game_path = &balatro_paths[0];
-        ensure_version_dll_exists(game_path).await?;
+        let game_path = &target.game_dir;
+        if replace {
+            download_version_dll(&target.lovely_path()?, tag, allow_unverified).await?;
+        } else {
+            ensure_version_dll_exists(game_path, tag).await?;
+        }
 
         Ok(game_path.join("Balatro.exe"))
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(target_os = "linux")]
+    {
+        let lovely_path = target.lovely_path()?;
+        if replace || !lovely_path.exists() {
+            match target.linux_mode() {
+                LinuxLaunchMode::Native => {
+                    if let Some(bins_dir) = lovely_path.parent() {
+                        fs::create_dir_all(bins_dir).map_err(|e| AppError::DirCreate {
+                            path: bins_dir.to_path_buf(),
+                            source: e.to_string(),
+                        })?;
+                    }
+                    download_and_install_lovely(&lovely_path, tag, allow_unverified).await?;
+                }
+                LinuxLaunchMode::Proton => {
+                    download_version_dll(&lovely_path, tag, allow_unverified).await?
+                }
+            }
+        }
+        Ok(lovely_path)
+    }
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
         Err(AppError::InvalidState(
             "Lovely injection is not supported on this platform.".into(),
@@ -115,6 +302,298 @@ pub async fn ensure_lovely_exists() -> Result<PathBuf, AppError> {
     }
 }
 
+const LOVELY_REPO: &str = "ethangreen-dev/lovely-injector";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LovelyAsset {
+    pub name: String,
+    #[serde(rename = "browser_download_url")]
+    pub url: String,
+    pub size: u64,
+    /// `sha256:<hex>` as published by GitHub, when available.
+    #[serde(default)]
+    pub digest: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct LovelyRelease {
+    #[serde(rename = "tag_name")]
+    pub tag: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub prerelease: bool,
+    #[serde(default)]
+    pub assets: Vec<LovelyAsset>,
+}
+
+impl LovelyRelease {
+    /// Version without the leading `v`, as stored in the `lovely_version` setting.
+    pub fn version(&self) -> &str {
+        self.tag.trim_start_matches('v')
+    }
+}
+
+/// Accept "0.7.1" as well as "v0.7.1"; lovely-injector tags carry the `v`.
+pub fn normalize_tag(tag: &str) -> String {
+    let tag = tag.trim();
+    if tag.starts_with('v') {
+        tag.to_string()
+    } else {
+        format!("v{tag}")
+    }
+}
+
+fn release_asset_url(tag: Option<&str>, asset: &str) -> String {
+    match tag {
+        Some(tag) => format!("https://github.com/{LOVELY_REPO}/releases/download/{tag}/{asset}"),
+        None => format!("https://github.com/{LOVELY_REPO}/releases/latest/download/{asset}"),
+    }
+}
+
+/// List lovely-injector releases, newest first.
+pub async fn list_lovely_releases() -> Result<Vec<LovelyRelease>, AppError> {
+    let url = format!("https://api.github.com/repos/{LOVELY_REPO}/releases?per_page=50");
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header(reqwest::header::USER_AGENT, "Balatro-Mod-Manager/1.0")
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| AppError::NetworkRequest {
+            url: url.clone(),
+            source: e.to_string(),
+        })?;
+
+    if !response.status().is_success() {
+        return Err(AppError::NetworkRequest {
+            url,
+            source: format!("GitHub API error (HTTP {})", response.status()),
+        });
+    }
+
+    response
+        .json::<Vec<LovelyRelease>>()
+        .await
+        .map_err(|e| AppError::NetworkRequest {
+            url,
+            source: format!("Failed to decode Lovely releases: {e}"),
+        })
+}
+
+/// Checksum files some releases publish next to their assets.
+const CHECKSUM_FILE_NAMES: &[&str] = &[
+    "SHA256SUMS",
+    "SHA256SUMS.txt",
+    "sha256sums.txt",
+    "checksums.txt",
+];
+
+async fn fetch_lovely_release(tag: &str) -> Result<LovelyRelease, AppError> {
+    let url = format!("https://api.github.com/repos/{LOVELY_REPO}/releases/tags/{tag}");
+    let response = reqwest::Client::new()
+        .get(&url)
+        .header(reqwest::header::USER_AGENT, "Balatro-Mod-Manager/1.0")
+        .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+        .send()
+        .await
+        .map_err(|e| AppError::NetworkRequest {
+            url: url.clone(),
+            source: e.to_string(),
+        })?;
+
+    if !response.status().is_success() {
+        return Err(AppError::NetworkRequest {
+            url,
+            source: format!("GitHub API error (HTTP {})", response.status()),
+        });
+    }
+
+    response
+        .json::<LovelyRelease>()
+        .await
+        .map_err(|e| AppError::NetworkRequest {
+            url,
+            source: format!("Failed to decode Lovely release: {e}"),
+        })
+}
+
+/// sha256 of known lovely-injector release assets as `<hex>  <tag>/<asset>` lines,
+/// so installing them needs neither the GitHub API nor a digest GitHub never published.
+const BUNDLED_CHECKSUMS: &str = include_str!("lovely-checksums.txt");
+
+/// Digests verified against GitHub before, in the same format, kept in the bins dir.
+const CHECKSUM_CACHE_FILE: &str = "lovely-checksums.txt";
+
+fn checksum_cache() -> Option<PathBuf> {
+    Some(crate::paths::bins_dir().ok()?.join(CHECKSUM_CACHE_FILE))
+}
+
+fn known_asset_sha256(cache: Option<&Path>, tag: &str, asset_name: &str) -> Option<String> {
+    let key = format!("{tag}/{asset_name}");
+    find_in_checksum_file(BUNDLED_CHECKSUMS, &key).or_else(|| {
+        let text = std::fs::read_to_string(cache?).ok()?;
+        find_in_checksum_file(&text, &key)
+    })
+}
+
+fn remember_asset_sha256(cache: Option<&Path>, tag: &str, asset_name: &str, digest: &str) {
+    use std::io::Write;
+
+    let Some(cache) = cache else {
+        log::warn!("Failed to cache the checksum of {asset_name}: no bins directory");
+        return;
+    };
+    let result = cache
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(cache)
+        })
+        .and_then(|mut file| writeln!(file, "{digest}  {tag}/{asset_name}"));
+    if let Err(e) = result {
+        log::warn!("Failed to cache the checksum of {asset_name}: {e}");
+    }
+}
+
+/// The sha256 expected for `asset_name` of release `tag`: from the bundled or cached
+/// table first, otherwise as GitHub or the release itself publishes it.
+async fn expected_asset_sha256(tag: &str, asset_name: &str) -> Result<String, AppError> {
+    let cache = checksum_cache();
+    if let Some(digest) = known_asset_sha256(cache.as_deref(), tag, asset_name) {
+        return Ok(digest);
+    }
+    let digest = published_asset_sha256(tag, asset_name).await?;
+    remember_asset_sha256(cache.as_deref(), tag, asset_name, &digest);
+    Ok(digest)
+}
+
+async fn published_asset_sha256(tag: &str, asset_name: &str) -> Result<String, AppError> {
+    let release = fetch_lovely_release(tag).await?;
+
+    if let Some(digest) = release
+        .assets
+        .iter()
+        .find(|a| a.name == asset_name)
+        .and_then(|a| a.digest.as_deref())
+        .and_then(parse_sha256_digest)
+    {
+        return Ok(digest);
+    }
+
+    // Older releases predate GitHub's asset digests; fall back to a published checksum file
+    let sidecar = format!("{asset_name}.sha256");
+    for checksum_asset in release
+        .assets
+        .iter()
+        .filter(|a| a.name == sidecar || CHECKSUM_FILE_NAMES.contains(&a.name.as_str()))
+    {
+        let text = reqwest::get(&checksum_asset.url)
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| AppError::Network(format!("Failed to download checksums: {e}")))?
+            .text()
+            .await
+            .map_err(|e| AppError::Network(format!("Failed to read checksums: {e}")))?;
+        if let Some(digest) = find_in_checksum_file(&text, asset_name) {
+            return Ok(digest);
+        }
+    }
+
+    Err(AppError::ChecksumUnavailable {
+        asset: asset_name.to_string(),
+    })
+}
+
+/// Check downloaded bytes against the checksum published for the release asset.
+/// With `allow_unverified` an asset without any checksum is accepted instead.
+async fn verify_release_asset(
+    tag: &str,
+    asset_name: &str,
+    data: &[u8],
+    allow_unverified: bool,
+) -> Result<(), AppError> {
+    let expected = match expected_asset_sha256(tag, asset_name).await {
+        Ok(expected) => expected,
+        Err(AppError::ChecksumUnavailable { .. }) if allow_unverified => {
+            log::warn!("Installing {tag}/{asset_name} without a published checksum, as confirmed");
+            accept_unverified(checksum_cache().as_deref(), tag, asset_name, data);
+            return Ok(());
+        }
+        Err(e) => return Err(e),
+    };
+    verify_sha256(asset_name, data, &expected)?;
+    log::info!("Verified sha256 of {asset_name}");
+    Ok(())
+}
+
+// Later downloads of a release the user accepted unverified must match what they accepted
+fn accept_unverified(cache: Option<&Path>, tag: &str, asset_name: &str, data: &[u8]) {
+    remember_asset_sha256(cache, tag, asset_name, &sha256_hex(data));
+}
+
+// Downloads name their release explicitly so the checksum lookup matches the bytes
+async fn resolve_tag(tag: Option<&str>) -> Result<String, AppError> {
+    match tag {
+        Some(tag) => Ok(normalize_tag(tag)),
+        None => Ok(normalize_tag(&get_latest_lovely_version().await?)),
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data)
+        .iter()
+        .map(|b| format!("{b:02x}"))
+        .collect()
+}
+
+fn verify_sha256(asset_name: &str, data: &[u8], expected: &str) -> Result<(), AppError> {
+    let actual = sha256_hex(data);
+    if actual.eq_ignore_ascii_case(expected) {
+        Ok(())
+    } else {
+        Err(AppError::ChecksumMismatch {
+            asset: asset_name.to_string(),
+            expected: expected.to_lowercase(),
+            actual,
+        })
+    }
+}
+
+fn is_sha256_hex(value: &str) -> bool {
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn parse_sha256_digest(digest: &str) -> Option<String> {
+    let hex = digest.strip_prefix("sha256:")?;
+    is_sha256_hex(hex).then(|| hex.to_lowercase())
+}
+
+// Accepts `sha256sum` output (`<hex>  <name>` / `<hex> *<name>`) or a bare hash
+fn find_in_checksum_file(text: &str, asset_name: &str) -> Option<String> {
+    let mut lines = text.lines().map(str::trim).filter(|l| !l.is_empty());
+    let mut single = None;
+    for line in lines.by_ref() {
+        let mut parts = line.split_whitespace();
+        let Some(hash) = parts.next().filter(|h| is_sha256_hex(h)) else {
+            continue;
+        };
+        match parts.next() {
+            Some(name) if name.trim_start_matches('*') == asset_name => {
+                return Some(hash.to_lowercase())
+            }
+            None => single = Some(hash.to_lowercase()),
+            _ => {}
+        }
+    }
+    single
+}
+
 /// Query GitHub for the latest Lovely release tag (e.g., "0.8.0").
 pub async fn get_latest_lovely_version() -> Result<String, AppError> {
     // We intentionally avoid downloading the artifact; just resolve the tag.
@@ -161,62 +640,39 @@ pub async fn get_latest_lovely_version() -> Result<String, AppError> {
     Ok(version)
 }
 
-/// Remove currently installed Lovely artifacts so a clean reinstall can occur.
-pub fn remove_installed_lovely() -> Result<(), AppError> {
-    #[cfg(target_os = "macos")]
-    {
-        let config_dir = dirs::config_dir()
-            .ok_or_else(|| AppError::DirNotFound(PathBuf::from("config directory")))?;
-        let bins_dir = config_dir.join("Balatro/bins");
-        let lovely_path = bins_dir.join("liblovely.dylib");
-        if lovely_path.exists() {
-            std::fs::remove_file(&lovely_path).map_err(|e| AppError::FileWrite {
-                path: lovely_path.clone(),
-                source: e.to_string(),
-            })?;
-        }
-        Ok(())
-    }
-
-    #[cfg(target_os = "windows")]
-    {
-        let balatro_paths = crate::finder::get_balatro_paths();
-        if balatro_paths.is_empty() {
-            return Ok(()); // Nothing to remove if we can't detect it
-        }
-        let game_path = &balatro_paths[0];
-        let dll_path = game_path.join("version.dll");
-        if dll_path.exists() {
-            std::fs::remove_file(&dll_path).map_err(|e| AppError::FileWrite {
-                path: dll_path.clone(),
-                source: e.to_string(),
-            })?;
-        }
-        return Ok(());
-    }
-
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
-    {
-        Err(AppError::InvalidState(
-            "Lovely injection is not supported on this platform.".into(),
-        ))
-    }
-}
-
-#[cfg(target_os = "macos")]
-async fn download_and_install_lovely(target_path: &Path) -> Result<(), AppError> {
+#[cfg(any(target_os = "macos", target_os = "linux"))]
+async fn download_and_install_lovely(
+    target_path: &Path,
+    tag: Option<&str>,
+    allow_unverified: bool,
+) -> Result<(), AppError> {
     let temp_dir = tempfile::tempdir().map_err(|e| AppError::FileWrite {
         path: PathBuf::from("temp directory"),
         source: e.to_string(),
     })?;
 
-    let arch = detect_architecture()?;
-    let url = format!(
-        "https://github.com/ethangreen-dev/lovely-injector/releases/latest/download/\
-    lovely-{arch}-apple-darwin.tar.gz"
-    );
+    #[cfg(target_os = "macos")]
+    let (asset_name, lib_name) = {
+        let arch = detect_architecture()?;
+        (
+            format!("lovely-{arch}-apple-darwin.tar.gz"),
+            "liblovely.dylib",
+        )
+    };
+
+    // Lovely only publishes x86_64 builds for Linux
+    #[cfg(target_os = "linux")]
+    let (asset_name, lib_name) = match std::env::consts::ARCH {
+        "x86_64" => (
+            "lovely-x86_64-unknown-linux-gnu.tar.gz".to_string(),
+            "liblovely.so",
+        ),
+        other => return Err(AppError::UnsupportedArchitecture(other.into())),
+    };
+    let tag = resolve_tag(tag).await?;
+    let url = release_asset_url(Some(&tag), &asset_name);
 
-    // Download latest release
+    // Download the requested release
     let client = reqwest::Client::new();
     let response = client
         .get(url)
@@ -235,6 +691,7 @@ async fn download_and_install_lovely(target_path: &Path) -> Result<(), AppError>
         .bytes()
         .await
         .map_err(|e| AppError::Network(e.to_string()))?;
+    verify_release_asset(&tag, &asset_name, &bytes, allow_unverified).await?;
     std::io::copy(&mut bytes.as_ref(), &mut file).map_err(|e| AppError::FileWrite {
         path: temp_tar_gz.clone(),
         source: e.to_string(),
@@ -254,35 +711,63 @@ async fn download_and_install_lovely(target_path: &Path) -> Result<(), AppError>
     })?;
 
     // Find the library in extracted files
-    let extracted_lib = temp_dir.path().join("liblovely.dylib");
-    fs::copy(&extracted_lib, target_path).map_err(|e| AppError::FileCopy {
+    let extracted_lib = temp_dir.path().join(lib_name);
+    let staged = staging_path(target_path);
+    fs::copy(&extracted_lib, &staged).map_err(|e| AppError::FileCopy {
         source: extracted_lib.display().to_string(),
-        dest: target_path.display().to_string(),
+        dest: staged.display().to_string(),
         source_error: e.to_string(),
     })?;
 
     // Set permissions
-    std::fs::set_permissions(target_path, std::fs::Permissions::from_mode(0o755))?;
+    std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o755))?;
 
-    Ok(())
+    replace_with_staged(&staged, target_path)
 }
 
-#[cfg(target_os = "windows")]
-async fn download_version_dll(target_path: &PathBuf) -> Result<(), AppError> {
+// Downloads are written next to their target so the final rename stays on one filesystem
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn staging_path(target_path: &Path) -> PathBuf {
+    let name = target_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    target_path.with_file_name(format!(".{name}.download"))
+}
+
+#[cfg(any(target_os = "macos", target_os = "windows", target_os = "linux"))]
+fn replace_with_staged(staged: &Path, target_path: &Path) -> Result<(), AppError> {
+    std::fs::rename(staged, target_path).map_err(|e| {
+        let _ = std::fs::remove_file(staged);
+        AppError::FileWrite {
+            path: target_path.to_path_buf(),
+            source: e.to_string(),
+        }
+    })
+}
+
+#[cfg(any(target_os = "windows", target_os = "linux"))]
+async fn download_version_dll(
+    target_path: &Path,
+    tag: Option<&str>,
+    allow_unverified: bool,
+) -> Result<(), AppError> {
     let temp_dir = tempfile::tempdir().map_err(|e| AppError::FileWrite {
         path: PathBuf::from("temp directory"),
         source: e.to_string(),
     })?;
 
-    // URL to the latest version.dll in the lovely injector repository
-    let url = "https://github.com/ethangreen-dev/lovely-injector/releases/latest/download/lovely-x86_64-pc-windows-msvc.zip";
+    // URL to the requested (or latest) version.dll in the lovely injector repository
+    let asset_name = "lovely-x86_64-pc-windows-msvc.zip";
+    let tag = resolve_tag(tag).await?;
+    let url = release_asset_url(Some(&tag), asset_name);
 
     log::info!("Downloading lovely injector for Windows from {}", url);
 
     // Download the ZIP file
     let client = reqwest::Client::new();
     let response = client
-        .get(url)
+        .get(&url)
         .send()
         .await
         .map_err(|e| AppError::Network(format!("Failed to download lovely injector: {e}")))?;
@@ -298,6 +783,7 @@ async fn download_version_dll(target_path: &PathBuf) -> Result<(), AppError> {
         .bytes()
         .await
         .map_err(|e| AppError::Network(format!("Failed to read download response: {e}")))?;
+    verify_release_asset(&tag, asset_name, &bytes, allow_unverified).await?;
 
     std::io::copy(&mut bytes.as_ref(), &mut file).map_err(|e| AppError::FileWrite {
         path: temp_zip.clone(),
@@ -330,16 +816,22 @@ async fn download_version_dll(target_path: &PathBuf) -> Result<(), AppError> {
 
         if entry_name.ends_with("version.dll") {
             log::info!("Found version.dll in zip archive");
-            let mut outfile = File::create(target_path).map_err(|e| AppError::FileWrite {
-                path: target_path.to_path_buf(),
+            let staged = staging_path(target_path);
+            let mut outfile = File::create(&staged).map_err(|e| AppError::FileWrite {
+                path: staged.clone(),
                 source: e.to_string(),
             })?;
 
-            std::io::copy(&mut file, &mut outfile).map_err(|e| AppError::FileWrite {
-                path: target_path.to_path_buf(),
-                source: e.to_string(),
+            std::io::copy(&mut file, &mut outfile).map_err(|e| {
+                let _ = std::fs::remove_file(&staged);
+                AppError::FileWrite {
+                    path: staged.clone(),
+                    source: e.to_string(),
+                }
             })?;
+            drop(outfile);
 
+            replace_with_staged(&staged, target_path)?;
             found_dll = true;
             break;
         }
@@ -353,3 +845,126 @@ async fn download_version_dll(target_path: &PathBuf) -> Result<(), AppError> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn release_urls_use_normalized_tags() {
+        assert_eq!(normalize_tag("0.7.1"), "v0.7.1");
+        assert_eq!(normalize_tag(" v0.7.1 "), "v0.7.1");
+        assert_eq!(
+            release_asset_url(Some("v0.7.1"), "lovely.zip"),
+            "https://github.com/ethangreen-dev/lovely-injector/releases/download/v0.7.1/lovely.zip"
+        );
+        assert!(release_asset_url(None, "lovely.zip").contains("/releases/latest/download/"));
+    }
+
+    #[test]
+    fn checksums_are_parsed_and_enforced() {
+        let data = b"lovely";
+        let other = "b6b0bd8c5a2fe4e5c73ae7c5bff91e1a0f6de6de3d3c0f14d5b1b9d35c0e67b0";
+        assert_eq!(
+            parse_sha256_digest(&format!("sha256:{}", other.to_uppercase())).as_deref(),
+            Some(other)
+        );
+        assert_eq!(parse_sha256_digest("md5:abc"), None);
+
+        let sums = format!("{other}  other.zip\n{}  *lovely.zip\n", "a".repeat(64));
+        assert_eq!(
+            find_in_checksum_file(&sums, "lovely.zip"),
+            Some("a".repeat(64))
+        );
+        assert_eq!(find_in_checksum_file(&sums, "missing.zip"), None);
+
+        assert!(verify_sha256("lovely.zip", data, &sha256_hex(data)).is_ok());
+        assert!(matches!(
+            verify_sha256("lovely.zip", data, other),
+            Err(AppError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn confirmed_unverified_download_pins_its_digest() {
+        let dir = tempdir().unwrap();
+        let cache = dir.path().join("bins").join(CHECKSUM_CACHE_FILE);
+        assert_eq!(
+            known_asset_sha256(Some(&cache), "v0.5.0", "lovely.zip"),
+            None
+        );
+
+        accept_unverified(Some(&cache), "v0.5.0", "lovely.zip", b"accepted");
+        let pinned = known_asset_sha256(Some(&cache), "v0.5.0", "lovely.zip").unwrap();
+        assert!(verify_sha256("lovely.zip", b"accepted", &pinned).is_ok());
+        assert!(verify_sha256("lovely.zip", b"swapped", &pinned).is_err());
+        // Keyed by tag as well as asset
+        assert_eq!(
+            known_asset_sha256(Some(&cache), "v0.6.0", "lovely.zip"),
+            None
+        );
+    }
+
+    #[test]
+    fn staged_download_replaces_the_binary() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("liblovely.so");
+        std::fs::write(&target, "old").unwrap();
+
+        let staged = staging_path(&target);
+        assert_eq!(staged, dir.path().join(".liblovely.so.download"));
+        std::fs::write(&staged, "new").unwrap();
+        replace_with_staged(&staged, &target).unwrap();
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+        assert!(!staged.exists());
+
+        // A failed swap cleans up the staged copy
+        std::fs::write(&staged, "newer").unwrap();
+        assert!(replace_with_staged(&staged, &dir.path().join("missing/liblovely.so")).is_err());
+        assert!(!staged.exists());
+        assert_eq!(std::fs::read_to_string(&target).unwrap(), "new");
+    }
+
+    #[test]
+    fn detects_proton_only_with_a_prefix() {
+        let td = tempdir().unwrap();
+        let game_dir = td.path().join("steamapps/common/Balatro");
+        std::fs::create_dir_all(&game_dir).unwrap();
+        std::fs::write(game_dir.join("Balatro.exe"), b"").unwrap();
+        assert_eq!(detect_linux_launch_mode(&game_dir), LinuxLaunchMode::Native);
+        assert_eq!(
+            native_game_file(&game_dir),
+            Some(game_dir.join("Balatro.exe"))
+        );
+
+        std::fs::create_dir_all(td.path().join("steamapps/compatdata/2379780")).unwrap();
+        assert_eq!(detect_linux_launch_mode(&game_dir), LinuxLaunchMode::Proton);
+        assert_eq!(
+            linux_lovely_path(LinuxLaunchMode::Proton, &game_dir).unwrap(),
+            game_dir.join("version.dll")
+        );
+    }
+
+    #[test]
+    fn target_follows_the_active_install() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let db = Database::open_in_memory()?;
+        assert!(LovelyTarget::from_db(&db).is_err());
+
+        let game_dir = td.path().join("Balatro");
+        db.set_installation_path(&game_dir.to_string_lossy())?;
+        let target = LovelyTarget::from_db(&db)?;
+        assert_eq!(target.game_dir, game_dir);
+
+        #[cfg(target_os = "linux")]
+        {
+            let proton = LovelyTarget {
+                linux_mode: Some(LinuxLaunchMode::Proton),
+                ..target
+            };
+            assert_eq!(proton.lovely_path()?, game_dir.join("version.dll"));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,679 @@
+use crate::balamod::Balatro;
+use crate::errors::AppError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PatchManifest {
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub dump_lua: Option<bool>,
+    /// Lower priorities are applied first.
+    #[serde(default)]
+    pub priority: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PatternPatch {
+    pub target: String,
+    pub pattern: String,
+    pub position: String,
+    pub payload: String,
+    #[serde(default)]
+    pub match_indent: bool,
+    #[serde(default)]
+    pub times: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RegexPatch {
+    pub target: String,
+    pub pattern: String,
+    pub position: String,
+    pub payload: String,
+    #[serde(default)]
+    pub root_capture: Option<String>,
+    #[serde(default)]
+    pub line_prepend: Option<String>,
+    #[serde(default)]
+    pub times: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct CopyPatch {
+    pub target: String,
+    pub position: String,
+    pub sources: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ModulePatch {
+    pub source: String,
+    pub before: String,
+    pub name: String,
+}
+
+/// One `[[patches]]` entry; the table name (`[patches.pattern]`, ...) selects the kind.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Patch {
+    Pattern(PatternPatch),
+    Regex(RegexPatch),
+    Copy(CopyPatch),
+    Module(ModulePatch),
+}
+
+impl Patch {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Patch::Pattern(_) => "pattern",
+            Patch::Regex(_) => "regex",
+            Patch::Copy(_) => "copy",
+            Patch::Module(_) => "module",
+        }
+    }
+
+    /// The game file this patch changes. Module patches are injected before their `before` file.
+    pub fn target(&self) -> &str {
+        match self {
+            Patch::Pattern(p) => &p.target,
+            Patch::Regex(p) => &p.target,
+            Patch::Copy(p) => &p.target,
+            Patch::Module(p) => &p.before,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PatchEntry {
+    /// Position of the patch within its file, starting at 0.
+    pub index: usize,
+    pub priority: i64,
+    pub patch: Patch,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PatchFile {
+    pub path: PathBuf,
+    pub manifest: PatchManifest,
+    pub patches: Vec<PatchEntry>,
+    /// Set when the file isn't valid TOML; nothing from it is applied then.
+    pub error: Option<String>,
+    /// Problems with individual patches, which Lovely skips or fails on.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ModPatches {
+    pub mod_name: String,
+    pub mod_path: PathBuf,
+    pub files: Vec<PatchFile>,
+}
+
+impl ModPatches {
+    pub fn patches(&self) -> impl Iterator<Item = (&PatchFile, &PatchEntry)> {
+        self.files
+            .iter()
+            .flat_map(|f| f.patches.iter().map(move |p| (f, p)))
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PatchReport {
+    pub mods: Vec<ModPatches>,
+    /// Game file -> names of the mods patching it.
+    pub targets: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Deserialize)]
+struct RawPatchFile {
+    #[serde(default)]
+    manifest: PatchManifest,
+    #[serde(default)]
+    patches: Vec<toml::Value>,
+}
+
+/// Parse the Lovely patches of every enabled mod in `mods_dir`.
+pub fn scan_mods(mods_dir: &Path) -> Result<PatchReport, AppError> {
+    let entries = fs::read_dir(mods_dir).map_err(|e| AppError::FileRead {
+        path: mods_dir.to_path_buf(),
+        source: e.to_string(),
+    })?;
+
+    let mut mod_dirs: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .filter(|p| {
+            // Lovely's own dump/log folder, and mods disabled through .lovelyignore
+            let name = p.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+            name != "lovely" && name != ".lovely" && !p.join(".lovelyignore").exists()
+        })
+        .collect();
+    mod_dirs.sort();
+
+    let mut report = PatchReport::default();
+    for mod_dir in mod_dirs {
+        let mod_patches = scan_mod(&mod_dir);
+        if mod_patches.files.is_empty() {
+            continue;
+        }
+        for (_, entry) in mod_patches.patches() {
+            let mods = report
+                .targets
+                .entry(entry.patch.target().to_string())
+                .or_default();
+            if !mods.contains(&mod_patches.mod_name) {
+                mods.push(mod_patches.mod_name.clone());
+            }
+        }
+        report.mods.push(mod_patches);
+    }
+    Ok(report)
+}
+
+/// Parse `lovely.toml` and `lovely/*.toml` of a single mod.
+pub fn scan_mod(mod_dir: &Path) -> ModPatches {
+    let mod_name = mod_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let mut paths = Vec::new();
+    let root_file = mod_dir.join("lovely.toml");
+    if root_file.is_file() {
+        paths.push(root_file);
+    }
+    if let Ok(entries) = fs::read_dir(mod_dir.join("lovely")) {
+        let mut nested: Vec<PathBuf> = entries
+            .flatten()
+            .map(|e| e.path())
+            .filter(|p| p.is_file() && p.extension().and_then(|e| e.to_str()) == Some("toml"))
+            .collect();
+        nested.sort();
+        paths.extend(nested);
+    }
+
+    let files = paths
+        .into_iter()
+        .map(|path| match fs::read_to_string(&path) {
+            Ok(content) => parse_patch_file(&path, &content),
+            Err(e) => PatchFile {
+                path,
+                error: Some(e.to_string()),
+                ..Default::default()
+            },
+        })
+        .collect();
+
+    ModPatches {
+        mod_name,
+        mod_path: mod_dir.to_path_buf(),
+        files,
+    }
+}
+
+pub fn parse_patch_file(path: &Path, content: &str) -> PatchFile {
+    let raw: RawPatchFile = match toml::from_str(content) {
+        Ok(raw) => raw,
+        Err(e) => {
+            return PatchFile {
+                path: path.to_path_buf(),
+                error: Some(e.to_string()),
+                ..Default::default()
+            }
+        }
+    };
+
+    let mut patches = Vec::new();
+    let mut warnings = Vec::new();
+    // Parse patches one by one so a single bad entry doesn't hide the rest
+    for (index, value) in raw.patches.into_iter().enumerate() {
+        match value.try_into::<Patch>() {
+            Ok(patch) => {
+                warnings.extend(validate_patch(&patch).map(|w| format!("patch #{index}: {w}")));
+                patches.push(PatchEntry {
+                    index,
+                    priority: raw.manifest.priority,
+                    patch,
+                });
+            }
+            Err(e) => warnings.push(format!("patch #{index}: {}", e.message())),
+        }
+    }
+
+    PatchFile {
+        path: path.to_path_buf(),
+        manifest: raw.manifest,
+        patches,
+        error: None,
+        warnings,
+    }
+}
+
+fn validate_patch(patch: &Patch) -> Option<String> {
+    if patch.target().trim().is_empty() {
+        return Some("empty target".to_string());
+    }
+    match patch {
+        Patch::Pattern(p) if !["before", "after", "at"].contains(&p.position.as_str()) => {
+            Some(format!("unknown position '{}'", p.position))
+        }
+        Patch::Regex(p) if !["before", "after", "at"].contains(&p.position.as_str()) => {
+            Some(format!("unknown position '{}'", p.position))
+        }
+        Patch::Regex(p) => Regex::new(&p.pattern)
+            .err()
+            .map(|e| format!("invalid regex: {e}")),
+        Patch::Copy(p) if !["prepend", "append"].contains(&p.position.as_str()) => {
+            Some(format!("unknown position '{}'", p.position))
+        }
+        Patch::Copy(p) if p.sources.is_empty() => Some("copy patch without sources".to_string()),
+        _ => None,
+    }
+}
+
+/// Identifies one patch: the mod, the patch file and the patch's index in it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PatchRef {
+    pub mod_name: String,
+    pub file: PathBuf,
+    pub index: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictKind {
+    /// Pattern patches from different mods looking for the same line.
+    SamePattern,
+    /// Patches from different mods whose matches in the game source overlap.
+    OverlappingMatch,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PatchConflict {
+    pub target: String,
+    pub kind: ConflictKind,
+    pub patches: Vec<PatchRef>,
+    pub detail: String,
+}
+
+/// A pattern or regex patch that matches nothing in the current game sources.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct StalePatch {
+    pub target: String,
+    pub patch: PatchRef,
+    pub pattern: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ConflictReport {
+    pub conflicts: Vec<PatchConflict>,
+    pub stale: Vec<StalePatch>,
+    /// Targets that couldn't be checked against a source (mod files, missing game files).
+    pub unchecked_targets: Vec<String>,
+}
+
+/// Read every targeted file out of the game archive. Missing files are left out.
+pub fn load_game_sources(balatro: &Balatro, report: &PatchReport) -> HashMap<String, String> {
+    let mut sources = HashMap::new();
+    for target in report.targets.keys() {
+        match balatro.get_file_data(target) {
+            Ok(data) if !data.is_empty() => {
+                sources.insert(target.clone(), String::from_utf8_lossy(&data).into_owned());
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Failed to read {target} from the game archive: {e}"),
+        }
+    }
+    sources
+}
+
+/// Find patches of different mods that fight over the same code, and patches
+/// that no longer match the game. `sources` maps target files to their contents.
+pub fn find_conflicts(report: &PatchReport, sources: &HashMap<String, String>) -> ConflictReport {
+    let mut by_target: BTreeMap<&str, Vec<(PatchRef, &Patch)>> = BTreeMap::new();
+    for mod_patches in &report.mods {
+        for (file, entry) in mod_patches.patches() {
+            by_target.entry(entry.patch.target()).or_default().push((
+                PatchRef {
+                    mod_name: mod_patches.mod_name.clone(),
+                    file: file.path.clone(),
+                    index: entry.index,
+                },
+                &entry.patch,
+            ));
+        }
+    }
+
+    let mut result = ConflictReport::default();
+    for (target, patches) in by_target {
+        let mut reported: Vec<(usize, usize)> = Vec::new();
+
+        // Same pattern string from different mods, detectable without the game source
+        for i in 0..patches.len() {
+            for j in i + 1..patches.len() {
+                let (a_ref, a) = &patches[i];
+                let (b_ref, b) = &patches[j];
+                if a_ref.mod_name == b_ref.mod_name {
+                    continue;
+                }
+                if let (Patch::Pattern(pa), Patch::Pattern(pb)) = (a, b) {
+                    if pa.pattern.trim() == pb.pattern.trim() {
+                        reported.push((i, j));
+                        result.conflicts.push(PatchConflict {
+                            target: target.to_string(),
+                            kind: ConflictKind::SamePattern,
+                            patches: vec![a_ref.clone(), b_ref.clone()],
+                            detail: format!(
+                                "both patch '{}' ({} / {})",
+                                pa.pattern.trim(),
+                                pa.position,
+                                pb.position
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+
+        let Some(source) = sources.get(target) else {
+            result.unchecked_targets.push(target.to_string());
+            continue;
+        };
+
+        let matches: Vec<Option<Vec<Range<usize>>>> = patches
+            .iter()
+            .map(|(_, patch)| match_ranges(patch, source))
+            .collect();
+
+        for ((patch_ref, patch), ranges) in patches.iter().zip(&matches) {
+            if ranges.as_ref().is_some_and(|r| r.is_empty()) {
+                let pattern = match patch {
+                    Patch::Pattern(p) => p.pattern.clone(),
+                    Patch::Regex(p) => p.pattern.clone(),
+                    _ => String::new(),
+                };
+                result.stale.push(StalePatch {
+                    target: target.to_string(),
+                    patch: patch_ref.clone(),
+                    pattern,
+                });
+            }
+        }
+
+        for i in 0..patches.len() {
+            for j in i + 1..patches.len() {
+                if patches[i].0.mod_name == patches[j].0.mod_name || reported.contains(&(i, j)) {
+                    continue;
+                }
+                let (Some(a), Some(b)) = (&matches[i], &matches[j]) else {
+                    continue;
+                };
+                if let Some(line) = first_overlap(a, b).map(|offset| line_number(source, offset)) {
+                    result.conflicts.push(PatchConflict {
+                        target: target.to_string(),
+                        kind: ConflictKind::OverlappingMatch,
+                        patches: vec![patches[i].0.clone(), patches[j].0.clone()],
+                        detail: format!(
+                            "{} and {} patches both match at line {line}",
+                            patches[i].1.kind(),
+                            patches[j].1.kind()
+                        ),
+                    });
+                }
+            }
+        }
+    }
+    result
+}
+
+// Byte ranges a patch matches in `source`; `None` for patches that don't match code
+fn match_ranges(patch: &Patch, source: &str) -> Option<Vec<Range<usize>>> {
+    match patch {
+        // Lovely matches pattern patches line by line against the trimmed line
+        Patch::Pattern(p) => {
+            let pattern = p.pattern.trim();
+            let mut offset = 0;
+            let mut ranges = Vec::new();
+            for line in source.split_inclusive('\n') {
+                if wildcard_match(pattern, line.trim()) {
+                    ranges.push(offset..offset + line.len());
+                }
+                offset += line.len();
+            }
+            Some(ranges)
+        }
+        Patch::Regex(p) => {
+            let re = Regex::new(&p.pattern).ok()?;
+            Some(
+                re.find_iter(source)
+                    .map(|m| m.range())
+                    .filter(|r| !r.is_empty())
+                    .collect(),
+            )
+        }
+        Patch::Copy(_) | Patch::Module(_) => None,
+    }
+}
+
+fn first_overlap(a: &[Range<usize>], b: &[Range<usize>]) -> Option<usize> {
+    a.iter()
+        .flat_map(|ra| b.iter().map(move |rb| (ra, rb)))
+        .filter(|(ra, rb)| ra.start < rb.end && rb.start < ra.end)
+        .map(|(ra, rb)| ra.start.max(rb.start))
+        .min()
+}
+
+fn line_number(source: &str, offset: usize) -> usize {
+    source[..offset].matches('\n').count() + 1
+}
+
+// Glob match supporting `*` and `?`, as used by Lovely pattern patches
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let p: Vec<char> = pattern.chars().collect();
+    let t: Vec<char> = text.chars().collect();
+    let (mut pi, mut ti) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while ti < t.len() {
+        if pi < p.len() && (p[pi] == '?' || p[pi] == t[ti]) {
+            pi += 1;
+            ti += 1;
+        } else if pi < p.len() && p[pi] == '*' {
+            backtrack = Some((pi, ti));
+            pi += 1;
+        } else if let Some((star, matched)) = backtrack {
+            pi = star + 1;
+            ti = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    p[pi..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const PATCHES: &str = r#"
+[manifest]
+version = "1.0.0"
+priority = 5
+
+[[patches]]
+[patches.pattern]
+target = "game.lua"
+pattern = "self.SPEEDFACTOR = 1"
+position = "after"
+payload = "print('hi')"
+match_indent = true
+
+[[patches]]
+[patches.regex]
+target = "functions/common_events.lua"
+pattern = "(?<indent>[\t ]*)return"
+position = "before"
+payload = "-- x"
+
+[[patches]]
+[patches.copy]
+target = "main.lua"
+position = "append"
+sources = ["extra.lua"]
+
+[[patches]]
+[patches.module]
+source = "nativefs.lua"
+before = "main.lua"
+name = "nativefs"
+
+[[patches]]
+[patches.teleport]
+target = "game.lua"
+"#;
+
+    #[test]
+    fn parses_every_patch_kind() {
+        let file = parse_patch_file(Path::new("lovely.toml"), PATCHES);
+        assert!(file.error.is_none());
+        assert_eq!(file.manifest.priority, 5);
+        let kinds: Vec<_> = file.patches.iter().map(|p| p.patch.kind()).collect();
+        assert_eq!(kinds, vec!["pattern", "regex", "copy", "module"]);
+        assert_eq!(file.patches[3].patch.target(), "main.lua");
+        // The unknown patch type is reported, not fatal
+        assert_eq!(file.warnings.len(), 1);
+        assert!(file.warnings[0].starts_with("patch #4"));
+    }
+
+    #[test]
+    fn scan_reports_targets_and_malformed_files() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let mods = td.path();
+        let a = mods.join("ModA");
+        fs::create_dir_all(a.join("lovely")).unwrap();
+        fs::write(a.join("lovely.toml"), PATCHES).unwrap();
+        fs::write(a.join("lovely/broken.toml"), "[[patches]\n").unwrap();
+
+        let disabled = mods.join("ModB");
+        fs::create_dir_all(&disabled).unwrap();
+        fs::write(disabled.join("lovely.toml"), PATCHES).unwrap();
+        fs::write(disabled.join(".lovelyignore"), "").unwrap();
+
+        fs::create_dir_all(mods.join("lovely")).unwrap();
+
+        let report = scan_mods(mods)?;
+        assert_eq!(report.mods.len(), 1);
+        assert_eq!(report.mods[0].files.len(), 2);
+        assert!(report.mods[0].files[1].error.is_some());
+        assert_eq!(report.targets["game.lua"], vec!["ModA".to_string()]);
+        assert!(report.targets.contains_key("functions/common_events.lua"));
+        Ok(())
+    }
+
+    fn mod_with(name: &str, patches: Vec<Patch>) -> ModPatches {
+        ModPatches {
+            mod_name: name.to_string(),
+            mod_path: PathBuf::from(name),
+            files: vec![PatchFile {
+                path: PathBuf::from(format!("{name}/lovely.toml")),
+                patches: patches
+                    .into_iter()
+                    .enumerate()
+                    .map(|(index, patch)| PatchEntry {
+                        index,
+                        priority: 0,
+                        patch,
+                    })
+                    .collect(),
+                ..Default::default()
+            }],
+        }
+    }
+
+    fn pattern(target: &str, pattern: &str) -> Patch {
+        Patch::Pattern(PatternPatch {
+            target: target.to_string(),
+            pattern: pattern.to_string(),
+            position: "after".to_string(),
+            payload: String::new(),
+            match_indent: true,
+            times: None,
+        })
+    }
+
+    fn regex(target: &str, pattern: &str) -> Patch {
+        Patch::Regex(RegexPatch {
+            target: target.to_string(),
+            pattern: pattern.to_string(),
+            position: "at".to_string(),
+            payload: String::new(),
+            root_capture: None,
+            line_prepend: None,
+            times: None,
+        })
+    }
+
+    #[test]
+    fn wildcard_patterns_match_lines() {
+        assert!(wildcard_match("self.SPEED* = 1", "self.SPEEDFACTOR = 1"));
+        assert!(wildcard_match("a?c", "abc"));
+        assert!(!wildcard_match("a?c", "abbc"));
+        assert!(wildcard_match("*", ""));
+    }
+
+    #[test]
+    fn finds_conflicts_and_stale_patches() {
+        let report = PatchReport {
+            mods: vec![
+                mod_with(
+                    "A",
+                    vec![
+                        pattern("game.lua", "self.SPEEDFACTOR = 1"),
+                        pattern("main.lua", "gone()"),
+                    ],
+                ),
+                mod_with(
+                    "B",
+                    vec![
+                        pattern("game.lua", "self.SPEEDFACTOR = 1"),
+                        regex("game.lua", r"G\.GAME\.dollars = \d+"),
+                    ],
+                ),
+                mod_with("C", vec![pattern("game.lua", "G.GAME.dollars = *")]),
+            ],
+            targets: BTreeMap::new(),
+        };
+        let mut sources = HashMap::new();
+        sources.insert(
+            "game.lua".to_string(),
+            "self.SPEEDFACTOR = 1\n    G.GAME.dollars = 4\n".to_string(),
+        );
+
+        let result = find_conflicts(&report, &sources);
+        let kinds: Vec<_> = result.conflicts.iter().map(|c| c.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![ConflictKind::SamePattern, ConflictKind::OverlappingMatch]
+        );
+        assert_eq!(result.conflicts[1].patches[0].mod_name, "B");
+        assert_eq!(result.conflicts[1].patches[1].mod_name, "C");
+        assert!(result.conflicts[1].detail.contains("line 2"));
+
+        assert_eq!(result.unchecked_targets, vec!["main.lua".to_string()]);
+        assert!(result.stale.is_empty());
+
+        sources.insert("main.lua".to_string(), "love.run()\n".to_string());
+        let result = find_conflicts(&report, &sources);
+        assert_eq!(result.stale.len(), 1);
+        assert_eq!(result.stale[0].pattern, "gone()");
+    }
+}
@@ -0,0 +1,385 @@
+use crate::cache::Mod;
+use crate::database::{Database, InstalledMod};
+use crate::errors::AppError;
+use crate::installer;
+use crate::save_backup::{self, SaveBackupReason};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub(crate) const IGNORE_FILE: &str = ".lovelyignore";
+
+/// An installed mod and whether Lovely currently loads it.
+#[derive(Serialize)]
+pub struct ModStatus {
+    #[serde(flatten)]
+    pub installed: InstalledMod,
+    pub enabled: bool,
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ModUpdate {
+    pub name: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// A mod is disabled while its folder holds a `.lovelyignore` marker.
+pub fn is_enabled(mod_dir: &Path) -> bool {
+    !mod_dir.join(IGNORE_FILE).exists()
+}
+
+/// The markers Lovely skips a mod by, present or not. Subfolders get their own
+/// marker so mods bundled inside another mod follow it.
+pub fn ignore_markers(mod_dir: &Path) -> Result<Vec<PathBuf>, AppError> {
+    if !mod_dir.exists() {
+        return Err(AppError::DirNotFound(mod_dir.to_path_buf()));
+    }
+    let entries = fs::read_dir(mod_dir).map_err(|e| AppError::FileRead {
+        path: mod_dir.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .collect();
+    dirs.push(mod_dir.to_path_buf());
+    Ok(dirs.into_iter().map(|dir| dir.join(IGNORE_FILE)).collect())
+}
+
+/// Add or remove the markers Lovely skips mods by; see [`ignore_markers`].
+pub fn set_enabled(mod_dir: &Path, enabled: bool) -> Result<(), AppError> {
+    set_enabled_keeping(mod_dir, enabled, &[])
+}
+
+/// [`set_enabled`], leaving the markers in `keep` untouched, e.g. bundled mods the
+/// user disabled before a temporary toggle.
+pub(crate) fn set_enabled_keeping(
+    mod_dir: &Path,
+    enabled: bool,
+    keep: &[PathBuf],
+) -> Result<(), AppError> {
+    for marker in ignore_markers(mod_dir)? {
+        if keep.contains(&marker) {
+            continue;
+        }
+        let result = if enabled {
+            if marker.exists() {
+                fs::remove_file(&marker)
+            } else {
+                Ok(())
+            }
+        } else {
+            fs::write(&marker, "")
+        };
+        result.map_err(|e| AppError::FileWrite {
+            path: marker,
+            source: e.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+/// Look up a tracked mod by name, ignoring case.
+pub fn find_installed(db: &Database, name: &str) -> Result<InstalledMod, AppError> {
+    db.get_installed_mods()?
+        .into_iter()
+        .find(|m| m.name.eq_ignore_ascii_case(name))
+        .ok_or_else(|| AppError::ModNotFound {
+            mod_name: name.to_string(),
+            version: String::new(),
+        })
+}
+
+pub fn list(db: &Database) -> Result<Vec<ModStatus>, AppError> {
+    Ok(db
+        .get_installed_mods()?
+        .into_iter()
+        .map(|m| ModStatus {
+            enabled: is_enabled(Path::new(&m.path)),
+            installed: m,
+        })
+        .collect())
+}
+
+pub fn set_mod_enabled(db: &Database, name: &str, enabled: bool) -> Result<(), AppError> {
+    let installed = find_installed(db, name)?;
+    set_enabled(Path::new(&installed.path), enabled)
+}
+
+/// Catalog mods matching `query` in their title, publisher or description;
+/// title matches come first.
+pub fn search<'a>(catalog: &'a [Mod], query: &str) -> Vec<&'a Mod> {
+    let query = query.to_lowercase();
+    let (mut by_title, others): (Vec<&Mod>, Vec<&Mod>) = catalog
+        .iter()
+        .filter(|m| {
+            m.title.to_lowercase().contains(&query)
+                || m.publisher.to_lowercase().contains(&query)
+                || m.description.to_lowercase().contains(&query)
+        })
+        .partition(|m| m.title.to_lowercase().contains(&query));
+    by_title.extend(others);
+    by_title
+}
+
+/// The catalog entry named `name` (its title or install folder), ignoring case.
+pub fn find_in_catalog<'a>(catalog: &'a [Mod], name: &str) -> Option<&'a Mod> {
+    catalog.iter().find(|m| {
+        m.title.eq_ignore_ascii_case(name)
+            || m.folderName
+                .as_deref()
+                .is_some_and(|f| f.eq_ignore_ascii_case(name))
+    })
+}
+
+/// Install a catalog mod and track it, the same way the UI does.
+pub async fn install_from_catalog(db: &Database, entry: &Mod) -> Result<InstalledMod, AppError> {
+    let reason = SaveBackupReason::for_install(Some(&catalog_folder(entry)));
+    save_backup::snapshot_quietly(db, reason);
+    install_catalog_entry(db, entry).await
+}
+
+/// Install a mod archive from a URL, tracked under `name` (or the folder it lands in).
+pub async fn install_from_url(
+    db: &Database,
+    url: &str,
+    name: Option<&str>,
+) -> Result<InstalledMod, AppError> {
+    let folder = name.map(url_folder);
+    save_backup::snapshot_quietly(db, SaveBackupReason::for_install(folder.as_deref()));
+    install_url(db, url, name).await
+}
+
+fn catalog_folder(entry: &Mod) -> String {
+    entry
+        .folderName
+        .clone()
+        .filter(|f| !f.is_empty())
+        .unwrap_or_else(|| entry.title.split_whitespace().collect())
+}
+
+fn url_folder(name: &str) -> String {
+    name.split_whitespace().collect()
+}
+
+// Leaves the save snapshot to the caller, so a batch of installs takes only one
+pub(crate) async fn install_catalog_entry(
+    db: &Database,
+    entry: &Mod,
+) -> Result<InstalledMod, AppError> {
+    let mut dependencies = Vec::new();
+    if entry.requires_steamodded {
+        dependencies.push("Steamodded".to_string());
+    }
+    if entry.requires_talisman {
+        dependencies.push("Talisman".to_string());
+    }
+    install_and_track(
+        db,
+        &entry.title,
+        &entry.download_url,
+        Some(catalog_folder(entry)),
+        dependencies,
+        entry.version.clone(),
+    )
+    .await
+}
+
+pub(crate) async fn install_url(
+    db: &Database,
+    url: &str,
+    name: Option<&str>,
+) -> Result<InstalledMod, AppError> {
+    let path = installer::install_mod(url.to_string(), name.map(url_folder)).await?;
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => path
+            .file_name()
+            .map(|n| n.to_string_lossy().into_owned())
+            .unwrap_or_else(|| url.to_string()),
+    };
+    track(db, &name, &path, Vec::new(), None)
+}
+
+async fn install_and_track(
+    db: &Database,
+    name: &str,
+    url: &str,
+    folder: Option<String>,
+    dependencies: Vec<String>,
+    version: Option<String>,
+) -> Result<InstalledMod, AppError> {
+    let path = installer::install_mod(url.to_string(), folder).await?;
+    track(db, name, &path, dependencies, version)
+}
+
+fn track(
+    db: &Database,
+    name: &str,
+    path: &Path,
+    dependencies: Vec<String>,
+    version: Option<String>,
+) -> Result<InstalledMod, AppError> {
+    let path = path.to_string_lossy().into_owned();
+    db.add_installed_mod(name, &path, &dependencies, version.clone())?;
+    Ok(InstalledMod {
+        name: name.to_string(),
+        path,
+        dependencies,
+        current_version: version,
+    })
+}
+
+/// Remove a mod's files and stop tracking it. Mods that depend on it are only
+/// removed along with it when `cascade` is set; otherwise they block the removal.
+pub fn uninstall(db: &Database, name: &str, cascade: bool) -> Result<Vec<String>, AppError> {
+    let root = find_installed(db, name)?;
+    let dependents: Vec<String> = db
+        .get_dependents(&root.name)?
+        .into_iter()
+        .filter(|d| d != &root.name)
+        .collect();
+    if !dependents.is_empty() && !cascade {
+        return Err(AppError::ModConflict {
+            mod_name: root.name,
+            conflicts: dependents,
+        });
+    }
+
+    let mut removed = Vec::new();
+    let mut pending = vec![root.name];
+    while let Some(current) = pending.pop() {
+        if removed.contains(&current) {
+            continue;
+        }
+        let details = db.get_mod_details(&current)?;
+        pending.extend(db.get_dependents(&current)?);
+        let path = PathBuf::from(&details.path);
+        if path.exists() {
+            installer::uninstall_mod(path.clone())?;
+        }
+        db.remove_installed_mod(&current)?;
+        installer::forget_user_config(&path);
+        removed.push(current);
+    }
+    Ok(removed)
+}
+
+/// Tracked mods whose catalog version differs from the installed one.
+pub fn outdated(installed: &[InstalledMod], catalog: &[Mod]) -> Vec<ModUpdate> {
+    installed
+        .iter()
+        .filter_map(|m| {
+            let latest = find_in_catalog(catalog, &m.name)?.version.clone()?;
+            let current = m.current_version.clone().filter(|v| !v.is_empty());
+            (current.as_deref() != Some(latest.as_str())).then(|| ModUpdate {
+                name: m.name.clone(),
+                from: current,
+                to: Some(latest),
+            })
+        })
+        .collect()
+}
+
+/// Reinstall outdated mods from the catalog; `only` limits this to one mod.
+pub async fn update(
+    db: &Database,
+    catalog: &[Mod],
+    only: Option<&str>,
+) -> Result<Vec<ModUpdate>, AppError> {
+    let installed = match only {
+        Some(name) => vec![find_installed(db, name)?],
+        None => db.get_installed_mods()?,
+    };
+    let updates = outdated(&installed, catalog);
+    if !updates.is_empty() {
+        save_backup::snapshot_quietly(db, SaveBackupReason::ModUpdate);
+    }
+    for update in &updates {
+        if let Some(entry) = find_in_catalog(catalog, &update.name) {
+            install_catalog_entry(db, entry).await?;
+        }
+    }
+    Ok(updates)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::ColorPair;
+    use tempfile::tempdir;
+
+    fn catalog_mod(title: &str, version: Option<&str>) -> Mod {
+        Mod {
+            title: title.to_string(),
+            description: format!("{title} description"),
+            image: String::new(),
+            categories: Vec::new(),
+            colors: ColorPair {
+                color1: String::new(),
+                color2: String::new(),
+            },
+            installed: false,
+            requires_steamodded: true,
+            requires_talisman: false,
+            publisher: "someone".to_string(),
+            repo: String::new(),
+            download_url: String::new(),
+            folderName: None,
+            version: version.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn set_enabled_marks_nested_folders() {
+        let td = tempdir().unwrap();
+        let mod_dir = td.path().join("Pack");
+        fs::create_dir_all(mod_dir.join("Bundled")).unwrap();
+
+        set_enabled(&mod_dir, false).unwrap();
+        assert!(!is_enabled(&mod_dir));
+        assert!(!is_enabled(&mod_dir.join("Bundled")));
+
+        set_enabled(&mod_dir, true).unwrap();
+        assert!(is_enabled(&mod_dir));
+        assert!(is_enabled(&mod_dir.join("Bundled")));
+    }
+
+    #[test]
+    fn search_and_outdated_use_catalog() {
+        let catalog = vec![
+            catalog_mod("Cryptid", Some("0.5.2")),
+            catalog_mod("Jokers Plus", Some("1.0.0")),
+        ];
+        let titles: Vec<_> = search(&catalog, "joker")
+            .iter()
+            .map(|m| m.title.as_str())
+            .collect();
+        assert_eq!(titles, ["Jokers Plus"]);
+
+        let installed = vec![
+            InstalledMod {
+                name: "cryptid".to_string(),
+                path: String::new(),
+                dependencies: Vec::new(),
+                current_version: Some("0.5.1".to_string()),
+            },
+            InstalledMod {
+                name: "Jokers Plus".to_string(),
+                path: String::new(),
+                dependencies: Vec::new(),
+                current_version: Some("1.0.0".to_string()),
+            },
+        ];
+        assert_eq!(
+            outdated(&installed, &catalog),
+            [ModUpdate {
+                name: "cryptid".to_string(),
+                from: Some("0.5.1".to_string()),
+                to: Some("0.5.2".to_string()),
+            }]
+        );
+    }
+}
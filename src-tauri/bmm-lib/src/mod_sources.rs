@@ -0,0 +1,309 @@
+//! Mod indexes the catalog is assembled from: the community GitLab index, any
+//! `index.json` URL, local folders and Thunderstore communities.
+
+use crate::catalog::{asset_slug, CatalogClient, CatalogEntry, IndexFileV1, ModMeta};
+use crate::database::{Database, ModSourceConfig, SourceKind};
+use crate::errors::AppError;
+use crate::paths;
+use crate::thunderstore::{ThunderstoreClient, ThunderstoreSource};
+use futures::future::{join_all, BoxFuture};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A place mod listings come from.
+pub trait ModSource: Send + Sync {
+    /// Name entries from this source are tagged with.
+    fn name(&self) -> &str;
+
+    fn fetch(&self) -> BoxFuture<'_, Result<Vec<CatalogEntry>, AppError>>;
+}
+
+/// A GitLab repository laid out like the community index.
+pub struct GitLabSource {
+    name: String,
+    client: CatalogClient,
+}
+
+impl GitLabSource {
+    pub fn new(name: &str, client: CatalogClient) -> Self {
+        Self {
+            name: name.to_string(),
+            client,
+        }
+    }
+}
+
+impl ModSource for GitLabSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<Vec<CatalogEntry>, AppError>> {
+        Box::pin(self.client.fetch_mods())
+    }
+}
+
+/// An `index.json` in the community index format, served over HTTP.
+pub struct IndexUrlSource {
+    name: String,
+    url: String,
+}
+
+impl IndexUrlSource {
+    pub fn new(name: &str, url: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            url: url.to_string(),
+        }
+    }
+}
+
+impl ModSource for IndexUrlSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<Vec<CatalogEntry>, AppError>> {
+        Box::pin(async move {
+            let network_error = |source: String| AppError::NetworkRequest {
+                url: self.url.clone(),
+                source,
+            };
+            let resp = reqwest::get(&self.url)
+                .await
+                .map_err(|e| network_error(e.to_string()))?;
+            if !resp.status().is_success() {
+                return Err(network_error(format!("status {}", resp.status())));
+            }
+            let index: IndexFileV1 = resp.json().await.map_err(|e| {
+                AppError::InvalidApiResponse(format!("Failed to parse {}: {e}", self.url))
+            })?;
+            Ok(index.mods)
+        })
+    }
+}
+
+/// A folder with an `index.json`, or with the index repository's
+/// `mods/<name>/meta.json` layout (descriptions and thumbnails optional).
+pub struct LocalDirSource {
+    name: String,
+    dir: PathBuf,
+}
+
+impl LocalDirSource {
+    pub fn new(name: &str, dir: impl Into<PathBuf>) -> Self {
+        Self {
+            name: name.to_string(),
+            dir: dir.into(),
+        }
+    }
+
+    fn read(&self) -> Result<Vec<CatalogEntry>, AppError> {
+        let index = self.dir.join("index.json");
+        if index.is_file() {
+            let data = read_to_string(&index)?;
+            let parsed: IndexFileV1 =
+                serde_json::from_str(&data).map_err(|e| AppError::JsonParse {
+                    path: index,
+                    source: e.to_string(),
+                })?;
+            return Ok(parsed.mods);
+        }
+
+        let mods_dir = self.dir.join("mods");
+        let entries = fs::read_dir(&mods_dir).map_err(|e| AppError::FileRead {
+            path: mods_dir.clone(),
+            source: e.to_string(),
+        })?;
+        let mut out = Vec::new();
+        for entry in entries.filter_map(|e| e.ok()) {
+            let dir = entry.path();
+            let meta_path = dir.join("meta.json");
+            let Ok(data) = fs::read_to_string(&meta_path) else {
+                continue;
+            };
+            let meta: ModMeta = match serde_json::from_str(&data) {
+                Ok(meta) => meta,
+                Err(e) => {
+                    log::warn!("Skipping {}: {e}", meta_path.display());
+                    continue;
+                }
+            };
+            let thumbnail = dir.join("thumbnail.jpg");
+            out.push(CatalogEntry {
+                dir_name: entry.file_name().to_string_lossy().into_owned(),
+                meta,
+                description: fs::read_to_string(dir.join("description.md")).unwrap_or_default(),
+                image_url: if thumbnail.is_file() {
+                    thumbnail.to_string_lossy().into_owned()
+                } else {
+                    String::new()
+                },
+                source: String::new(),
+            });
+        }
+        Ok(out)
+    }
+}
+
+impl ModSource for LocalDirSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<Vec<CatalogEntry>, AppError>> {
+        Box::pin(async move { self.read() })
+    }
+}
+
+fn read_to_string(path: &Path) -> Result<String, AppError> {
+    fs::read_to_string(path).map_err(|e| AppError::FileRead {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })
+}
+
+/// The source a configuration row describes.
+pub fn from_config(
+    config: &ModSourceConfig,
+    db: &Database,
+) -> Result<Box<dyn ModSource>, AppError> {
+    Ok(match config.kind {
+        SourceKind::Gitlab => {
+            let mut cache_dir = paths::manager_dir()?.join("mod_index_cache");
+            // The community index keeps the cache it always had
+            if config.name != Database::DEFAULT_SOURCE {
+                cache_dir = cache_dir.join(asset_slug(&config.name));
+            }
+            Box::new(GitLabSource::new(
+                &config.name,
+                CatalogClient::for_repository(&config.location, cache_dir)?,
+            ))
+        }
+        SourceKind::IndexUrl => Box::new(IndexUrlSource::new(&config.name, &config.location)),
+        SourceKind::LocalDir => Box::new(LocalDirSource::new(&config.name, &config.location)),
+        SourceKind::Thunderstore => Box::new(
+            ThunderstoreSource::new(&config.name, ThunderstoreClient::with_url(&config.location))
+                .with_origins(db.get_thunderstore_origins()?),
+        ),
+    })
+}
+
+/// Enabled sources from the database, in priority order. Misconfigured ones are
+/// skipped with a warning so one bad entry doesn't hide the whole catalog.
+pub fn configured(db: &Database) -> Result<Vec<Box<dyn ModSource>>, AppError> {
+    Ok(db
+        .get_mod_sources()?
+        .iter()
+        .filter(|c| c.enabled)
+        .filter_map(|c| match from_config(c, db) {
+            Ok(source) => Some(source),
+            Err(e) => {
+                log::warn!("Ignoring mod source '{}': {e}", c.name);
+                None
+            }
+        })
+        .collect())
+}
+
+/// Fetch every source at once and merge their entries in source order, tagging each
+/// with its source. A mod listed by several sources comes from the first of them.
+/// Sources that fail are skipped; the error is only returned if all of them fail.
+pub async fn fetch_all(sources: &[Box<dyn ModSource>]) -> Result<Vec<CatalogEntry>, AppError> {
+    let results = join_all(sources.iter().map(|s| s.fetch())).await;
+
+    let mut seen = HashSet::new();
+    let mut merged = Vec::new();
+    let mut last_error = None;
+    let mut any_ok = false;
+    for (source, result) in sources.iter().zip(results) {
+        match result {
+            Ok(entries) => {
+                any_ok = true;
+                for mut entry in entries {
+                    if seen.insert(entry.meta.title.to_lowercase()) {
+                        entry.source = source.name().to_string();
+                        merged.push(entry);
+                    }
+                }
+            }
+            Err(e) => {
+                log::warn!("Mod source '{}' failed: {e}", source.name());
+                last_error = Some(e);
+            }
+        }
+    }
+    match last_error {
+        Some(e) if !any_ok => Err(e),
+        _ => {
+            merged.sort_by_cached_key(|e| e.meta.title.to_lowercase());
+            Ok(merged)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn meta(title: &str, version: &str) -> String {
+        format!(
+            r#"{{"requires-steamodded": false, "requires-talisman": false, "categories": [],
+                "author": "a", "repo": "", "title": "{title}", "version": "{version}"}}"#
+        )
+    }
+
+    #[tokio::test]
+    async fn merges_sources_in_priority_order() {
+        let td = tempdir().unwrap();
+        for (dir, title, version) in [("Cool", "Cool Mod", "2.0"), ("Private", "Private", "1.0")] {
+            let mod_dir = td.path().join("mods").join(dir);
+            fs::create_dir_all(&mod_dir).unwrap();
+            fs::write(mod_dir.join("meta.json"), meta(title, version)).unwrap();
+        }
+        fs::write(td.path().join("mods/Cool/description.md"), "local copy").unwrap();
+
+        let mut server = mockito::Server::new_async().await;
+        let _index = server
+            .mock("GET", "/index.json")
+            .with_status(200)
+            .with_body(format!(
+                r#"{{"version": 1, "mods": [
+                    {{"dir_name": "Cool", "meta": {}, "description": "", "image_url": ""}},
+                    {{"dir_name": "Other", "meta": {}, "description": "", "image_url": ""}}
+                ]}}"#,
+                meta("Cool Mod", "1.0"),
+                meta("Other", "0.1")
+            ))
+            .create_async()
+            .await;
+
+        let sources: Vec<Box<dyn ModSource>> = vec![
+            Box::new(LocalDirSource::new("local", td.path())),
+            Box::new(IndexUrlSource::new(
+                "mirror",
+                &format!("{}/index.json", server.url()),
+            )),
+            Box::new(LocalDirSource::new("missing", td.path().join("nope"))),
+        ];
+        let merged = fetch_all(&sources).await.unwrap();
+        let tagged: Vec<_> = merged
+            .iter()
+            .map(|e| (e.meta.title.as_str(), e.source.as_str()))
+            .collect();
+        assert_eq!(
+            tagged,
+            [
+                ("Cool Mod", "local"),
+                ("Other", "mirror"),
+                ("Private", "local")
+            ]
+        );
+        assert_eq!(merged[0].meta.version, "2.0");
+        assert_eq!(merged[0].description, "local copy");
+
+        assert!(fetch_all(&sources[2..]).await.is_err());
+    }
+}
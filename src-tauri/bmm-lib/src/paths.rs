@@ -0,0 +1,250 @@
+use crate::database::Database;
+use crate::errors::AppError;
+use crate::lovely::BALATRO_STEAM_APP_ID;
+use lazy_static::lazy_static;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Overrides every other source for the Mods directory.
+pub const MODS_DIR_ENV: &str = "BMM_MODS_DIR";
+/// Redirects every path below into a sandbox root (`<root>/config`, `<root>/cache`),
+/// so tests can run real install flows without touching the user's files.
+pub const TEST_DIR_ENV: &str = "BMM_TEST_DIR";
+
+/// What the game's data location depends on, taken from settings and the active install.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PathConfig {
+    /// Mods directory chosen in settings.
+    pub mods_dir: Option<PathBuf>,
+    /// Wine prefix the active install runs in; the game then reads its data from
+    /// the prefix's AppData rather than the host config dir.
+    pub wine_prefix: Option<PathBuf>,
+}
+
+lazy_static! {
+    static ref CONFIG: RwLock<PathConfig> = RwLock::new(PathConfig::default());
+}
+
+pub fn configure(config: PathConfig) {
+    match CONFIG.write() {
+        Ok(mut guard) => *guard = config,
+        Err(e) => log::error!("Path config lock poisoned: {e}"),
+    }
+}
+
+pub fn current() -> PathConfig {
+    CONFIG.read().map(|c| c.clone()).unwrap_or_default()
+}
+
+/// Re-read the settings and active install. Call after anything that changes them.
+pub fn refresh(db: &Database) -> Result<(), AppError> {
+    configure(config_from_db(db)?);
+    Ok(())
+}
+
+pub fn config_from_db(db: &Database) -> Result<PathConfig, AppError> {
+    let wine_prefix = match db.get_installation_path()? {
+        Some(game_dir) if cfg!(target_os = "linux") => {
+            let game_dir = PathBuf::from(game_dir);
+            let mode = db
+                .get_linux_launch_mode()?
+                .unwrap_or_else(|| crate::lovely::detect_linux_launch_mode(&game_dir));
+            match mode {
+                crate::lovely::LinuxLaunchMode::Proton => proton_prefix(&game_dir),
+                crate::lovely::LinuxLaunchMode::Native => None,
+            }
+        }
+        _ => None,
+    };
+    Ok(PathConfig {
+        mods_dir: db.get_mods_dir()?.map(PathBuf::from),
+        wine_prefix,
+    })
+}
+
+/// The Proton prefix Steam keeps next to the library `game_dir` was installed into.
+pub fn proton_prefix(game_dir: &Path) -> Option<PathBuf> {
+    let steamapps = game_dir.parent()?.parent()?;
+    Some(
+        steamapps
+            .join("compatdata")
+            .join(BALATRO_STEAM_APP_ID)
+            .join("pfx"),
+    )
+}
+
+/// `%APPDATA%` as seen by a Windows program running inside `prefix`.
+pub fn prefix_appdata(prefix: &Path) -> PathBuf {
+    prefix
+        .join("drive_c")
+        .join("users")
+        .join("steamuser")
+        .join("AppData")
+        .join("Roaming")
+}
+
+pub fn sandbox_root() -> Option<PathBuf> {
+    std::env::var_os(TEST_DIR_ENV)
+        .filter(|d| !d.is_empty())
+        .map(PathBuf::from)
+}
+
+fn host_config_dir() -> Result<PathBuf, AppError> {
+    if let Some(root) = sandbox_root() {
+        return Ok(root.join("config"));
+    }
+    dirs::config_dir().ok_or_else(|| AppError::DirNotFound(PathBuf::from("config directory")))
+}
+
+fn host_data_dir() -> Result<PathBuf, AppError> {
+    if let Some(root) = sandbox_root() {
+        return Ok(root.join("data"));
+    }
+    dirs::data_dir().ok_or_else(|| AppError::DirNotFound(PathBuf::from("data directory")))
+}
+
+/// The manager's own files: database, backups, logs, downloaded binaries.
+pub fn manager_dir() -> Result<PathBuf, AppError> {
+    Ok(host_config_dir()?.join("Balatro"))
+}
+
+/// Where the game (through Lovely) reads its data from.
+pub fn game_data_dir() -> Result<PathBuf, AppError> {
+    Ok(resolve_game_data_dir(&current(), &host_config_dir()?))
+}
+
+/// Where the game keeps its saves (and Steamodded its mod configs).
+pub fn save_dir() -> Result<PathBuf, AppError> {
+    Ok(resolve_save_dir(
+        &current(),
+        cfg!(target_os = "linux"),
+        &host_config_dir()?,
+        &host_data_dir()?,
+    ))
+}
+
+pub fn mods_dir() -> Result<PathBuf, AppError> {
+    Ok(resolve_mods_dir(
+        &current(),
+        std::env::var_os(MODS_DIR_ENV),
+        &host_config_dir()?,
+    ))
+}
+
+/// Lovely binaries the manager downloads for native launches.
+pub fn bins_dir() -> Result<PathBuf, AppError> {
+    Ok(manager_dir()?.join("bins"))
+}
+
+pub fn logs_dir() -> Result<PathBuf, AppError> {
+    Ok(manager_dir()?.join("logs"))
+}
+
+pub fn cache_dir() -> Result<PathBuf, AppError> {
+    let root = match sandbox_root() {
+        Some(root) => root.join("cache"),
+        None => dirs::cache_dir()
+            .ok_or_else(|| AppError::DirNotFound(PathBuf::from("cache directory")))?,
+    };
+    Ok(root.join("balatro-mod-manager"))
+}
+
+fn resolve_game_data_dir(config: &PathConfig, config_root: &Path) -> PathBuf {
+    match &config.wine_prefix {
+        Some(prefix) => prefix_appdata(prefix).join("Balatro"),
+        None => config_root.join("Balatro"),
+    }
+}
+
+// A native LÖVE launch on Linux saves under LÖVE's own data dir, while Lovely still
+// reads mods from the game data dir
+fn resolve_save_dir(
+    config: &PathConfig,
+    linux: bool,
+    config_root: &Path,
+    data_root: &Path,
+) -> PathBuf {
+    if linux && config.wine_prefix.is_none() {
+        data_root.join("love").join("Balatro")
+    } else {
+        resolve_game_data_dir(config, config_root)
+    }
+}
+
+fn resolve_mods_dir(config: &PathConfig, env: Option<OsString>, config_root: &Path) -> PathBuf {
+    if let Some(dir) = env.filter(|d| !d.is_empty()) {
+        return PathBuf::from(dir);
+    }
+    if let Some(dir) = &config.mods_dir {
+        return dir.clone();
+    }
+    resolve_game_data_dir(config, config_root).join("Mods")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mods_dir_precedence() {
+        let root = Path::new("/home/u/.config");
+        let mut config = PathConfig::default();
+        assert_eq!(
+            resolve_mods_dir(&config, None, root),
+            root.join("Balatro").join("Mods")
+        );
+
+        config.wine_prefix = proton_prefix(Path::new("/lib/steamapps/common/Balatro"));
+        assert_eq!(
+            resolve_mods_dir(&config, None, root),
+            Path::new(
+                "/lib/steamapps/compatdata/2379780/pfx/drive_c/users/steamuser/AppData/Roaming/Balatro/Mods"
+            )
+        );
+
+        config.mods_dir = Some(PathBuf::from("/custom/Mods"));
+        assert_eq!(
+            resolve_mods_dir(&config, Some(OsString::new()), root),
+            Path::new("/custom/Mods")
+        );
+        assert_eq!(
+            resolve_mods_dir(&config, Some(OsString::from("/env/Mods")), root),
+            Path::new("/env/Mods")
+        );
+    }
+
+    #[test]
+    fn save_dir_follows_launch_mode() {
+        let config_root = Path::new("/home/u/.config");
+        let data_root = Path::new("/home/u/.local/share");
+        let mut config = PathConfig::default();
+        assert_eq!(
+            resolve_save_dir(&config, false, config_root, data_root),
+            config_root.join("Balatro")
+        );
+        assert_eq!(
+            resolve_save_dir(&config, true, config_root, data_root),
+            Path::new("/home/u/.local/share/love/Balatro")
+        );
+
+        config.wine_prefix = proton_prefix(Path::new("/lib/steamapps/common/Balatro"));
+        assert_eq!(
+            resolve_save_dir(&config, true, config_root, data_root),
+            Path::new(
+                "/lib/steamapps/compatdata/2379780/pfx/drive_c/users/steamuser/AppData/Roaming/Balatro"
+            )
+        );
+    }
+
+    #[test]
+    fn config_from_db_reads_mods_setting() {
+        let db = Database::open_in_memory().unwrap();
+        assert_eq!(config_from_db(&db).unwrap(), PathConfig::default());
+        db.set_mods_dir(Some("/custom/Mods")).unwrap();
+        assert_eq!(
+            config_from_db(&db).unwrap().mods_dir,
+            Some(PathBuf::from("/custom/Mods"))
+        );
+    }
+}
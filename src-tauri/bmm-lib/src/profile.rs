@@ -0,0 +1,167 @@
+use crate::cache::Mod;
+use crate::database::{Database, InstalledMod};
+use crate::errors::AppError;
+use crate::mod_manager;
+use crate::save_backup::{self, SaveBackupReason};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+
+/// A set of mods to reproduce on another machine, stored as JSON:
+/// `{"name": "...", "mods": [{"name": "Cryptid"}, {"name": "X", "url": "...", "enabled": false}]}`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Profile {
+    pub name: String,
+    #[serde(default)]
+    pub mods: Vec<ProfileMod>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ProfileMod {
+    /// Catalog title, or the name to track a URL install under.
+    pub name: String,
+    /// Archive to install from instead of the catalog.
+    #[serde(default)]
+    pub url: Option<String>,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// What applying a profile changes.
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct ProfilePlan {
+    pub install: Vec<ProfileMod>,
+    pub enable: Vec<String>,
+    pub disable: Vec<String>,
+}
+
+pub fn load(path: &Path) -> Result<Profile, AppError> {
+    let data = fs::read_to_string(path).map_err(|e| AppError::FileRead {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    serde_json::from_str(&data).map_err(|e| AppError::JsonParse {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })
+}
+
+/// Install what the profile lists but isn't installed, set each listed mod's enabled
+/// state, and disable installed mods the profile leaves out (unless something it
+/// enables depends on them).
+pub fn plan(profile: &Profile, installed: &[InstalledMod]) -> ProfilePlan {
+    let is_installed = |name: &str| installed.iter().any(|m| m.name.eq_ignore_ascii_case(name));
+    let listed: HashSet<String> = profile.mods.iter().map(|m| m.name.to_lowercase()).collect();
+    let required: HashSet<String> = installed
+        .iter()
+        .filter(|m| {
+            profile
+                .mods
+                .iter()
+                .any(|p| p.enabled && p.name.eq_ignore_ascii_case(&m.name))
+        })
+        .flat_map(|m| m.dependencies.iter().map(|d| d.to_lowercase()))
+        .collect();
+
+    let mut plan = ProfilePlan::default();
+    for wanted in &profile.mods {
+        if !is_installed(&wanted.name) {
+            plan.install.push(wanted.clone());
+        }
+        if wanted.enabled {
+            plan.enable.push(wanted.name.clone());
+        } else {
+            plan.disable.push(wanted.name.clone());
+        }
+    }
+    for m in installed {
+        let key = m.name.to_lowercase();
+        if !listed.contains(&key) && !required.contains(&key) {
+            plan.disable.push(m.name.clone());
+        }
+    }
+    plan
+}
+
+pub async fn apply(
+    db: &Database,
+    catalog: &[Mod],
+    profile: &Profile,
+) -> Result<ProfilePlan, AppError> {
+    let plan = plan(profile, &db.get_installed_mods()?);
+    if !plan.install.is_empty() {
+        save_backup::snapshot_quietly(db, SaveBackupReason::ModInstall);
+    }
+    for wanted in &plan.install {
+        match &wanted.url {
+            Some(url) => {
+                mod_manager::install_url(db, url, Some(&wanted.name)).await?;
+            }
+            None => {
+                let entry =
+                    mod_manager::find_in_catalog(catalog, &wanted.name).ok_or_else(|| {
+                        AppError::ModNotFound {
+                            mod_name: wanted.name.clone(),
+                            version: String::new(),
+                        }
+                    })?;
+                mod_manager::install_catalog_entry(db, entry).await?;
+            }
+        }
+    }
+    for name in &plan.enable {
+        mod_manager::set_mod_enabled(db, name, true)?;
+    }
+    for name in &plan.disable {
+        mod_manager::set_mod_enabled(db, name, false)?;
+    }
+    Ok(plan)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn installed(name: &str, dependencies: &[&str]) -> InstalledMod {
+        InstalledMod {
+            name: name.to_string(),
+            path: String::new(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+            current_version: None,
+        }
+    }
+
+    #[test]
+    fn plan_installs_missing_and_disables_unlisted() {
+        let profile: Profile = serde_json::from_str(
+            r#"{"name": "run", "mods": [
+                {"name": "Cryptid"},
+                {"name": "Talisman", "enabled": false},
+                {"name": "Extra", "url": "https://example.com/extra.zip"}
+            ]}"#,
+        )
+        .unwrap();
+        let current = [
+            installed("cryptid", &["Steamodded"]),
+            installed("Steamodded", &[]),
+            installed("Talisman", &[]),
+            installed("Old", &[]),
+        ];
+
+        let plan = plan(&profile, &current);
+        assert_eq!(
+            plan.install
+                .iter()
+                .map(|m| m.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Extra"]
+        );
+        assert_eq!(plan.enable, ["Cryptid", "Extra"]);
+        assert_eq!(plan.disable, ["Talisman", "Old"]);
+    }
+}
@@ -0,0 +1,186 @@
+use crate::errors::AppError;
+use crate::mod_manager::{self, IGNORE_FILE};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Mods disabled for a safe-mode launch, persisted so they can be re-enabled
+/// even if the manager exits before the game does.
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct SafeModeState {
+    disabled: Vec<PathBuf>,
+    /// The markers safe mode wrote; only these are removed again, so mods the
+    /// user disabled inside a pack stay disabled. Older state files lack them.
+    #[serde(default)]
+    markers: Option<Vec<PathBuf>>,
+}
+
+pub fn default_state_file() -> Result<PathBuf, AppError> {
+    Ok(crate::paths::manager_dir()?.join("safe_mode.json"))
+}
+
+/// Steamodded stays enabled in safe mode; everything else is a regular mod.
+pub fn is_steamodded_dir(path: &Path) -> bool {
+    let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+        return false;
+    };
+    let lower = name.to_lowercase();
+    lower.contains("steamodded") || lower == "smods" || lower.starts_with("smods-")
+}
+
+/// Disable every enabled mod except Steamodded by dropping a `.lovelyignore` into it.
+/// Returns the mod folders that were disabled.
+pub fn enter(mods_dir: &Path, state_file: &Path) -> Result<Vec<PathBuf>, AppError> {
+    // A previous safe-mode session that never got cleaned up comes first
+    restore(state_file)?;
+
+    let entries = fs::read_dir(mods_dir).map_err(|e| AppError::FileRead {
+        path: mods_dir.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    let mut disabled: Vec<PathBuf> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_dir())
+        .filter(|p| {
+            let name = p.file_name().map(|n| n.to_string_lossy().to_string());
+            !matches!(name.as_deref(), Some("lovely") | Some(".lovely"))
+        })
+        .filter(|p| !is_steamodded_dir(p) && mod_manager::is_enabled(p))
+        .collect();
+    disabled.sort();
+
+    let mut markers = Vec::new();
+    for dir in &disabled {
+        markers.extend(
+            mod_manager::ignore_markers(dir)?
+                .into_iter()
+                .filter(|m| !m.exists()),
+        );
+    }
+
+    // Record before touching anything so a failure halfway can still be undone
+    save_state(
+        state_file,
+        &SafeModeState {
+            disabled: disabled.clone(),
+            markers: Some(markers.clone()),
+        },
+    )?;
+    for marker in markers {
+        if let Err(e) = fs::write(&marker, "") {
+            restore(state_file)?;
+            return Err(AppError::FileWrite {
+                path: marker,
+                source: e.to_string(),
+            });
+        }
+    }
+
+    log::info!("Safe mode: disabled {} mods", disabled.len());
+    Ok(disabled)
+}
+
+/// Re-enable the mods disabled by [`enter`]. Returns how many were re-enabled.
+pub fn restore(state_file: &Path) -> Result<usize, AppError> {
+    if !state_file.exists() {
+        return Ok(0);
+    }
+    let data = fs::read_to_string(state_file).map_err(|e| AppError::FileRead {
+        path: state_file.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    let state: SafeModeState = serde_json::from_str(&data).map_err(|e| AppError::JsonParse {
+        path: state_file.to_path_buf(),
+        source: e.to_string(),
+    })?;
+
+    let markers = state.markers.unwrap_or_else(|| {
+        state
+            .disabled
+            .iter()
+            .map(|dir| dir.join(IGNORE_FILE))
+            .collect()
+    });
+    for marker in &markers {
+        if marker.exists() {
+            if let Err(e) = fs::remove_file(marker) {
+                log::warn!("Failed to remove {}: {e}", marker.display());
+            }
+        }
+    }
+    let restored = state
+        .disabled
+        .iter()
+        .filter(|dir| mod_manager::is_enabled(dir))
+        .count();
+
+    fs::remove_file(state_file).map_err(|e| AppError::FileWrite {
+        path: state_file.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    log::info!("Safe mode: re-enabled {restored} mods");
+    Ok(restored)
+}
+
+pub fn is_active(state_file: &Path) -> bool {
+    state_file.exists()
+}
+
+fn save_state(state_file: &Path, state: &SafeModeState) -> Result<(), AppError> {
+    if let Some(parent) = state_file.parent() {
+        fs::create_dir_all(parent).map_err(|e| AppError::DirCreate {
+            path: parent.to_path_buf(),
+            source: e.to_string(),
+        })?;
+    }
+    let json = serde_json::to_string_pretty(state)
+        .map_err(|e| AppError::InvalidState(format!("Failed to serialize safe mode state: {e}")))?;
+    fs::write(state_file, json).map_err(|e| AppError::FileWrite {
+        path: state_file.to_path_buf(),
+        source: e.to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_mode_disables_and_restores_only_enabled_mods() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mods = tmp.path().join("Mods");
+        for name in ["Steamodded", "Cryptid", "Talisman", "lovely", "Disabled"] {
+            fs::create_dir_all(mods.join(name)).unwrap();
+        }
+        fs::write(mods.join("Disabled").join(IGNORE_FILE), "").unwrap();
+        // A pack with one bundled mod enabled and one the user turned off
+        fs::create_dir_all(mods.join("Pack").join("On")).unwrap();
+        fs::create_dir_all(mods.join("Pack").join("Off")).unwrap();
+        fs::write(mods.join("Pack").join("Off").join(IGNORE_FILE), "").unwrap();
+        let state_file = tmp.path().join("safe_mode.json");
+
+        let disabled = enter(&mods, &state_file).unwrap();
+        assert_eq!(
+            disabled,
+            vec![
+                mods.join("Cryptid"),
+                mods.join("Pack"),
+                mods.join("Talisman")
+            ]
+        );
+        assert!(is_active(&state_file));
+        assert!(mods.join("Cryptid").join(IGNORE_FILE).exists());
+        assert!(!mod_manager::is_enabled(&mods.join("Pack").join("On")));
+        assert!(!mods.join("Steamodded").join(IGNORE_FILE).exists());
+        assert!(!mods.join("lovely").join(IGNORE_FILE).exists());
+
+        assert_eq!(restore(&state_file).unwrap(), 3);
+        assert!(!is_active(&state_file));
+        assert!(!mods.join("Cryptid").join(IGNORE_FILE).exists());
+        assert!(mod_manager::is_enabled(&mods.join("Pack").join("On")));
+        // Mods the user had disabled stay disabled
+        assert!(mods.join("Disabled").join(IGNORE_FILE).exists());
+        assert!(mods.join("Pack").join("Off").join(IGNORE_FILE).exists());
+    }
+}
@@ -0,0 +1,795 @@
+use crate::backup::{remove_path, sibling_path};
+use crate::database::Database;
+use crate::errors::AppError;
+use chrono::Local;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub const DEFAULT_SAVE_BACKUP_RETENTION: usize = 20;
+
+const ARCHIVE_EXTENSION: &str = "tar.gz";
+const MANIFEST_EXTENSION: &str = "json";
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SaveBackupReason {
+    Launch,
+    ModInstall,
+    ModUpdate,
+    PreRestore,
+    Manual,
+}
+
+impl SaveBackupReason {
+    /// `ModUpdate` when a mod already sits in `folder` under the Mods dir, else `ModInstall`.
+    pub fn for_install(folder: Option<&str>) -> Self {
+        let exists = folder.is_some_and(|name| {
+            crate::paths::mods_dir()
+                .map(|d| d.join(name).exists())
+                .unwrap_or(false)
+        });
+        if exists {
+            Self::ModUpdate
+        } else {
+            Self::ModInstall
+        }
+    }
+}
+
+/// Best-effort snapshot taken before launches and installs; it never fails the caller.
+pub fn snapshot_quietly(db: &Database, reason: SaveBackupReason) {
+    match SaveBackupManager::from_db(db) {
+        Ok(manager) => manager.snapshot_quietly(reason),
+        Err(e) => log::warn!("Failed to snapshot save files ({reason:?}): {e}"),
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SaveFileEntry {
+    pub size: u64,
+    pub hash: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SaveSnapshot {
+    pub id: String,
+    pub created_at: u64,
+    pub reason: SaveBackupReason,
+    pub archive_size: u64,
+    // Relative path (always '/'-separated) -> size and content hash
+    pub files: BTreeMap<String, SaveFileEntry>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SaveFileStatus {
+    Added,
+    Removed,
+    Modified,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SaveFileChange {
+    pub path: String,
+    pub status: SaveFileStatus,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+}
+
+/// Snapshots the Balatro save directory into timestamped, gzip-compressed archives.
+///
+/// Only the game's own save data is captured (top-level `*.jkr` files and the
+/// numbered profile folders). On Windows and macOS the save directory also holds
+/// `Mods`, logs and our database, which must never be touched by a restore.
+pub struct SaveBackupManager {
+    save_dir: PathBuf,
+    backup_dir: PathBuf,
+    retention: usize,
+}
+
+impl SaveBackupManager {
+    pub fn new(retention: usize) -> Result<Self, AppError> {
+        let backup_dir = crate::paths::manager_dir()?.join("save_backups");
+        Ok(Self::with_dirs(
+            crate::paths::save_dir()?,
+            backup_dir,
+            retention,
+        ))
+    }
+
+    /// A manager for the game's save dir that keeps as many snapshots as the user chose.
+    pub fn from_db(db: &Database) -> Result<Self, AppError> {
+        Self::new(db.get_save_backup_retention()?)
+    }
+
+    pub fn with_dirs(save_dir: PathBuf, backup_dir: PathBuf, retention: usize) -> Self {
+        Self {
+            save_dir,
+            backup_dir,
+            retention: retention.max(1),
+        }
+    }
+
+    pub fn save_dir(&self) -> &Path {
+        &self.save_dir
+    }
+
+    /// Create a snapshot of the current save files, then drop the oldest ones beyond
+    /// the retention limit. Returns `None` when there is nothing to back up (no saves yet).
+    pub fn snapshot(&self, reason: SaveBackupReason) -> Result<Option<SaveSnapshot>, AppError> {
+        let snapshot = self.create_snapshot(reason)?;
+        if snapshot.is_some() {
+            self.prune()?;
+        }
+        Ok(snapshot)
+    }
+
+    /// [`Self::snapshot`] for callers that go ahead either way; failures are only logged.
+    pub fn snapshot_quietly(&self, reason: SaveBackupReason) {
+        if let Err(e) = self.snapshot(reason) {
+            log::warn!("Failed to snapshot save files ({reason:?}): {e}");
+        }
+    }
+
+    fn create_snapshot(&self, reason: SaveBackupReason) -> Result<Option<SaveSnapshot>, AppError> {
+        let files = collect_save_files(&self.save_dir)?;
+        if files.is_empty() {
+            log::debug!(
+                "No save files found in {}; skipping snapshot",
+                self.save_dir.display()
+            );
+            return Ok(None);
+        }
+
+        fs::create_dir_all(&self.backup_dir).map_err(|e| AppError::DirCreate {
+            path: self.backup_dir.clone(),
+            source: e.to_string(),
+        })?;
+
+        let id = self.next_id();
+        let archive_path = self.archive_path(&id);
+        let manifest_path = self.manifest_path(&id);
+        let result = self.write_snapshot(&id, reason, &files, &archive_path, &manifest_path);
+        if result.is_err() {
+            // Half-written snapshots would be listed (or counted for retention) as good ones
+            let _ = remove_path(&manifest_path);
+            let _ = remove_path(&archive_path);
+        }
+        let snapshot = result?;
+
+        log::info!(
+            "Created save snapshot {id} ({reason:?}, {} files)",
+            files.len()
+        );
+        Ok(Some(snapshot))
+    }
+
+    fn write_snapshot(
+        &self,
+        id: &str,
+        reason: SaveBackupReason,
+        files: &[String],
+        archive_path: &Path,
+        manifest_path: &Path,
+    ) -> Result<SaveSnapshot, AppError> {
+        let archive = File::create(archive_path).map_err(|e| AppError::FileWrite {
+            path: archive_path.to_path_buf(),
+            source: e.to_string(),
+        })?;
+
+        let mut builder = tar::Builder::new(GzEncoder::new(archive, Compression::default()));
+        let mut manifest_files = BTreeMap::new();
+        for rel in files {
+            let abs = self.save_dir.join(rel);
+            let data = fs::read(&abs).map_err(|e| AppError::FileRead {
+                path: abs.clone(),
+                source: e.to_string(),
+            })?;
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(get_mtime(&abs));
+            header.set_cksum();
+            builder
+                .append_data(&mut header, rel, data.as_slice())
+                .map_err(|e| AppError::FileWrite {
+                    path: archive_path.to_path_buf(),
+                    source: e.to_string(),
+                })?;
+            manifest_files.insert(
+                rel.clone(),
+                SaveFileEntry {
+                    size: data.len() as u64,
+                    hash: content_hash(&data),
+                },
+            );
+        }
+        let encoder = builder.into_inner().map_err(|e| AppError::FileWrite {
+            path: archive_path.to_path_buf(),
+            source: e.to_string(),
+        })?;
+        encoder.finish().map_err(|e| AppError::FileWrite {
+            path: archive_path.to_path_buf(),
+            source: e.to_string(),
+        })?;
+
+        let snapshot = SaveSnapshot {
+            id: id.to_string(),
+            created_at: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+            reason,
+            archive_size: fs::metadata(archive_path).map(|m| m.len()).unwrap_or(0),
+            files: manifest_files,
+        };
+        fs::write(manifest_path, serde_json::to_vec_pretty(&snapshot)?).map_err(|e| {
+            AppError::FileWrite {
+                path: manifest_path.to_path_buf(),
+                source: e.to_string(),
+            }
+        })?;
+        Ok(snapshot)
+    }
+
+    /// List snapshots, newest first.
+    pub fn list(&self) -> Result<Vec<SaveSnapshot>, AppError> {
+        if !self.backup_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let entries = fs::read_dir(&self.backup_dir).map_err(|e| AppError::FileRead {
+            path: self.backup_dir.clone(),
+            source: e.to_string(),
+        })?;
+
+        let mut snapshots = Vec::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().and_then(|e| e.to_str()) != Some(MANIFEST_EXTENSION) {
+                continue;
+            }
+            match read_manifest(&path) {
+                Ok(snapshot) => snapshots.push(snapshot),
+                Err(e) => log::warn!("Skipping unreadable save snapshot manifest: {e}"),
+            }
+        }
+
+        snapshots.sort_by(|a, b| b.id.cmp(&a.id));
+        Ok(snapshots)
+    }
+
+    pub fn get(&self, id: &str) -> Result<SaveSnapshot, AppError> {
+        validate_id(id)?;
+        let manifest_path = self.manifest_path(id);
+        if !manifest_path.exists() {
+            return Err(AppError::FileNotFound {
+                path: manifest_path,
+                source: format!("Save snapshot '{id}' not found"),
+            });
+        }
+        read_manifest(&manifest_path)
+    }
+
+    /// Compare a snapshot against another snapshot, or against the live save
+    /// directory when `other` is `None`. Changes describe going from `id` to `other`.
+    pub fn diff(&self, id: &str, other: Option<&str>) -> Result<Vec<SaveFileChange>, AppError> {
+        let base = self.get(id)?.files;
+        let target = match other {
+            Some(other_id) => self.get(other_id)?.files,
+            None => {
+                let mut current = BTreeMap::new();
+                for rel in collect_save_files(&self.save_dir)? {
+                    let abs = self.save_dir.join(&rel);
+                    let data = fs::read(&abs).map_err(|e| AppError::FileRead {
+                        path: abs.clone(),
+                        source: e.to_string(),
+                    })?;
+                    current.insert(
+                        rel,
+                        SaveFileEntry {
+                            size: data.len() as u64,
+                            hash: content_hash(&data),
+                        },
+                    );
+                }
+                current
+            }
+        };
+
+        Ok(diff_file_maps(&base, &target))
+    }
+
+    /// Restore a snapshot over the live save files.
+    /// The archive is read and checked against its manifest before anything is touched,
+    /// and the current state is snapshotted first so a restore can itself be undone.
+    pub fn restore(&self, id: &str) -> Result<(), AppError> {
+        let snapshot = self.get(id)?;
+        let files = self.read_archive(&snapshot)?;
+
+        // Pruning waits until the restore is done: with retention full, it would
+        // otherwise delete the very snapshot being restored
+        self.create_snapshot(SaveBackupReason::PreRestore)?;
+
+        fs::create_dir_all(&self.save_dir).map_err(|e| AppError::DirCreate {
+            path: self.save_dir.clone(),
+            source: e.to_string(),
+        })?;
+        // Unpack next to the save folder and only swap the save entries in once every
+        // file is written, so a failed restore never leaves a half-restored profile
+        let staging = sibling_path(&self.save_dir, "restore")?;
+        remove_path(&staging)?;
+        let result =
+            write_files(&staging, files).and_then(|_| swap_save_entries(&staging, &self.save_dir));
+        let _ = remove_path(&staging);
+        result?;
+
+        log::info!("Restored save snapshot {id}");
+        self.prune()
+    }
+
+    // Unpack a snapshot into memory, making sure it holds exactly the files its
+    // manifest lists, with matching contents, and nothing outside the save data
+    fn read_archive(&self, snapshot: &SaveSnapshot) -> Result<Vec<(String, Vec<u8>)>, AppError> {
+        let archive_path = self.archive_path(&snapshot.id);
+        let damaged = |reason: String| AppError::FileRead {
+            path: archive_path.clone(),
+            source: format!("Save snapshot '{}' is damaged: {reason}", snapshot.id),
+        };
+        let archive = File::open(&archive_path).map_err(|e| AppError::FileRead {
+            path: archive_path.clone(),
+            source: e.to_string(),
+        })?;
+        let mut tar = tar::Archive::new(GzDecoder::new(archive));
+        let entries = tar.entries().map_err(|e| damaged(e.to_string()))?;
+
+        let mut files = Vec::new();
+        for entry in entries {
+            let mut entry = entry.map_err(|e| damaged(e.to_string()))?;
+            let path = entry.path().map_err(|e| damaged(e.to_string()))?;
+            let rel = path.to_string_lossy().replace('\\', "/");
+            if !is_safe_save_path(&path) {
+                return Err(AppError::PathValidation {
+                    path: archive_path.clone(),
+                    reason: format!("Unexpected path in save snapshot: {rel}"),
+                });
+            }
+            let mut data = Vec::new();
+            entry
+                .read_to_end(&mut data)
+                .map_err(|e| damaged(e.to_string()))?;
+            match snapshot.files.get(&rel) {
+                Some(expected)
+                    if expected.size == data.len() as u64
+                        && expected.hash == content_hash(&data) => {}
+                _ => return Err(damaged(format!("{rel} does not match the manifest"))),
+            }
+            files.push((rel, data));
+        }
+        if files.len() != snapshot.files.len() {
+            return Err(damaged("files are missing from the archive".to_string()));
+        }
+        Ok(files)
+    }
+
+    pub fn delete(&self, id: &str) -> Result<(), AppError> {
+        validate_id(id)?;
+        for path in [self.archive_path(id), self.manifest_path(id)] {
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| AppError::FileWrite {
+                    path: path.clone(),
+                    source: e.to_string(),
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    fn prune(&self) -> Result<(), AppError> {
+        let snapshots = self.list()?;
+        for old in snapshots.iter().skip(self.retention) {
+            log::debug!("Pruning save snapshot {}", old.id);
+            self.delete(&old.id)?;
+        }
+        Ok(())
+    }
+
+    fn next_id(&self) -> String {
+        let base = Local::now().format("%Y%m%d-%H%M%S%3f").to_string();
+        let mut id = base.clone();
+        let mut n = 1;
+        while self.manifest_path(&id).exists() || self.archive_path(&id).exists() {
+            id = format!("{base}-{n}");
+            n += 1;
+        }
+        id
+    }
+
+    fn archive_path(&self, id: &str) -> PathBuf {
+        self.backup_dir.join(format!("{id}.{ARCHIVE_EXTENSION}"))
+    }
+
+    fn manifest_path(&self, id: &str) -> PathBuf {
+        self.backup_dir.join(format!("{id}.{MANIFEST_EXTENSION}"))
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<SaveSnapshot, AppError> {
+    let data = fs::read(path).map_err(|e| AppError::FileRead {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    serde_json::from_slice(&data).map_err(|e| AppError::JsonParse {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })
+}
+
+fn validate_id(id: &str) -> Result<(), AppError> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(AppError::InvalidState(format!(
+            "Invalid save snapshot id: {id}"
+        )));
+    }
+    Ok(())
+}
+
+fn write_files(dir: &Path, files: Vec<(String, Vec<u8>)>) -> Result<(), AppError> {
+    fs::create_dir_all(dir).map_err(|e| AppError::DirCreate {
+        path: dir.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    for (rel, data) in files {
+        let abs = dir.join(&rel);
+        if let Some(parent) = abs.parent() {
+            fs::create_dir_all(parent).map_err(|e| AppError::DirCreate {
+                path: parent.to_path_buf(),
+                source: e.to_string(),
+            })?;
+        }
+        fs::write(&abs, data).map_err(|e| AppError::FileWrite {
+            path: abs.clone(),
+            source: e.to_string(),
+        })?;
+    }
+    Ok(())
+}
+
+// Top-level names of the save entries in `dir`
+fn save_entries(dir: &Path) -> Result<Vec<String>, AppError> {
+    let entries = fs::read_dir(dir).map_err(|e| AppError::FileRead {
+        path: dir.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    Ok(entries
+        .flatten()
+        .filter(|e| is_save_entry(&e.file_name().to_string_lossy(), e.path().is_dir()))
+        .map(|e| e.file_name().to_string_lossy().to_string())
+        .collect())
+}
+
+// Replace the save entries of `save_dir` with those in `staged`, leaving everything
+// else (mods, configs) alone. The live entries are moved aside first and put back
+// if any rename fails.
+fn swap_save_entries(staged: &Path, save_dir: &Path) -> Result<(), AppError> {
+    let old = sibling_path(save_dir, "old")?;
+    remove_path(&old)?;
+    fs::create_dir_all(&old).map_err(|e| AppError::DirCreate {
+        path: old.clone(),
+        source: e.to_string(),
+    })?;
+
+    let mut moved_aside = Vec::new();
+    let mut moved_in = Vec::new();
+    let result = move_save_entries(save_dir, &old, &mut moved_aside)
+        .and_then(|_| move_save_entries(staged, save_dir, &mut moved_in));
+    let mut put_back = true;
+    if result.is_err() {
+        for name in &moved_in {
+            let _ = remove_path(&save_dir.join(name));
+        }
+        for name in &moved_aside {
+            put_back &= fs::rename(old.join(name), save_dir.join(name)).is_ok();
+        }
+    }
+    if !put_back {
+        log::error!(
+            "Saves from before the failed restore are left in {}",
+            old.display()
+        );
+    } else if let Err(e) = remove_path(&old) {
+        log::warn!("Failed to remove replaced saves {}: {e}", old.display());
+    }
+    result
+}
+
+fn move_save_entries(from: &Path, to: &Path, moved: &mut Vec<String>) -> Result<(), AppError> {
+    for name in save_entries(from)? {
+        fs::rename(from.join(&name), to.join(&name)).map_err(|e| AppError::FileWrite {
+            path: from.join(&name),
+            source: e.to_string(),
+        })?;
+        moved.push(name);
+    }
+    Ok(())
+}
+
+// Top-level `*.jkr` files (settings) and numbered profile folders are save data.
+fn is_save_entry(name: &str, is_dir: bool) -> bool {
+    if is_dir {
+        !name.is_empty() && name.chars().all(|c| c.is_ascii_digit())
+    } else {
+        name.ends_with(".jkr")
+    }
+}
+
+// Relative, inside a save entry, and free of `..` or absolute components
+fn is_safe_save_path(path: &Path) -> bool {
+    let mut components = path.components().peekable();
+    let Some(Component::Normal(first)) = components.next() else {
+        return false;
+    };
+    let nested = components.peek().is_some();
+    is_save_entry(&first.to_string_lossy(), nested)
+        && components.all(|c| matches!(c, Component::Normal(_)))
+}
+
+fn collect_save_files(save_dir: &Path) -> Result<Vec<String>, AppError> {
+    let mut files = Vec::new();
+    if !save_dir.exists() {
+        return Ok(files);
+    }
+
+    let entries = fs::read_dir(save_dir).map_err(|e| AppError::FileRead {
+        path: save_dir.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        let is_dir = path.is_dir();
+        if !is_save_entry(&name, is_dir) {
+            continue;
+        }
+        if is_dir {
+            collect_files_recursive(&path, &name, &mut files)?;
+        } else {
+            files.push(name);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn collect_files_recursive(
+    dir: &Path,
+    prefix: &str,
+    out: &mut Vec<String>,
+) -> Result<(), AppError> {
+    let entries = fs::read_dir(dir).map_err(|e| AppError::FileRead {
+        path: dir.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        let rel = format!("{prefix}/{}", entry.file_name().to_string_lossy());
+        if path.is_dir() {
+            collect_files_recursive(&path, &rel, out)?;
+        } else {
+            out.push(rel);
+        }
+    }
+    Ok(())
+}
+
+fn diff_file_maps(
+    base: &BTreeMap<String, SaveFileEntry>,
+    target: &BTreeMap<String, SaveFileEntry>,
+) -> Vec<SaveFileChange> {
+    let mut changes = Vec::new();
+    for (path, old) in base {
+        match target.get(path) {
+            None => changes.push(SaveFileChange {
+                path: path.clone(),
+                status: SaveFileStatus::Removed,
+                old_size: Some(old.size),
+                new_size: None,
+            }),
+            Some(new) if new.hash != old.hash || new.size != old.size => {
+                changes.push(SaveFileChange {
+                    path: path.clone(),
+                    status: SaveFileStatus::Modified,
+                    old_size: Some(old.size),
+                    new_size: Some(new.size),
+                })
+            }
+            Some(_) => {}
+        }
+    }
+    for (path, new) in target {
+        if !base.contains_key(path) {
+            changes.push(SaveFileChange {
+                path: path.clone(),
+                status: SaveFileStatus::Added,
+                old_size: None,
+                new_size: Some(new.size),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+fn get_mtime(path: &Path) -> u64 {
+    path.metadata()
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+// FNV-1a; only used to tell whether a save file changed between snapshots
+fn content_hash(data: &[u8]) -> u64 {
+    let mut hash: u64 = 1469598103934665603;
+    for b in data {
+        hash ^= *b as u64;
+        hash = hash.wrapping_mul(1099511628211);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(path: &Path, contents: &str) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    #[test]
+    fn snapshot_only_captures_save_files() {
+        let td = tempdir().unwrap();
+        let save_dir = td.path().join("Balatro");
+        write(&save_dir.join("settings.jkr"), "settings");
+        write(&save_dir.join("1/profile.jkr"), "profile");
+        write(&save_dir.join("Mods/SomeMod/main.lua"), "mod");
+        write(&save_dir.join("bmm_storage.db"), "db");
+
+        let manager = SaveBackupManager::with_dirs(save_dir, td.path().join("backups"), 5);
+        let snapshot = manager
+            .snapshot(SaveBackupReason::Manual)
+            .unwrap()
+            .expect("snapshot created");
+
+        let files: Vec<_> = snapshot.files.keys().cloned().collect();
+        assert_eq!(files, vec!["1/profile.jkr", "settings.jkr"]);
+        assert_eq!(manager.list().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn restore_roundtrip_and_diff() {
+        let td = tempdir().unwrap();
+        let save_dir = td.path().join("Balatro");
+        write(&save_dir.join("1/save.jkr"), "run one");
+        write(&save_dir.join("Mods/Keep/main.lua"), "mod");
+
+        let manager = SaveBackupManager::with_dirs(save_dir.clone(), td.path().join("b"), 5);
+        let snapshot = manager.snapshot(SaveBackupReason::Launch).unwrap().unwrap();
+
+        write(&save_dir.join("1/save.jkr"), "run two, longer");
+        write(&save_dir.join("2/profile.jkr"), "new profile");
+
+        let changes = manager.diff(&snapshot.id, None).unwrap();
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0].path, "1/save.jkr");
+        assert_eq!(changes[0].status, SaveFileStatus::Modified);
+        assert_eq!(changes[1].status, SaveFileStatus::Added);
+
+        manager.restore(&snapshot.id).unwrap();
+        assert_eq!(
+            fs::read_to_string(save_dir.join("1/save.jkr")).unwrap(),
+            "run one"
+        );
+        assert!(!save_dir.join("2/profile.jkr").exists());
+        assert!(save_dir.join("Mods/Keep/main.lua").exists());
+        // Nothing is left of the staging area
+        let leftovers: Vec<_> = fs::read_dir(td.path())
+            .unwrap()
+            .flatten()
+            .map(|e| e.file_name())
+            .filter(|n| n.to_string_lossy().starts_with('.'))
+            .collect();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+        // The pre-restore state was kept as its own snapshot
+        assert_eq!(manager.list().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn retention_prunes_oldest() {
+        let td = tempdir().unwrap();
+        let save_dir = td.path().join("Balatro");
+        write(&save_dir.join("settings.jkr"), "s");
+
+        let manager = SaveBackupManager::with_dirs(save_dir, td.path().join("b"), 2);
+        let first = manager.snapshot(SaveBackupReason::Manual).unwrap().unwrap();
+        manager.snapshot(SaveBackupReason::Manual).unwrap();
+        manager.snapshot(SaveBackupReason::Manual).unwrap();
+
+        let ids: Vec<_> = manager.list().unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(ids.len(), 2);
+        assert!(!ids.contains(&first.id));
+    }
+
+    #[test]
+    fn restoring_oldest_snapshot_with_full_retention() {
+        let td = tempdir().unwrap();
+        let save_dir = td.path().join("Balatro");
+        write(&save_dir.join("1/save.jkr"), "oldest");
+
+        let manager = SaveBackupManager::with_dirs(save_dir.clone(), td.path().join("b"), 2);
+        let oldest = manager.snapshot(SaveBackupReason::Manual).unwrap().unwrap();
+        write(&save_dir.join("1/save.jkr"), "newer");
+        manager.snapshot(SaveBackupReason::Manual).unwrap();
+        write(&save_dir.join("1/save.jkr"), "current");
+
+        manager.restore(&oldest.id).unwrap();
+        assert_eq!(
+            fs::read_to_string(save_dir.join("1/save.jkr")).unwrap(),
+            "oldest"
+        );
+        // The pre-restore snapshot is kept and retention still holds
+        let snapshots = manager.list().unwrap();
+        assert_eq!(snapshots.len(), 2);
+        assert_eq!(snapshots[0].reason, SaveBackupReason::PreRestore);
+    }
+
+    #[test]
+    fn damaged_archive_leaves_saves_alone() {
+        let td = tempdir().unwrap();
+        let save_dir = td.path().join("Balatro");
+        write(&save_dir.join("1/save.jkr"), "kept");
+
+        let manager = SaveBackupManager::with_dirs(save_dir.clone(), td.path().join("b"), 5);
+        let snapshot = manager.snapshot(SaveBackupReason::Manual).unwrap().unwrap();
+        fs::write(manager.archive_path(&snapshot.id), b"not a tarball").unwrap();
+        write(&save_dir.join("1/save.jkr"), "live");
+
+        assert!(manager.restore(&snapshot.id).is_err());
+        assert_eq!(
+            fs::read_to_string(save_dir.join("1/save.jkr")).unwrap(),
+            "live"
+        );
+        assert_eq!(manager.list().unwrap().len(), 1);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn failed_snapshot_leaves_no_archive_behind() {
+        let td = tempdir().unwrap();
+        let save_dir = td.path().join("Balatro");
+        write(&save_dir.join("1/save.jkr"), "run");
+        // A save file that can't be read fails the snapshot halfway
+        std::os::unix::fs::symlink(td.path().join("missing"), save_dir.join("settings.jkr"))
+            .unwrap();
+
+        let backup_dir = td.path().join("b");
+        let manager = SaveBackupManager::with_dirs(save_dir, backup_dir.clone(), 5);
+        assert!(manager.snapshot(SaveBackupReason::Manual).is_err());
+        assert_eq!(fs::read_dir(&backup_dir).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn rejects_path_like_ids() {
+        let td = tempdir().unwrap();
+        let manager = SaveBackupManager::with_dirs(td.path().join("s"), td.path().join("b"), 2);
+        assert!(manager.delete("../../etc").is_err());
+    }
+}
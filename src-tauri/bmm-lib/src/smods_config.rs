@@ -0,0 +1,566 @@
+use crate::errors::AppError;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+/// A Lua value as found in a Steamodded config table.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum LuaValue {
+    Nil,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Table(Vec<TableEntry>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum LuaKey {
+    Int(i64),
+    String(String),
+}
+
+/// Table entries keep their file order so a round trip doesn't reshuffle the config.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TableEntry {
+    pub key: LuaKey,
+    pub value: LuaValue,
+}
+
+impl LuaValue {
+    /// Look up a string key in a table value.
+    pub fn get(&self, key: &str) -> Option<&LuaValue> {
+        match self {
+            LuaValue::Table(entries) => entries
+                .iter()
+                .find(|e| matches!(&e.key, LuaKey::String(k) if k == key))
+                .map(|e| &e.value),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SmodsConfigFile {
+    pub mod_id: String,
+    pub path: PathBuf,
+    pub size: u64,
+    pub modified: u64,
+}
+
+/// Steamodded keeps each mod's saved options in `<save dir>/config/<mod id>.jkr`.
+pub fn config_dir() -> Result<PathBuf, AppError> {
+    Ok(crate::paths::save_dir()?.join("config"))
+}
+
+pub fn config_path(config_dir: &Path, mod_id: &str) -> Result<PathBuf, AppError> {
+    if mod_id.is_empty() || mod_id.contains(['/', '\\', ':']) || mod_id == "." || mod_id == ".." {
+        return Err(AppError::invalid_path(mod_id, "Invalid mod id"));
+    }
+    Ok(config_dir.join(format!("{mod_id}.jkr")))
+}
+
+pub fn list_configs(config_dir: &Path) -> Result<Vec<SmodsConfigFile>, AppError> {
+    if !config_dir.exists() {
+        return Ok(Vec::new());
+    }
+    let entries = fs::read_dir(config_dir).map_err(|e| AppError::FileRead {
+        path: config_dir.to_path_buf(),
+        source: e.to_string(),
+    })?;
+
+    let mut configs = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("jkr") {
+            continue;
+        }
+        let Some(mod_id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        let meta = entry.metadata().ok();
+        configs.push(SmodsConfigFile {
+            mod_id: mod_id.to_string(),
+            size: meta.as_ref().map(|m| m.len()).unwrap_or(0),
+            modified: meta
+                .and_then(|m| m.modified().ok())
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            path,
+        });
+    }
+    configs.sort_by_key(|c| c.mod_id.to_lowercase());
+    Ok(configs)
+}
+
+/// Read a mod's saved config, falling back to the defaults in the mod's own
+/// `config.lua` when the game hasn't written one yet.
+pub fn read_config(
+    config_dir: &Path,
+    mod_id: &str,
+    mod_dir: Option<&Path>,
+) -> Result<LuaValue, AppError> {
+    let path = config_path(config_dir, mod_id)?;
+    if path.exists() {
+        return read_lua_file(&path);
+    }
+    if let Some(defaults) = mod_dir.map(|d| d.join("config.lua")).filter(|p| p.exists()) {
+        return read_lua_file(&defaults);
+    }
+    Err(AppError::FileNotFound {
+        path,
+        source: format!("No saved config for {mod_id}"),
+    })
+}
+
+pub fn read_lua_file(path: &Path) -> Result<LuaValue, AppError> {
+    let content = fs::read_to_string(path).map_err(|e| AppError::FileRead {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    parse_config(&content).map_err(|e| AppError::FileRead {
+        path: path.to_path_buf(),
+        source: format!("Invalid config table: {e}"),
+    })
+}
+
+/// Write an edited config back. The previous file is kept as `<mod id>.jkr.bak`
+/// and the new one is written to a temp file and renamed into place.
+pub fn write_config(
+    config_dir: &Path,
+    mod_id: &str,
+    value: &LuaValue,
+) -> Result<PathBuf, AppError> {
+    if !matches!(value, LuaValue::Table(_)) {
+        return Err(AppError::InvalidState(
+            "A mod config must be a table".to_string(),
+        ));
+    }
+    let text = serialize_config(value)?;
+    // Never write something the game won't be able to load back
+    if parse_config(&text).as_ref() != Ok(value) {
+        return Err(AppError::InvalidState(format!(
+            "Config for {mod_id} doesn't round-trip; refusing to write it"
+        )));
+    }
+
+    let path = config_path(config_dir, mod_id)?;
+    fs::create_dir_all(config_dir).map_err(|e| AppError::DirCreate {
+        path: config_dir.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    if path.exists() {
+        let backup = path.with_extension("jkr.bak");
+        fs::copy(&path, &backup).map_err(|e| AppError::FileCopy {
+            source: path.display().to_string(),
+            dest: backup.display().to_string(),
+            source_error: e.to_string(),
+        })?;
+    }
+
+    let tmp = path.with_extension("jkr.tmp");
+    fs::write(&tmp, &text).map_err(|e| AppError::FileWrite {
+        path: tmp.clone(),
+        source: e.to_string(),
+    })?;
+    fs::rename(&tmp, &path).map_err(|e| AppError::FileWrite {
+        path: path.clone(),
+        source: e.to_string(),
+    })?;
+    log::info!("Wrote Steamodded config for {mod_id} to {}", path.display());
+    Ok(path)
+}
+
+/// Parse a config file of the form `return { ... }` (a bare table is accepted too).
+pub fn parse_config(source: &str) -> Result<LuaValue, String> {
+    let mut parser = Parser {
+        src: source.as_bytes(),
+        pos: 0,
+    };
+    parser.skip_trivia();
+    if parser.src[parser.pos..].starts_with(b"return") {
+        parser.pos += "return".len();
+    }
+    let value = parser.value()?;
+    parser.skip_trivia();
+    if parser.src.get(parser.pos) == Some(&b';') {
+        parser.pos += 1;
+        parser.skip_trivia();
+    }
+    if parser.pos < parser.src.len() {
+        return Err(format!("unexpected trailing input at byte {}", parser.pos));
+    }
+    Ok(value)
+}
+
+pub fn serialize_config(value: &LuaValue) -> Result<String, AppError> {
+    let mut out = String::from("return ");
+    write_value(value, 0, &mut out)?;
+    out.push('\n');
+    Ok(out)
+}
+
+fn write_value(value: &LuaValue, depth: usize, out: &mut String) -> Result<(), AppError> {
+    match value {
+        LuaValue::Nil => out.push_str("nil"),
+        LuaValue::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        LuaValue::Number(n) => {
+            if !n.is_finite() {
+                return Err(AppError::InvalidState(format!(
+                    "Cannot store {n} in a mod config"
+                )));
+            }
+            if n.fract() == 0.0 && n.abs() < 1e15 {
+                out.push_str(&format!("{}", *n as i64));
+            } else {
+                out.push_str(&format!("{n:?}"));
+            }
+        }
+        LuaValue::String(s) => write_string(s, out),
+        LuaValue::Table(entries) if entries.is_empty() => out.push_str("{}"),
+        LuaValue::Table(entries) => {
+            out.push_str("{\n");
+            for entry in entries {
+                out.push_str(&"\t".repeat(depth + 1));
+                match &entry.key {
+                    LuaKey::Int(i) => out.push_str(&format!("[{i}]")),
+                    LuaKey::String(k) => {
+                        out.push('[');
+                        write_string(k, out);
+                        out.push(']');
+                    }
+                }
+                out.push_str(" = ");
+                write_value(&entry.value, depth + 1, out)?;
+                out.push_str(",\n");
+            }
+            out.push_str(&"\t".repeat(depth));
+            out.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\0' => out.push_str("\\0"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+struct Parser<'a> {
+    src: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.src.get(self.pos).copied()
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), String> {
+        self.skip_trivia();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(format!("expected '{}' at byte {}", byte as char, self.pos))
+        }
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            while self.peek().is_some_and(|c| c.is_ascii_whitespace()) {
+                self.pos += 1;
+            }
+            if !self.src[self.pos..].starts_with(b"--") {
+                return;
+            }
+            self.pos += 2;
+            if let Some(level) = self.long_bracket_level() {
+                let _ = self.long_string(level);
+            } else {
+                while self.peek().is_some_and(|c| c != b'\n') {
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+
+    fn value(&mut self) -> Result<LuaValue, String> {
+        self.skip_trivia();
+        match self.peek() {
+            Some(b'{') => self.table(),
+            Some(b'"') | Some(b'\'') => self.quoted_string().map(LuaValue::String),
+            Some(b'[') if self.long_bracket_level().is_some() => {
+                let level = self.long_bracket_level().unwrap_or(0);
+                self.long_string(level).map(LuaValue::String)
+            }
+            Some(c) if c == b'-' || c == b'.' || c.is_ascii_digit() => {
+                self.number().map(LuaValue::Number)
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == b'_' => match self.identifier().as_str() {
+                "true" => Ok(LuaValue::Bool(true)),
+                "false" => Ok(LuaValue::Bool(false)),
+                "nil" => Ok(LuaValue::Nil),
+                other => Err(format!("unsupported expression '{other}'")),
+            },
+            Some(c) => Err(format!("unexpected '{}' at byte {}", c as char, self.pos)),
+            None => Err("unexpected end of input".to_string()),
+        }
+    }
+
+    fn table(&mut self) -> Result<LuaValue, String> {
+        self.expect(b'{')?;
+        let mut entries = Vec::new();
+        let mut next_index = 1i64;
+        loop {
+            self.skip_trivia();
+            if self.peek() == Some(b'}') {
+                self.pos += 1;
+                return Ok(LuaValue::Table(entries));
+            }
+
+            let key = if self.peek() == Some(b'[') && self.long_bracket_level().is_none() {
+                self.pos += 1;
+                let key = match self.value()? {
+                    LuaValue::String(s) => LuaKey::String(s),
+                    LuaValue::Number(n) if n.fract() == 0.0 => LuaKey::Int(n as i64),
+                    other => return Err(format!("unsupported table key {other:?}")),
+                };
+                self.expect(b']')?;
+                self.expect(b'=')?;
+                Some(key)
+            } else if self
+                .peek()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == b'_')
+            {
+                let start = self.pos;
+                let ident = self.identifier();
+                self.skip_trivia();
+                if self.peek() == Some(b'=') && self.src.get(self.pos + 1) != Some(&b'=') {
+                    self.pos += 1;
+                    Some(LuaKey::String(ident))
+                } else {
+                    // A positional `true`/`false`/`nil`
+                    self.pos = start;
+                    None
+                }
+            } else {
+                None
+            };
+
+            let value = self.value()?;
+            let key = key.unwrap_or_else(|| {
+                let key = LuaKey::Int(next_index);
+                next_index += 1;
+                key
+            });
+            entries.push(TableEntry { key, value });
+
+            self.skip_trivia();
+            match self.peek() {
+                Some(b',') | Some(b';') => self.pos += 1,
+                Some(b'}') => {}
+                _ => return Err(format!("expected ',' or '}}' at byte {}", self.pos)),
+            }
+        }
+    }
+
+    fn identifier(&mut self) -> String {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'_')
+        {
+            self.pos += 1;
+        }
+        String::from_utf8_lossy(&self.src[start..self.pos]).into_owned()
+    }
+
+    fn number(&mut self) -> Result<f64, String> {
+        let start = self.pos;
+        let negative = self.peek() == Some(b'-');
+        if negative {
+            self.pos += 1;
+            self.skip_trivia();
+        }
+        let digits_start = self.pos;
+        let is_hex =
+            self.src[self.pos..].starts_with(b"0x") || self.src[self.pos..].starts_with(b"0X");
+        if is_hex {
+            self.pos += 2;
+        }
+        while self.peek().is_some_and(|c| {
+            c.is_ascii_hexdigit() || c == b'.' || (!is_hex && matches!(c, b'e' | b'E'))
+        }) {
+            let exponent = matches!(self.peek(), Some(b'e' | b'E')) && !is_hex;
+            self.pos += 1;
+            if exponent && matches!(self.peek(), Some(b'+' | b'-')) {
+                self.pos += 1;
+            }
+        }
+
+        let text = std::str::from_utf8(&self.src[digits_start..self.pos])
+            .map_err(|_| format!("invalid number at byte {start}"))?;
+        let value = if is_hex {
+            i64::from_str_radix(&text[2..], 16).map(|v| v as f64).ok()
+        } else {
+            text.parse::<f64>().ok()
+        }
+        .ok_or_else(|| format!("invalid number '{text}' at byte {start}"))?;
+        Ok(if negative { -value } else { value })
+    }
+
+    fn quoted_string(&mut self) -> Result<String, String> {
+        let quote = self.peek().unwrap_or(b'"');
+        self.pos += 1;
+        let mut bytes = Vec::new();
+        loop {
+            let Some(c) = self.peek() else {
+                return Err("unterminated string".to_string());
+            };
+            self.pos += 1;
+            if c == quote {
+                break;
+            }
+            if c != b'\\' {
+                bytes.push(c);
+                continue;
+            }
+            let Some(escaped) = self.peek() else {
+                return Err("unterminated string".to_string());
+            };
+            self.pos += 1;
+            match escaped {
+                b'n' => bytes.push(b'\n'),
+                b'r' => bytes.push(b'\r'),
+                b't' => bytes.push(b'\t'),
+                b'a' => bytes.push(0x07),
+                b'b' => bytes.push(0x08),
+                b'f' => bytes.push(0x0c),
+                b'v' => bytes.push(0x0b),
+                b'\n' => bytes.push(b'\n'),
+                b'0'..=b'9' => {
+                    let start = self.pos - 1;
+                    while self.pos - start < 3 && self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                        self.pos += 1;
+                    }
+                    let code: u32 = std::str::from_utf8(&self.src[start..self.pos])
+                        .ok()
+                        .and_then(|s| s.parse().ok())
+                        .filter(|c| *c <= 255)
+                        .ok_or_else(|| format!("invalid escape at byte {start}"))?;
+                    bytes.push(code as u8);
+                }
+                other => bytes.push(other),
+            }
+        }
+        String::from_utf8(bytes).map_err(|_| "string is not valid UTF-8".to_string())
+    }
+
+    // `[[`, `[=[`, ... : returns the number of `=` signs
+    fn long_bracket_level(&self) -> Option<usize> {
+        let rest = &self.src[self.pos..];
+        if rest.first() != Some(&b'[') {
+            return None;
+        }
+        let level = rest[1..].iter().take_while(|&&c| c == b'=').count();
+        (rest.get(level + 1) == Some(&b'[')).then_some(level)
+    }
+
+    fn long_string(&mut self, level: usize) -> Result<String, String> {
+        self.pos += level + 2;
+        // A newline right after the opening bracket is skipped, as in Lua
+        if self.peek() == Some(b'\n') {
+            self.pos += 1;
+        }
+        let close = format!("]{}]", "=".repeat(level));
+        let rest = &self.src[self.pos..];
+        let end = rest
+            .windows(close.len())
+            .position(|w| w == close.as_bytes())
+            .ok_or_else(|| "unterminated long string".to_string())?;
+        let text = String::from_utf8(rest[..end].to_vec())
+            .map_err(|_| "string is not valid UTF-8".to_string())?;
+        self.pos += end + close.len();
+        Ok(text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn parses_steamodded_config() {
+        let src = r#"return {["enabled"]=true,["jokers"]={["rarity"]=3,},["name"]="Jimbo \"J\"",[1]=0.5,}"#;
+        let value = parse_config(src).unwrap();
+        assert_eq!(value.get("enabled"), Some(&LuaValue::Bool(true)));
+        assert_eq!(
+            value.get("jokers").and_then(|j| j.get("rarity")),
+            Some(&LuaValue::Number(3.0))
+        );
+        assert_eq!(
+            value.get("name"),
+            Some(&LuaValue::String("Jimbo \"J\"".to_string()))
+        );
+    }
+
+    #[test]
+    fn parses_hand_written_config_lua() {
+        let src = "-- defaults\nreturn {\n  speed = -2, -- slower\n  tags = { 'a', \"b\" };\n  note = [[multi\nline]],\n  hex = 0x10,\n}\n";
+        let value = parse_config(src).unwrap();
+        assert_eq!(value.get("speed"), Some(&LuaValue::Number(-2.0)));
+        assert_eq!(value.get("hex"), Some(&LuaValue::Number(16.0)));
+        assert_eq!(
+            value.get("note"),
+            Some(&LuaValue::String("multi\nline".to_string()))
+        );
+        let LuaValue::Table(tags) = value.get("tags").unwrap() else {
+            panic!("tags should be a table");
+        };
+        assert_eq!(tags[1].key, LuaKey::Int(2));
+        assert!(parse_config("return { x = os.exit() }").is_err());
+    }
+
+    #[test]
+    fn write_round_trips_and_keeps_backup() -> Result<(), AppError> {
+        let td = tempdir().unwrap();
+        let dir = td.path().join("config");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Cool.jkr"), "return {[\"x\"]=1,}").unwrap();
+
+        let mut value = read_config(&dir, "Cool", None)?;
+        if let LuaValue::Table(entries) = &mut value {
+            entries[0].value = LuaValue::Number(2.5);
+            entries.push(TableEntry {
+                key: LuaKey::String("label".to_string()),
+                value: LuaValue::String("a\nb".to_string()),
+            });
+        }
+        write_config(&dir, "Cool", &value)?;
+
+        assert_eq!(read_config(&dir, "Cool", None)?, value);
+        assert_eq!(
+            fs::read_to_string(dir.join("Cool.jkr.bak")).unwrap(),
+            "return {[\"x\"]=1,}"
+        );
+        assert_eq!(list_configs(&dir)?.len(), 1);
+        assert!(config_path(&dir, "../evil").is_err());
+        Ok(())
+    }
+}
@@ -1,5 +1,4 @@
 use anyhow::{anyhow, Context, Result};
-use dirs;
 use log::info;
 use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
 use serde::{Deserialize, Serialize};
@@ -99,10 +98,7 @@ impl ModInstaller {
 
     fn get_installation_path(&self) -> Result<PathBuf> {
         // Construct the mods path
-        let mod_path = dirs::config_dir()
-            .context("Failed to get config directory")?
-            .join("Balatro")
-            .join("Mods");
+        let mod_path = crate::paths::mods_dir()?;
 
         // dbg!(&mod_path);
 
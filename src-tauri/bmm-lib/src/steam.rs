@@ -0,0 +1,481 @@
+use crate::errors::AppError;
+use crate::lovely::BALATRO_STEAM_APP_ID;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A node of Valve's KeyValues text format (`.vdf`, `.acf`).
+#[derive(Debug, Clone, PartialEq)]
+pub enum VdfValue {
+    String(String),
+    Object(Vec<(String, VdfValue)>),
+}
+
+impl VdfValue {
+    /// Child by key; keys are case-insensitive in KeyValues.
+    pub fn get(&self, key: &str) -> Option<&VdfValue> {
+        match self {
+            VdfValue::Object(entries) => entries
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v),
+            VdfValue::String(_) => None,
+        }
+    }
+
+    pub fn get_str(&self, key: &str) -> Option<&str> {
+        self.get(key).and_then(VdfValue::as_str)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            VdfValue::String(s) => Some(s),
+            VdfValue::Object(_) => None,
+        }
+    }
+
+    pub fn entries(&self) -> &[(String, VdfValue)] {
+        match self {
+            VdfValue::Object(entries) => entries,
+            VdfValue::String(_) => &[],
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SteamBuild {
+    /// The build for the host OS (Windows build on Windows, the macOS app on macOS).
+    Native,
+    /// The Windows build running under Proton.
+    Proton,
+}
+
+/// Balatro as installed in one Steam library.
+#[derive(Serialize, Debug, Clone)]
+pub struct SteamInstall {
+    pub library: PathBuf,
+    pub install_dir: PathBuf,
+    pub build_id: Option<String>,
+    pub build: SteamBuild,
+}
+
+/// Parse KeyValues text into an object holding its top-level keys.
+pub fn parse_vdf(text: &str) -> Result<VdfValue, AppError> {
+    let mut tokens = Tokenizer {
+        chars: text.chars().peekable(),
+    };
+    let root = parse_object(&mut tokens, false)?;
+    Ok(VdfValue::Object(root))
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Str(String),
+    Open,
+    Close,
+}
+
+struct Tokenizer<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+}
+
+impl Tokenizer<'_> {
+    fn next_token(&mut self) -> Result<Option<Token>, AppError> {
+        loop {
+            match self.chars.peek() {
+                None => return Ok(None),
+                Some(c) if c.is_whitespace() => {
+                    self.chars.next();
+                }
+                Some('/') => {
+                    // `//` comment to end of line
+                    self.chars.next();
+                    if self.chars.peek() == Some(&'/') {
+                        for c in self.chars.by_ref() {
+                            if c == '\n' {
+                                break;
+                            }
+                        }
+                    } else {
+                        return Ok(Some(Token::Str(self.bare("/"))));
+                    }
+                }
+                Some('[') => {
+                    // Platform conditionals like `[$WIN32]` carry no data we use
+                    for c in self.chars.by_ref() {
+                        if c == ']' {
+                            break;
+                        }
+                    }
+                }
+                Some('{') => {
+                    self.chars.next();
+                    return Ok(Some(Token::Open));
+                }
+                Some('}') => {
+                    self.chars.next();
+                    return Ok(Some(Token::Close));
+                }
+                Some('"') => {
+                    self.chars.next();
+                    return self.quoted().map(|s| Some(Token::Str(s)));
+                }
+                Some(_) => return Ok(Some(Token::Str(self.bare("")))),
+            }
+        }
+    }
+
+    fn quoted(&mut self) -> Result<String, AppError> {
+        let mut out = String::new();
+        while let Some(c) = self.chars.next() {
+            match c {
+                '"' => return Ok(out),
+                '\\' => match self.chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('t') => out.push('\t'),
+                    Some(other) => out.push(other),
+                    None => break,
+                },
+                c => out.push(c),
+            }
+        }
+        Err(AppError::InvalidState(
+            "Unterminated string in VDF".to_string(),
+        ))
+    }
+
+    fn bare(&mut self, prefix: &str) -> String {
+        let mut out = prefix.to_string();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_whitespace() || c == '{' || c == '}' || c == '"' {
+                break;
+            }
+            out.push(c);
+            self.chars.next();
+        }
+        out
+    }
+}
+
+fn parse_object(
+    tokens: &mut Tokenizer<'_>,
+    nested: bool,
+) -> Result<Vec<(String, VdfValue)>, AppError> {
+    let mut entries = Vec::new();
+    loop {
+        let key = match tokens.next_token()? {
+            Some(Token::Str(key)) => key,
+            Some(Token::Close) if nested => return Ok(entries),
+            None if !nested => return Ok(entries),
+            other => {
+                return Err(AppError::InvalidState(format!(
+                    "Unexpected {other:?} in VDF, expected a key"
+                )))
+            }
+        };
+        let value = match tokens.next_token()? {
+            Some(Token::Str(value)) => VdfValue::String(value),
+            Some(Token::Open) => VdfValue::Object(parse_object(tokens, true)?),
+            other => {
+                return Err(AppError::InvalidState(format!(
+                    "Unexpected {other:?} in VDF after key \"{key}\""
+                )))
+            }
+        };
+        entries.push((key, value));
+    }
+}
+
+fn read_vdf(path: &Path) -> Result<VdfValue, AppError> {
+    let text = fs::read_to_string(path).map_err(|e| AppError::FileRead {
+        path: path.to_path_buf(),
+        source: e.to_string(),
+    })?;
+    parse_vdf(&text)
+}
+
+/// Where Steam clients may live on this machine, including Flatpak and Snap installs.
+pub fn steam_roots() -> Vec<PathBuf> {
+    let mut roots = Vec::new();
+
+    #[cfg(target_os = "windows")]
+    {
+        use winreg::enums::HKEY_LOCAL_MACHINE;
+        use winreg::RegKey;
+
+        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+        if let Ok(key) = hklm.open_subkey("SOFTWARE\\WOW6432Node\\Valve\\Steam") {
+            if let Ok(path) = key.get_value::<String, _>("InstallPath") {
+                roots.push(PathBuf::from(path));
+            }
+        }
+        roots.push(PathBuf::from("C:\\Program Files (x86)\\Steam"));
+    }
+
+    #[cfg(target_os = "macos")]
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join("Library/Application Support/Steam"));
+    }
+
+    #[cfg(target_os = "linux")]
+    if let Some(home) = dirs::home_dir() {
+        roots.push(home.join(".local/share/Steam"));
+        roots.push(home.join(".steam/steam"));
+        roots.push(home.join(".steam/root"));
+        // Flatpak
+        roots.push(home.join(".var/app/com.valvesoftware.Steam/.local/share/Steam"));
+        roots.push(home.join(".var/app/com.valvesoftware.Steam/data/Steam"));
+        // Snap
+        roots.push(home.join("snap/steam/common/.local/share/Steam"));
+    }
+
+    roots.retain(|r| r.join("steamapps").is_dir());
+    dedup_existing(roots)
+}
+
+/// Every library folder of a Steam client, the client's own folder first.
+pub fn library_folders(steam_root: &Path) -> Vec<PathBuf> {
+    let mut libraries = vec![steam_root.to_path_buf()];
+    let vdf_path = steam_root.join("steamapps").join("libraryfolders.vdf");
+    match read_vdf(&vdf_path) {
+        Ok(vdf) => libraries.extend(library_paths(&vdf)),
+        Err(e) => log::debug!("No library list at {}: {e}", vdf_path.display()),
+    }
+    dedup_existing(libraries)
+}
+
+fn library_paths(vdf: &VdfValue) -> Vec<PathBuf> {
+    let Some(folders) = vdf.get("libraryfolders") else {
+        return Vec::new();
+    };
+    folders
+        .entries()
+        .iter()
+        .filter(|(key, _)| key.chars().all(|c| c.is_ascii_digit()))
+        .filter_map(|(_, value)| match value {
+            // Current format: "0" { "path" "..." "apps" { ... } }
+            VdfValue::Object(_) => value.get_str("path").map(PathBuf::from),
+            // Older clients: "1" "/path/to/library"
+            VdfValue::String(path) => Some(PathBuf::from(path)),
+        })
+        .collect()
+}
+
+/// Balatro in one library, read from its `appmanifest_2379780.acf`.
+pub fn read_install(library: &Path) -> Option<SteamInstall> {
+    let manifest_path = library
+        .join("steamapps")
+        .join(format!("appmanifest_{BALATRO_STEAM_APP_ID}.acf"));
+    if !manifest_path.is_file() {
+        return None;
+    }
+    let manifest = match read_vdf(&manifest_path) {
+        Ok(vdf) => vdf,
+        Err(e) => {
+            log::warn!("Failed to parse {}: {e}", manifest_path.display());
+            return None;
+        }
+    };
+    let app = manifest.get("AppState")?;
+    let install_dir = library
+        .join("steamapps")
+        .join("common")
+        .join(app.get_str("installdir").unwrap_or("Balatro"));
+    if !install_dir.is_dir() {
+        return None;
+    }
+
+    let proton_override = app
+        .get("UserConfig")
+        .and_then(|c| c.get_str("platform_override_source"))
+        .is_some_and(|p| p.eq_ignore_ascii_case("windows"));
+    let build = if !cfg!(target_os = "windows")
+        && (proton_override
+            || (install_dir.join("Balatro.exe").is_file()
+                && !install_dir.join("Balatro.app").exists()))
+    {
+        SteamBuild::Proton
+    } else {
+        SteamBuild::Native
+    };
+
+    Some(SteamInstall {
+        library: library.to_path_buf(),
+        install_dir,
+        build_id: app.get_str("buildid").map(str::to_string),
+        build,
+    })
+}
+
+/// Balatro installs across every Steam client and library on this machine.
+pub fn find_steam_installs() -> Vec<SteamInstall> {
+    let mut seen = HashSet::new();
+    steam_roots()
+        .iter()
+        .flat_map(|root| library_folders(root))
+        .filter_map(|library| read_install(&library))
+        .filter(|install| {
+            let key = fs::canonicalize(&install.install_dir)
+                .unwrap_or_else(|_| install.install_dir.clone());
+            seen.insert(key)
+        })
+        .collect()
+}
+
+/// Balatro's launch options for every Steam account on this machine, from each
+/// account's `userdata/<id>/config/localconfig.vdf`.
+pub fn balatro_launch_options() -> Vec<String> {
+    let mut options = Vec::new();
+    for root in steam_roots() {
+        let Ok(accounts) = fs::read_dir(root.join("userdata")) else {
+            continue;
+        };
+        for account in accounts.flatten() {
+            let config = account.path().join("config").join("localconfig.vdf");
+            if !config.is_file() {
+                continue;
+            }
+            match read_vdf(&config) {
+                Ok(vdf) => options.extend(app_launch_options(&vdf).map(str::to_string)),
+                Err(e) => log::warn!("Failed to parse {}: {e}", config.display()),
+            }
+        }
+    }
+    options
+}
+
+fn app_launch_options(vdf: &VdfValue) -> Option<&str> {
+    vdf.get("UserLocalConfigStore")?
+        .get("Software")?
+        .get("Valve")?
+        .get("Steam")?
+        .get("apps")?
+        .get(BALATRO_STEAM_APP_ID)?
+        .get_str("LaunchOptions")
+}
+
+// `~/.steam/steam` is usually a symlink to `~/.local/share/Steam`, so compare real paths
+fn dedup_existing(paths: Vec<PathBuf>) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|p| p.is_dir())
+        .filter(|p| seen.insert(fs::canonicalize(p).unwrap_or_else(|_| p.clone())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_library_folders_in_both_formats() {
+        let current = r#"
+"libraryfolders"
+{
+	"0"
+	{
+		"path"		"/home/user/.local/share/Steam"
+		"label"		""
+		"apps"
+		{
+			"2379780"		"1048576"
+		}
+	}
+	"1"
+	{
+		"path"		"/mnt/games/SteamLibrary"
+	}
+}
+"#;
+        let vdf = parse_vdf(current).unwrap();
+        assert_eq!(
+            library_paths(&vdf),
+            vec![
+                PathBuf::from("/home/user/.local/share/Steam"),
+                PathBuf::from("/mnt/games/SteamLibrary")
+            ]
+        );
+
+        let legacy = "\"LibraryFolders\"\n{\n\t\"TimeNextStatsReport\"\t\"1\"\n\t\"1\"\t\"D:\\\\Games\\\\Steam\"\n}\n";
+        let vdf = parse_vdf(legacy).unwrap();
+        assert_eq!(library_paths(&vdf), vec![PathBuf::from("D:\\Games\\Steam")]);
+    }
+
+    #[test]
+    fn reads_appmanifest_from_secondary_library() {
+        let tmp = tempfile::tempdir().unwrap();
+        let library = tmp.path();
+        let game = library.join("steamapps/common/Balatro");
+        fs::create_dir_all(&game).unwrap();
+        fs::write(game.join("Balatro.exe"), b"").unwrap();
+        fs::write(
+            library.join("steamapps/appmanifest_2379780.acf"),
+            r#""AppState"
+{
+	"appid"		"2379780"
+	"name"		"Balatro"
+	"installdir"		"Balatro" // comment
+	"buildid"		"14588217"
+	"UserConfig"
+	{
+		"platform_override_dest"		"linux"
+		"platform_override_source"		"windows"
+	}
+}
+"#,
+        )
+        .unwrap();
+
+        let install = read_install(library).unwrap();
+        assert_eq!(install.install_dir, game);
+        assert_eq!(install.build_id.as_deref(), Some("14588217"));
+        if !cfg!(target_os = "windows") {
+            assert_eq!(install.build, SteamBuild::Proton);
+        }
+        assert!(read_install(&library.join("missing")).is_none());
+    }
+
+    #[test]
+    fn reads_launch_options_from_localconfig() {
+        let config = r#""UserLocalConfigStore"
+{
+	"Software"
+	{
+		"Valve"
+		{
+			"Steam"
+			{
+				"Apps"
+				{
+					"2379780"
+					{
+						"LaunchOptions"		"WINEDLLOVERRIDES=\"version=n,b\" %command%"
+					}
+					"620"
+					{
+						"LaunchOptions"		"-novid"
+					}
+				}
+			}
+		}
+	}
+}
+"#;
+        let vdf = parse_vdf(config).unwrap();
+        assert_eq!(
+            app_launch_options(&vdf),
+            Some("WINEDLLOVERRIDES=\"version=n,b\" %command%")
+        );
+        let vdf = parse_vdf("\"UserLocalConfigStore\" { }").unwrap();
+        assert_eq!(app_launch_options(&vdf), None);
+    }
+
+    #[test]
+    fn rejects_malformed_vdf() {
+        assert!(parse_vdf("\"a\" { \"b\" \"c\"").is_err());
+        assert!(parse_vdf("\"a\" \"unterminated").is_err());
+    }
+}
@@ -0,0 +1,517 @@
+use crate::balamod::Balatro;
+use crate::database::InstallPlatform;
+use crate::steam::{self, SteamBuild};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How a detected install has to be started for its store to be happy.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum LaunchMethod {
+    /// Through the Steam client (`launch_balatro`'s regular platform path).
+    Steam,
+    /// Run the game executable directly (DRM-free copies, itch, GOG Galaxy, Game Pass).
+    Direct { executable: PathBuf },
+    /// Through Heroic Games Launcher, which owns the Wine/Proton setup.
+    Heroic { runner: String, app_name: String },
+    /// Through Lutris, which owns the Wine prefix.
+    Lutris { slug: String },
+}
+
+impl LaunchMethod {
+    /// URI handing the launch to an external launcher, if the method needs one.
+    pub fn launcher_uri(&self) -> Option<String> {
+        match self {
+            LaunchMethod::Heroic { runner, app_name } => Some(format!(
+                "heroic://launch?appName={app_name}&runner={runner}"
+            )),
+            LaunchMethod::Lutris { slug } => Some(format!("lutris:rungame/{slug}")),
+            LaunchMethod::Steam | LaunchMethod::Direct { .. } => None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DetectedInstall {
+    pub store: InstallPlatform,
+    pub label: String,
+    pub path: PathBuf,
+    pub launch: LaunchMethod,
+}
+
+/// One place Balatro can come from.
+pub trait StoreDetector {
+    fn name(&self) -> &'static str;
+    fn detect(&self) -> Vec<DetectedInstall>;
+}
+
+pub fn detectors() -> Vec<Box<dyn StoreDetector>> {
+    vec![
+        Box::new(SteamDetector),
+        Box::new(HeroicDetector::default()),
+        Box::new(LutrisDetector::default()),
+        Box::new(GogGalaxyDetector),
+        Box::new(GamePassDetector),
+        Box::new(ItchDetector::default()),
+    ]
+}
+
+/// Every Balatro install any detector knows about, without duplicate paths.
+pub fn detect_all() -> Vec<DetectedInstall> {
+    let mut seen = HashSet::new();
+    detectors()
+        .iter()
+        .flat_map(|d| {
+            let found = d.detect();
+            log::debug!("{} detector found {} installs", d.name(), found.len());
+            found
+        })
+        .filter(|i| seen.insert(fs::canonicalize(&i.path).unwrap_or_else(|_| i.path.clone())))
+        .collect()
+}
+
+/// The launch method for an install path, from whichever store claims it.
+pub fn launch_method_for(path: &Path) -> Option<LaunchMethod> {
+    let target = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    detect_all()
+        .into_iter()
+        .find(|i| fs::canonicalize(&i.path).unwrap_or_else(|_| i.path.clone()) == target)
+        .map(|i| i.launch)
+}
+
+/// Like `launch_method_for`, but an install no store claims is started the way its
+/// platform suggests: DRM-free copies directly, anything else through Steam.
+pub fn resolve_launch_method(path: &Path, platform: InstallPlatform) -> LaunchMethod {
+    launch_method_for(path).unwrap_or_else(|| match platform {
+        InstallPlatform::DrmFree
+        | InstallPlatform::Gog
+        | InstallPlatform::GamePass
+        | InstallPlatform::Itch => LaunchMethod::Direct {
+            executable: path.join("Balatro.exe"),
+        },
+        InstallPlatform::Steam | InstallPlatform::SteamBeta | InstallPlatform::Other => {
+            LaunchMethod::Steam
+        }
+    })
+}
+
+fn is_balatro_dir(path: &Path) -> bool {
+    Balatro::from_custom_path(path.to_path_buf()).is_some()
+}
+
+fn read_json(path: &Path) -> Option<Value> {
+    let data = fs::read_to_string(path).ok()?;
+    match serde_json::from_str(&data) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            log::debug!("Failed to parse {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+pub struct SteamDetector;
+
+impl StoreDetector for SteamDetector {
+    fn name(&self) -> &'static str {
+        "Steam"
+    }
+
+    fn detect(&self) -> Vec<DetectedInstall> {
+        steam::find_steam_installs()
+            .into_iter()
+            .map(|install| DetectedInstall {
+                store: InstallPlatform::Steam,
+                label: match install.build {
+                    SteamBuild::Native => "Steam".to_string(),
+                    SteamBuild::Proton => "Steam (Proton)".to_string(),
+                },
+                path: install.install_dir,
+                launch: LaunchMethod::Steam,
+            })
+            .collect()
+    }
+}
+
+/// Heroic's GOG and sideloaded games plus Legendary (Epic), which Heroic drives.
+pub struct HeroicDetector {
+    config_dirs: Vec<PathBuf>,
+    legendary_dirs: Vec<PathBuf>,
+}
+
+impl Default for HeroicDetector {
+    fn default() -> Self {
+        let mut config_dirs = Vec::new();
+        let mut legendary_dirs = Vec::new();
+        if let Some(config) = dirs::config_dir() {
+            config_dirs.push(config.join("heroic"));
+            legendary_dirs.push(config.join("legendary"));
+        }
+        if let Some(home) = dirs::home_dir() {
+            let flatpak = home.join(".var/app/com.heroicgameslauncher.hgl/config");
+            config_dirs.push(flatpak.join("heroic"));
+            legendary_dirs.push(flatpak.join("legendary"));
+            legendary_dirs.push(home.join(".config/legendary"));
+        }
+        Self {
+            config_dirs,
+            legendary_dirs,
+        }
+    }
+}
+
+impl HeroicDetector {
+    fn gog(&self, config: &Path) -> Vec<DetectedInstall> {
+        let Some(json) = read_json(&config.join("gog_store").join("installed.json")) else {
+            return Vec::new();
+        };
+        json["installed"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|game| {
+                let path = PathBuf::from(game["install_path"].as_str()?);
+                let app_name = game["appName"].as_str()?;
+                is_balatro_dir(&path).then(|| DetectedInstall {
+                    store: InstallPlatform::Gog,
+                    label: "GOG (Heroic)".to_string(),
+                    path,
+                    launch: LaunchMethod::Heroic {
+                        runner: "gog".to_string(),
+                        app_name: app_name.to_string(),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    fn sideload(&self, config: &Path) -> Vec<DetectedInstall> {
+        let Some(json) = read_json(&config.join("sideload_apps").join("library.json")) else {
+            return Vec::new();
+        };
+        json["games"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|game| {
+                game["title"]
+                    .as_str()
+                    .is_some_and(|t| t.to_lowercase().contains("balatro"))
+            })
+            .filter_map(|game| {
+                let executable = PathBuf::from(game["install"]["executable"].as_str()?);
+                let path = executable.parent()?.to_path_buf();
+                is_balatro_dir(&path).then(|| DetectedInstall {
+                    store: InstallPlatform::DrmFree,
+                    label: "Heroic".to_string(),
+                    path,
+                    launch: LaunchMethod::Heroic {
+                        runner: "sideload".to_string(),
+                        app_name: game["app_name"].as_str().unwrap_or_default().to_string(),
+                    },
+                })
+            })
+            .collect()
+    }
+
+    fn legendary(&self, dir: &Path) -> Vec<DetectedInstall> {
+        let Some(Value::Object(games)) = read_json(&dir.join("installed.json")) else {
+            return Vec::new();
+        };
+        games
+            .values()
+            .filter_map(|game| {
+                let path = PathBuf::from(game["install_path"].as_str()?);
+                let app_name = game["app_name"].as_str()?;
+                is_balatro_dir(&path).then(|| DetectedInstall {
+                    store: InstallPlatform::Other,
+                    label: "Epic (Heroic)".to_string(),
+                    path,
+                    launch: LaunchMethod::Heroic {
+                        runner: "legendary".to_string(),
+                        app_name: app_name.to_string(),
+                    },
+                })
+            })
+            .collect()
+    }
+}
+
+impl StoreDetector for HeroicDetector {
+    fn name(&self) -> &'static str {
+        "Heroic"
+    }
+
+    fn detect(&self) -> Vec<DetectedInstall> {
+        let mut found = Vec::new();
+        for config in &self.config_dirs {
+            found.extend(self.gog(config));
+            found.extend(self.sideload(config));
+        }
+        for dir in &self.legendary_dirs {
+            found.extend(self.legendary(dir));
+        }
+        found
+    }
+}
+
+/// Lutris keeps one YAML file per game; only `game.exe` is needed from it.
+pub struct LutrisDetector {
+    games_dirs: Vec<PathBuf>,
+}
+
+impl Default for LutrisDetector {
+    fn default() -> Self {
+        let mut games_dirs = Vec::new();
+        if let Some(data) = dirs::data_dir() {
+            games_dirs.push(data.join("lutris").join("games"));
+        }
+        if let Some(config) = dirs::config_dir() {
+            games_dirs.push(config.join("lutris").join("games"));
+        }
+        if let Some(home) = dirs::home_dir() {
+            games_dirs.push(home.join(".var/app/net.lutris.Lutris/data/lutris/games"));
+        }
+        Self { games_dirs }
+    }
+}
+
+impl LutrisDetector {
+    // `exe:` under the top-level `game:` section; avoids pulling in a YAML parser
+    fn game_exe(yaml: &str) -> Option<PathBuf> {
+        let mut in_game = false;
+        for line in yaml.lines() {
+            if !line.starts_with(' ') && !line.starts_with('\t') {
+                in_game = line.trim_end() == "game:";
+                continue;
+            }
+            if in_game {
+                if let Some(exe) = line.trim().strip_prefix("exe:") {
+                    let exe = exe.trim().trim_matches(|c| c == '"' || c == '\'');
+                    return (!exe.is_empty()).then(|| PathBuf::from(exe));
+                }
+            }
+        }
+        None
+    }
+}
+
+impl StoreDetector for LutrisDetector {
+    fn name(&self) -> &'static str {
+        "Lutris"
+    }
+
+    fn detect(&self) -> Vec<DetectedInstall> {
+        let mut found = Vec::new();
+        for dir in &self.games_dirs {
+            let Ok(entries) = fs::read_dir(dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|e| e.path()) {
+                if path.extension().is_none_or(|ext| ext != "yml") {
+                    continue;
+                }
+                let Some(exe) = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|yaml| Self::game_exe(&yaml))
+                else {
+                    continue;
+                };
+                let Some(game_dir) = exe.parent().filter(|d| is_balatro_dir(d)) else {
+                    continue;
+                };
+                // Config files are named `<slug>-<timestamp>.yml`
+                let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+                let slug = match stem.rsplit_once('-') {
+                    Some((slug, ts)) if ts.chars().all(|c| c.is_ascii_digit()) => slug,
+                    _ => &stem,
+                };
+                found.push(DetectedInstall {
+                    store: InstallPlatform::Other,
+                    label: "Lutris".to_string(),
+                    path: game_dir.to_path_buf(),
+                    launch: LaunchMethod::Lutris {
+                        slug: slug.to_string(),
+                    },
+                });
+            }
+        }
+        found
+    }
+}
+
+/// GOG Galaxy registers its games under `HKLM\SOFTWARE\WOW6432Node\GOG.com\Games`.
+pub struct GogGalaxyDetector;
+
+impl StoreDetector for GogGalaxyDetector {
+    fn name(&self) -> &'static str {
+        "GOG Galaxy"
+    }
+
+    #[cfg(target_os = "windows")]
+    fn detect(&self) -> Vec<DetectedInstall> {
+        use winreg::enums::HKEY_LOCAL_MACHINE;
+        use winreg::RegKey;
+
+        let hklm = RegKey::predef(HKEY_LOCAL_MACHINE);
+        let Ok(games) = hklm.open_subkey("SOFTWARE\\WOW6432Node\\GOG.com\\Games") else {
+            return Vec::new();
+        };
+        games
+            .enum_keys()
+            .flatten()
+            .filter_map(|id| games.open_subkey(&id).ok())
+            .filter(|game| {
+                game.get_value::<String, _>("gameName")
+                    .is_ok_and(|n| n.to_lowercase().contains("balatro"))
+            })
+            .filter_map(|game| {
+                let path = PathBuf::from(game.get_value::<String, _>("path").ok()?);
+                is_balatro_dir(&path).then(|| DetectedInstall {
+                    store: InstallPlatform::Gog,
+                    label: "GOG Galaxy".to_string(),
+                    launch: LaunchMethod::Direct {
+                        executable: path.join("Balatro.exe"),
+                    },
+                    path,
+                })
+            })
+            .collect()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn detect(&self) -> Vec<DetectedInstall> {
+        Vec::new()
+    }
+}
+
+/// Game Pass / Microsoft Store installs land in `<drive>:\XboxGames\Balatro\Content`.
+pub struct GamePassDetector;
+
+impl StoreDetector for GamePassDetector {
+    fn name(&self) -> &'static str {
+        "Game Pass"
+    }
+
+    #[cfg(target_os = "windows")]
+    fn detect(&self) -> Vec<DetectedInstall> {
+        ('C'..='Z')
+            .map(|drive| PathBuf::from(format!("{drive}:\\XboxGames\\Balatro\\Content")))
+            .filter(|path| is_balatro_dir(path))
+            .map(|path| DetectedInstall {
+                store: InstallPlatform::GamePass,
+                label: "Game Pass".to_string(),
+                launch: LaunchMethod::Direct {
+                    executable: path.join("Balatro.exe"),
+                },
+                path,
+            })
+            .collect()
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    fn detect(&self) -> Vec<DetectedInstall> {
+        Vec::new()
+    }
+}
+
+/// The itch app installs into `<itch config>/apps/<game>`.
+pub struct ItchDetector {
+    apps_dirs: Vec<PathBuf>,
+}
+
+impl Default for ItchDetector {
+    fn default() -> Self {
+        let apps_dirs = dirs::config_dir()
+            .map(|config| vec![config.join("itch").join("apps")])
+            .unwrap_or_default();
+        Self { apps_dirs }
+    }
+}
+
+impl StoreDetector for ItchDetector {
+    fn name(&self) -> &'static str {
+        "itch"
+    }
+
+    fn detect(&self) -> Vec<DetectedInstall> {
+        self.apps_dirs
+            .iter()
+            .filter_map(|dir| fs::read_dir(dir).ok())
+            .flat_map(|entries| entries.flatten().map(|e| e.path()))
+            .filter(|path| {
+                path.file_name()
+                    .is_some_and(|n| n.to_string_lossy().to_lowercase().contains("balatro"))
+                    && is_balatro_dir(path)
+            })
+            .map(|path| DetectedInstall {
+                store: InstallPlatform::Itch,
+                label: "itch".to_string(),
+                launch: LaunchMethod::Direct {
+                    executable: path.join("Balatro.exe"),
+                },
+                path,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_exe_from_lutris_game_config() {
+        let yaml = "game:\n  exe: /home/u/Games/balatro/drive_c/Balatro/Balatro.exe\n  prefix: /home/u/Games/balatro\nsystem: {}\nwine:\n  exe: /should/not/match\n";
+        assert_eq!(
+            LutrisDetector::game_exe(yaml),
+            Some(PathBuf::from(
+                "/home/u/Games/balatro/drive_c/Balatro/Balatro.exe"
+            ))
+        );
+        assert_eq!(LutrisDetector::game_exe("wine:\n  exe: /x\n"), None);
+    }
+
+    // Windows validates installs by their LÖVE DLLs rather than the executable
+    #[cfg(not(target_os = "windows"))]
+    #[test]
+    fn heroic_gog_install_launches_through_heroic() {
+        let tmp = tempfile::tempdir().unwrap();
+        let game = tmp.path().join("Games").join("Balatro");
+        fs::create_dir_all(&game).unwrap();
+        let config = tmp.path().join("heroic");
+        fs::create_dir_all(config.join("gog_store")).unwrap();
+        fs::write(
+            config.join("gog_store").join("installed.json"),
+            serde_json::json!({
+                "installed": [
+                    {"appName": "1234", "install_path": game, "platform": "windows"},
+                    {"appName": "999", "install_path": tmp.path().join("Other"), "platform": "windows"}
+                ]
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let detector = HeroicDetector {
+            config_dirs: vec![config],
+            legendary_dirs: Vec::new(),
+        };
+        // Only directories that hold the game count
+        assert!(detector.detect().is_empty());
+
+        let marker = Balatro { path: game.clone() }.get_exe_path();
+        fs::create_dir_all(marker.parent().unwrap()).unwrap();
+        fs::write(&marker, b"").unwrap();
+        let found = detector.detect();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].path, game);
+        assert_eq!(
+            found[0].launch.launcher_uri().as_deref(),
+            Some("heroic://launch?appName=1234&runner=gog")
+        );
+    }
+}
@@ -0,0 +1,490 @@
+//! Packages from the Balatro community on Thunderstore.
+
+use crate::catalog::{CatalogEntry, ModMeta};
+use crate::database::{Database, InstalledMod, ThunderstoreOrigin};
+use crate::errors::AppError;
+use crate::installer;
+use crate::mod_sources::ModSource;
+use crate::save_backup::{self, SaveBackupReason};
+use futures::future::BoxFuture;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+pub const DEFAULT_COMMUNITY_URL: &str = "https://thunderstore.io/c/balatro";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Package {
+    pub name: String,
+    /// `Namespace-Name`
+    pub full_name: String,
+    pub owner: String,
+    #[serde(default)]
+    pub package_url: String,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub is_deprecated: bool,
+    /// Newest first.
+    pub versions: Vec<PackageVersion>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PackageVersion {
+    pub version_number: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub icon: String,
+    /// `Namespace-Name-1.2.3` strings.
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+    pub download_url: String,
+    #[serde(default)]
+    pub website_url: String,
+}
+
+impl Package {
+    pub fn latest(&self) -> Option<&PackageVersion> {
+        self.versions.first()
+    }
+
+    /// Name the package is tracked under once installed.
+    pub fn install_name(&self, names: &InstallNames) -> String {
+        names.of(&self.owner, &self.name)
+    }
+}
+
+/// A dependency string, `Namespace-Name-1.2.3`. Thunderstore doesn't allow dashes in
+/// namespaces or names, so the first two dashes split it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DependencyRef {
+    pub namespace: String,
+    pub name: String,
+    pub version: Option<String>,
+}
+
+impl DependencyRef {
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.trim().splitn(3, '-');
+        let namespace = parts.next().filter(|p| !p.is_empty())?;
+        let name = parts.next().filter(|p| !p.is_empty())?;
+        Some(Self {
+            namespace: namespace.to_string(),
+            name: name.to_string(),
+            version: parts.next().map(str::to_string),
+        })
+    }
+
+    pub fn full_name(&self) -> String {
+        format!("{}-{}", self.namespace, self.name)
+    }
+
+    pub fn install_name(&self, names: &InstallNames) -> String {
+        names.of(&self.namespace, &self.name)
+    }
+}
+
+// Steamodded is published as `Steamodded-smods`; everything else installs under its
+// package name, which is also what the index and local detection call it. A name that
+// another namespace publishes too becomes `Namespace-Name`, so neither overwrites the other.
+fn install_name(packages: &[Package], namespace: &str, name: &str) -> String {
+    if namespace.eq_ignore_ascii_case("steamodded") && name.eq_ignore_ascii_case("smods") {
+        return "Steamodded".to_string();
+    }
+    let taken = name.eq_ignore_ascii_case("steamodded")
+        || packages
+            .iter()
+            .any(|p| p.name.eq_ignore_ascii_case(name) && !p.owner.eq_ignore_ascii_case(namespace));
+    if taken {
+        format!("{namespace}-{name}")
+    } else {
+        name.to_string()
+    }
+}
+
+/// Decides the names packages are tracked under. An installed package keeps the name
+/// recorded when it was installed, whatever the index publishes since; any other
+/// package gets a fresh one that clashes with neither the index nor those records.
+pub struct InstallNames<'a> {
+    packages: &'a [Package],
+    origins: Vec<ThunderstoreOrigin>,
+}
+
+impl<'a> InstallNames<'a> {
+    pub fn new(packages: &'a [Package], origins: Vec<ThunderstoreOrigin>) -> Self {
+        Self { packages, origins }
+    }
+
+    pub fn from_db(db: &Database, packages: &'a [Package]) -> Result<Self, AppError> {
+        Ok(Self::new(packages, db.get_thunderstore_origins()?))
+    }
+
+    pub fn packages(&self) -> &'a [Package] {
+        self.packages
+    }
+
+    pub fn of(&self, namespace: &str, name: &str) -> String {
+        if let Some(origin) = self.origins.iter().find(|o| {
+            o.namespace.eq_ignore_ascii_case(namespace) && o.package.eq_ignore_ascii_case(name)
+        }) {
+            return origin.mod_name.clone();
+        }
+        let fresh = install_name(self.packages, namespace, name);
+        if self
+            .origins
+            .iter()
+            .any(|o| o.mod_name.eq_ignore_ascii_case(&fresh))
+        {
+            format!("{namespace}-{name}")
+        } else {
+            fresh
+        }
+    }
+}
+
+pub struct ThunderstoreClient {
+    http: reqwest::Client,
+    community_url: String,
+}
+
+impl ThunderstoreClient {
+    pub fn new() -> Self {
+        Self::with_url(DEFAULT_COMMUNITY_URL)
+    }
+
+    /// A client for the community at `community_url` (`https://host/c/<community>`).
+    pub fn with_url(community_url: &str) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            community_url: community_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub async fn fetch_packages(&self) -> Result<Vec<Package>, AppError> {
+        let url = format!("{}/api/v1/package/", self.community_url);
+        let network_error = |source: String| AppError::NetworkRequest {
+            url: url.clone(),
+            source,
+        };
+        let resp = self
+            .http
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| network_error(e.to_string()))?;
+        if !resp.status().is_success() {
+            return Err(network_error(format!(
+                "Thunderstore status: {}",
+                resp.status()
+            )));
+        }
+        resp.json().await.map_err(|e| {
+            AppError::InvalidApiResponse(format!("Failed to parse Thunderstore packages: {e}"))
+        })
+    }
+}
+
+impl Default for ThunderstoreClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Lists the community's packages as catalog entries, keyed by their full name.
+pub struct ThunderstoreSource {
+    name: String,
+    client: ThunderstoreClient,
+    origins: Vec<ThunderstoreOrigin>,
+}
+
+impl ThunderstoreSource {
+    pub fn new(name: &str, client: ThunderstoreClient) -> Self {
+        Self {
+            name: name.to_string(),
+            client,
+            origins: Vec::new(),
+        }
+    }
+
+    /// Title installed packages by the names they were installed under.
+    pub fn with_origins(mut self, origins: Vec<ThunderstoreOrigin>) -> Self {
+        self.origins = origins;
+        self
+    }
+}
+
+impl ModSource for ThunderstoreSource {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn fetch(&self) -> BoxFuture<'_, Result<Vec<CatalogEntry>, AppError>> {
+        Box::pin(async move {
+            let packages = self.client.fetch_packages().await?;
+            let names = InstallNames::new(&packages, self.origins.clone());
+            Ok(packages
+                .iter()
+                .filter(|p| !p.is_deprecated)
+                .filter_map(|p| to_entry(p, &names))
+                .collect())
+        })
+    }
+}
+
+fn to_entry(package: &Package, names: &InstallNames) -> Option<CatalogEntry> {
+    let latest = package.latest()?;
+    let requires = |wanted: &str| {
+        latest
+            .dependencies
+            .iter()
+            .filter_map(|d| DependencyRef::parse(d))
+            .any(|d| d.install_name(names) == wanted)
+    };
+    let install_name = package.install_name(names);
+    Some(CatalogEntry {
+        dir_name: package.full_name.clone(),
+        meta: ModMeta {
+            requires_steamodded: requires("Steamodded"),
+            requires_talisman: requires("Talisman"),
+            categories: package.categories.clone(),
+            author: package.owner.clone(),
+            repo: if latest.website_url.is_empty() {
+                package.package_url.clone()
+            } else {
+                latest.website_url.clone()
+            },
+            folder_name: install_name.split_whitespace().collect(),
+            title: install_name,
+            download_url: Some(latest.download_url.clone()),
+            version: latest.version_number.clone(),
+            automatic_version_check: false,
+            last_updated: 0,
+        },
+        description: latest.description.clone(),
+        image_url: latest.icon.clone(),
+        source: String::new(),
+    })
+}
+
+/// Packages to install for `full_name`, dependencies first. Dependencies that are
+/// already installed are left out; the package itself is always included so
+/// installing it again updates it. Dependencies resolve to their newest version.
+pub fn plan<'a>(
+    names: &InstallNames<'a>,
+    full_name: &str,
+    installed: &[InstalledMod],
+) -> Result<Vec<&'a Package>, AppError> {
+    fn visit<'a>(
+        names: &InstallNames<'a>,
+        full_name: &str,
+        installed: &[InstalledMod],
+        root: bool,
+        seen: &mut HashSet<String>,
+        order: &mut Vec<&'a Package>,
+    ) -> Result<(), AppError> {
+        if !seen.insert(full_name.to_lowercase()) {
+            return Ok(());
+        }
+        let package = names
+            .packages()
+            .iter()
+            .find(|p| p.full_name.eq_ignore_ascii_case(full_name))
+            .ok_or_else(|| AppError::ModNotFound {
+                mod_name: full_name.to_string(),
+                version: String::new(),
+            })?;
+        let name = package.install_name(names);
+        if !root && installed.iter().any(|m| m.name.eq_ignore_ascii_case(&name)) {
+            return Ok(());
+        }
+        for dep in package.latest().map(|v| &v.dependencies[..]).unwrap_or(&[]) {
+            match DependencyRef::parse(dep) {
+                Some(dep) => visit(names, &dep.full_name(), installed, false, seen, order)?,
+                None => log::warn!("Ignoring malformed dependency '{dep}' of {full_name}"),
+            }
+        }
+        order.push(package);
+        Ok(())
+    }
+
+    let mut order = Vec::new();
+    visit(
+        names,
+        full_name,
+        installed,
+        true,
+        &mut HashSet::new(),
+        &mut order,
+    )?;
+    Ok(order)
+}
+
+/// Download and extract a package's newest version into the Mods folder. The
+/// returned record still has to be tracked in the database with [`track`].
+pub async fn download(
+    package: &Package,
+    names: &InstallNames<'_>,
+) -> Result<InstalledMod, AppError> {
+    let latest = package.latest().ok_or_else(|| AppError::ModNotFound {
+        mod_name: package.full_name.clone(),
+        version: String::new(),
+    })?;
+    let name = package.install_name(names);
+    let folder = name.split_whitespace().collect::<String>();
+    let path = installer::install_mod(latest.download_url.clone(), Some(folder)).await?;
+    Ok(InstalledMod {
+        name,
+        path: path.to_string_lossy().into_owned(),
+        dependencies: latest
+            .dependencies
+            .iter()
+            .filter_map(|d| DependencyRef::parse(d))
+            .map(|d| d.install_name(names))
+            .collect(),
+        current_version: Some(latest.version_number.clone()),
+    })
+}
+
+/// Install a package and whatever it depends on that isn't installed yet.
+pub async fn install(
+    db: &Database,
+    packages: &[Package],
+    full_name: &str,
+) -> Result<Vec<InstalledMod>, AppError> {
+    let names = InstallNames::from_db(db, packages)?;
+    let planned = plan(&names, full_name, &db.get_installed_mods()?)?;
+    save_backup::snapshot_quietly(db, SaveBackupReason::ModInstall);
+    let mut installed = Vec::new();
+    for package in planned {
+        let record = download(package, &names).await?;
+        track(db, package, &record)?;
+        installed.push(record);
+    }
+    Ok(installed)
+}
+
+/// Track a downloaded package, remembering which package its name stands for.
+pub fn track(db: &Database, package: &Package, record: &InstalledMod) -> Result<(), AppError> {
+    db.add_installed_mod(
+        &record.name,
+        &record.path,
+        &record.dependencies,
+        record.current_version.clone(),
+    )?;
+    db.set_thunderstore_origin(&ThunderstoreOrigin {
+        mod_name: record.name.clone(),
+        namespace: package.owner.clone(),
+        package: package.name.clone(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn package(owner: &str, name: &str, dependencies: &[&str]) -> Package {
+        Package {
+            name: name.to_string(),
+            full_name: format!("{owner}-{name}"),
+            owner: owner.to_string(),
+            package_url: String::new(),
+            categories: Vec::new(),
+            is_deprecated: false,
+            versions: vec![PackageVersion {
+                version_number: "1.0.0".to_string(),
+                description: String::new(),
+                icon: String::new(),
+                dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+                download_url: String::new(),
+                website_url: String::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn plan_orders_dependencies_and_skips_installed() {
+        assert_eq!(
+            DependencyRef::parse("Steamodded-smods-1.0.0-beta"),
+            Some(DependencyRef {
+                namespace: "Steamodded".to_string(),
+                name: "smods".to_string(),
+                version: Some("1.0.0-beta".to_string()),
+            })
+        );
+        assert_eq!(DependencyRef::parse("nodash"), None);
+
+        let packages = [
+            package("Steamodded", "smods", &[]),
+            package("Ace", "Lib", &["Steamodded-smods-1.0.0"]),
+            package("Ace", "Cards", &["Ace-Lib-0.1.0", "Steamodded-smods-1.0.0"]),
+        ];
+        let index = InstallNames::new(&packages, Vec::new());
+        let names = |plan: Vec<&Package>| -> Vec<String> {
+            plan.into_iter().map(|p| p.install_name(&index)).collect()
+        };
+        assert_eq!(
+            names(plan(&index, "ace-cards", &[]).unwrap()),
+            ["Steamodded", "Lib", "Cards"]
+        );
+
+        let installed = [InstalledMod {
+            name: "Steamodded".to_string(),
+            path: String::new(),
+            dependencies: Vec::new(),
+            current_version: None,
+        }];
+        assert_eq!(
+            names(plan(&index, "Ace-Cards", &installed).unwrap()),
+            ["Lib", "Cards"]
+        );
+        assert!(matches!(
+            plan(&index, "Ace-Missing", &[]),
+            Err(AppError::ModNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn names_published_by_several_namespaces_keep_their_namespace() {
+        let packages = [
+            package("Steamodded", "smods", &[]),
+            package("someone", "smods", &[]),
+            package("Ace", "Jokers", &[]),
+            package("Bee", "jokers", &["Steamodded-smods-1.0.0"]),
+            package("Ace", "Cards", &[]),
+            package("Copycat", "Steamodded", &[]),
+        ];
+        let index = InstallNames::new(&packages, Vec::new());
+        let name = |full_name: &str| {
+            packages
+                .iter()
+                .find(|p| p.full_name == full_name)
+                .unwrap()
+                .install_name(&index)
+        };
+        assert_eq!(name("Steamodded-smods"), "Steamodded");
+        assert_eq!(name("someone-smods"), "someone-smods");
+        assert_eq!(name("Ace-Jokers"), "Ace-Jokers");
+        assert_eq!(name("Bee-jokers"), "Bee-jokers");
+        assert_eq!(name("Ace-Cards"), "Cards");
+        assert_eq!(name("Copycat-Steamodded"), "Copycat-Steamodded");
+    }
+
+    #[test]
+    fn installed_packages_keep_their_recorded_name() {
+        let origin = |mod_name: &str, namespace: &str, package: &str| ThunderstoreOrigin {
+            mod_name: mod_name.to_string(),
+            namespace: namespace.to_string(),
+            package: package.to_string(),
+        };
+        // Ace-Cards was installed as "Cards" before Bee published a package of that name
+        let packages = [package("Ace", "Cards", &[]), package("Bee", "Cards", &[])];
+        let names = InstallNames::new(&packages, vec![origin("Cards", "Ace", "Cards")]);
+        assert_eq!(packages[0].install_name(&names), "Cards");
+        assert_eq!(packages[1].install_name(&names), "Bee-Cards");
+
+        // Bee-Cards, installed when it was the only one, now clashes with nobody else
+        let packages = [package("Ace", "Cards", &[])];
+        let names = InstallNames::new(&packages, vec![origin("Cards", "Bee", "Cards")]);
+        assert_eq!(packages[0].install_name(&names), "Ace-Cards");
+    }
+}
@@ -0,0 +1,47 @@
+// tests/common/mod.rs
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use tempfile::tempdir;
+
+// The sandbox is selected through process-wide env vars, so tests using it take turns
+static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+pub struct TestEnv {
+    pub temp_dir: tempfile::TempDir,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestEnv {
+    pub fn new() -> Self {
+        let lock = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let temp_dir = match tempdir() {
+            Ok(dir) => dir,
+            Err(e) => panic!("Failed to create temporary directory: {}", e),
+        };
+        // Setup environment variables
+        std::env::set_var("BMM_TEST_MODE", "1");
+        std::env::set_var(
+            "BMM_TEST_DIR",
+            match temp_dir.path().to_str() {
+                Some(path) => path,
+                None => panic!("Failed to convert temporary directory path to string"),
+            },
+        );
+        std::env::remove_var("BMM_MODS_DIR");
+        Self {
+            temp_dir,
+            _lock: lock,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        self.temp_dir.path()
+    }
+}
+
+impl Drop for TestEnv {
+    fn drop(&mut self) {
+        std::env::remove_var("BMM_TEST_MODE");
+        std::env::remove_var("BMM_TEST_DIR");
+    }
+}
@@ -0,0 +1,259 @@
+mod common;
+
+use bmm_lib::database::{Database, RepoOrigin};
+use bmm_lib::forge::{self, ForgeClient, RepoRef};
+use bmm_lib::paths;
+use common::TestEnv;
+use mockito::Matcher;
+use serde_json::json;
+use std::io::Write;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+// Source archives from both forges wrap the tree in one `<repo>-<ref>/` folder
+fn source_archive(root: &str, version: &str) -> Vec<u8> {
+    let mut buf = Vec::new();
+    {
+        let mut zw = ZipWriter::new(std::io::Cursor::new(&mut buf));
+        let opts: FileOptions<'_, ()> = FileOptions::default();
+        zw.start_file(format!("{root}/main.lua"), opts).unwrap();
+        zw.write_all(format!("-- {version}\n").as_bytes()).unwrap();
+        zw.finish().unwrap();
+    }
+    buf
+}
+
+#[tokio::test]
+async fn installs_gitlab_releases_and_tags_and_checks_for_updates() {
+    let env = TestEnv::new();
+    let mut server = mockito::Server::new_async().await;
+    let url = server.url();
+    let api = "/api/v4/projects/group%2FCool-Mod";
+    let _releases = server
+        .mock("GET", format!("{api}/releases").as_str())
+        .match_query(Matcher::UrlEncoded("per_page".into(), "100".into()))
+        .with_status(200)
+        .with_body(
+            json!([
+                {
+                    "tag_name": "v1.1",
+                    "name": "Cool Mod 1.1",
+                    "released_at": "2024-05-01T00:00:00Z",
+                    "assets": { "links": [{
+                        "name": "Cool-Mod.zip",
+                        "url": format!("{url}/files/Cool-Mod.zip")
+                    }] }
+                },
+                { "tag_name": "v1.0", "name": "", "assets": { "links": [] } }
+            ])
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    let _tags = server
+        .mock("GET", format!("{api}/repository/tags").as_str())
+        .match_query(Matcher::UrlEncoded("per_page".into(), "100".into()))
+        .with_status(200)
+        .with_body(json!([{ "name": "v1.1" }, { "name": "v1.0" }, { "name": "v0.9" }]).to_string())
+        .create_async()
+        .await;
+    let _archive = server
+        .mock("GET", format!("{api}/repository/archive.zip").as_str())
+        .match_query(Matcher::UrlEncoded("sha".into(), "v1.0".into()))
+        .with_status(200)
+        .with_body(source_archive("Cool-Mod-v1.0-abc", "1.0"))
+        .create_async()
+        .await;
+    let _asset = server
+        .mock("GET", "/files/Cool-Mod.zip")
+        .with_status(200)
+        .with_body(source_archive("Cool-Mod", "1.1"))
+        .create_async()
+        .await;
+
+    let client = ForgeClient::new();
+    let repo = RepoRef::parse(&format!("{url}/group/Cool-Mod@v1.0")).unwrap();
+    let versions = client.list_versions(&repo).await.unwrap();
+    let listed: Vec<_> = versions
+        .iter()
+        .map(|v| (v.tag.as_str(), v.name.as_str(), v.release))
+        .collect();
+    assert_eq!(
+        listed,
+        [
+            ("v1.1", "Cool Mod 1.1", true),
+            ("v1.0", "v1.0", true),
+            ("v0.9", "v0.9", false)
+        ]
+    );
+
+    let db = Database::new().unwrap();
+    let install = client.install(&repo, None, None).await.unwrap();
+    forge::track(&db, &install).unwrap();
+    let main = paths::mods_dir().unwrap().join("Cool-Mod/main.lua");
+    assert!(main.starts_with(env.root()));
+    assert_eq!(std::fs::read_to_string(&main).unwrap(), "-- 1.0\n");
+
+    let origin = db.get_repo_origin("Cool-Mod").unwrap().unwrap();
+    assert_eq!(origin.repo_url, format!("{url}/group/Cool-Mod"));
+    assert_eq!(origin.git_ref, "v1.0");
+    assert_eq!(
+        client.check_update(&origin).await.unwrap().as_deref(),
+        Some("v1.1")
+    );
+
+    // A release asset, from the newest release when no version is given
+    let repo = RepoRef::parse(&format!("{url}/group/Cool-Mod")).unwrap();
+    let install = client
+        .install(&repo, None, Some("Cool-Mod.zip"))
+        .await
+        .unwrap();
+    forge::track(&db, &install).unwrap();
+    assert_eq!(std::fs::read_to_string(&main).unwrap(), "-- 1.1\n");
+    let origin = db.get_repo_origin("Cool-Mod").unwrap().unwrap();
+    assert_eq!(origin.asset.as_deref(), Some("Cool-Mod.zip"));
+    assert_eq!(
+        db.get_mod_details("Cool-Mod")
+            .unwrap()
+            .current_version
+            .as_deref(),
+        Some("v1.1")
+    );
+    assert_eq!(client.check_update(&origin).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn installs_github_default_branch() {
+    let _env = TestEnv::new();
+    let mut server = mockito::Server::new_async().await;
+    let _repo = server
+        .mock("GET", "/repos/someone/Jokers")
+        .with_status(200)
+        .with_body(json!({ "default_branch": "main" }).to_string())
+        .create_async()
+        .await;
+    let _zipball = server
+        .mock("GET", "/repos/someone/Jokers/zipball/main")
+        .with_status(200)
+        .with_body(source_archive("someone-Jokers-0123abc", "main"))
+        .create_async()
+        .await;
+
+    let client = ForgeClient::with_github_api(&server.url());
+    let repo = RepoRef::parse("someone/Jokers").unwrap();
+    let install = client.install(&repo, None, None).await.unwrap();
+    assert_eq!(install.origin.git_ref, "main");
+    assert_eq!(install.origin.repo_url, "https://github.com/someone/Jokers");
+    assert!(paths::mods_dir().unwrap().join("Jokers/main.lua").exists());
+
+    // A branch install has no release to compare against
+    let _releases = server
+        .mock("GET", "/repos/someone/Jokers/releases")
+        .match_query(Matcher::UrlEncoded("per_page".into(), "100".into()))
+        .with_status(200)
+        .with_body("[]")
+        .create_async()
+        .await;
+    let _tags = server
+        .mock("GET", "/repos/someone/Jokers/tags")
+        .match_query(Matcher::UrlEncoded("per_page".into(), "100".into()))
+        .with_status(200)
+        .with_body("[]")
+        .create_async()
+        .await;
+    assert_eq!(client.check_update(&install.origin).await.unwrap(), None);
+}
+
+#[tokio::test]
+async fn update_check_reads_every_page_of_releases() {
+    let _env = TestEnv::new();
+    let mut server = mockito::Server::new_async().await;
+    let url = server.url();
+    let release = |tag: &str| json!({ "tag_name": tag, "draft": false, "prerelease": false });
+    let _first = server
+        .mock("GET", "/repos/someone/Jokers/releases")
+        .match_query(Matcher::Exact("per_page=100".into()))
+        .with_status(200)
+        .with_header(
+            "link",
+            &format!("<{url}/repos/someone/Jokers/releases?per_page=100&page=2>; rel=\"next\""),
+        )
+        .with_body(json!([release("v3.0"), release("v2.9")]).to_string())
+        .create_async()
+        .await;
+    let _second = server
+        .mock("GET", "/repos/someone/Jokers/releases")
+        .match_query(Matcher::Exact("per_page=100&page=2".into()))
+        .with_status(200)
+        .with_body(json!([release("v1.0")]).to_string())
+        .create_async()
+        .await;
+    let _tags = server
+        .mock("GET", "/repos/someone/Jokers/tags")
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_body("[]")
+        .create_async()
+        .await;
+
+    let client = ForgeClient::with_github_api(&url);
+    let origin = RepoOrigin {
+        mod_name: "Jokers".to_string(),
+        repo_url: "https://github.com/someone/Jokers".to_string(),
+        git_ref: "v1.0".to_string(),
+        asset: None,
+    };
+    assert_eq!(
+        client.check_update(&origin).await.unwrap().as_deref(),
+        Some("v3.0")
+    );
+}
+
+#[tokio::test]
+async fn update_check_ignores_older_releases_than_an_installed_prerelease() {
+    let _env = TestEnv::new();
+    let mut server = mockito::Server::new_async().await;
+    let url = server.url();
+    let release = |tag: &str, prerelease: bool, published_at: &str| {
+        json!({
+            "tag_name": tag,
+            "draft": false,
+            "prerelease": prerelease,
+            "published_at": published_at
+        })
+    };
+    let _releases = server
+        .mock("GET", "/repos/someone/Jokers/releases")
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_body(
+            json!([
+                release("v3.0-beta", true, "2024-03-01T00:00:00Z"),
+                release("v2.9", false, "2024-02-01T00:00:00Z"),
+            ])
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    let _tags = server
+        .mock("GET", "/repos/someone/Jokers/tags")
+        .match_query(Matcher::Any)
+        .with_status(200)
+        .with_body("[]")
+        .create_async()
+        .await;
+
+    let client = ForgeClient::with_github_api(&url);
+    let origin = |git_ref: &str| RepoOrigin {
+        mod_name: "Jokers".to_string(),
+        repo_url: "https://github.com/someone/Jokers".to_string(),
+        git_ref: git_ref.to_string(),
+        asset: None,
+    };
+    // v2.9 would be a downgrade from the prerelease
+    assert_eq!(
+        client.check_update(&origin("v3.0-beta")).await.unwrap(),
+        None
+    );
+    assert_eq!(client.check_update(&origin("v2.9")).await.unwrap(), None);
+}
@@ -0,0 +1,83 @@
+mod common;
+
+use bmm_lib::database::Database;
+use bmm_lib::{installer, local_mod_detection, paths};
+use common::TestEnv;
+use std::io::Write;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+fn mod_archive() -> Vec<u8> {
+    let mut buf = Vec::new();
+    {
+        let mut zw = ZipWriter::new(std::io::Cursor::new(&mut buf));
+        let opts: FileOptions<'_, ()> = FileOptions::default();
+        zw.start_file("CoolMod-main/CoolMod.json", opts).unwrap();
+        zw.write_all(
+            br#"{
+                "id": "CoolMod",
+                "name": "Cool Mod",
+                "author": ["Alice"],
+                "description": "Sandbox test mod",
+                "prefix": "cool",
+                "main_file": "CoolMod.lua",
+                "version": "1.0.0"
+            }"#,
+        )
+        .unwrap();
+        zw.start_file("CoolMod-main/CoolMod.lua", opts).unwrap();
+        zw.write_all(b"-- cool\n").unwrap();
+        zw.finish().unwrap();
+    }
+    buf
+}
+
+#[test]
+fn paths_resolve_inside_sandbox() {
+    let env = TestEnv::new();
+    for dir in [
+        paths::manager_dir().unwrap(),
+        paths::mods_dir().unwrap(),
+        paths::bins_dir().unwrap(),
+        paths::logs_dir().unwrap(),
+        paths::cache_dir().unwrap(),
+        paths::save_dir().unwrap(),
+    ] {
+        assert!(dir.starts_with(env.root()), "{dir:?} escaped the sandbox");
+    }
+}
+
+#[tokio::test]
+async fn install_detect_uninstall_in_sandbox() {
+    let env = TestEnv::new();
+    let mut server = mockito::Server::new_async().await;
+    let download = server
+        .mock("GET", "/CoolMod.zip")
+        .with_status(200)
+        .with_header("content-type", "application/zip")
+        .with_body(mod_archive())
+        .create_async()
+        .await;
+
+    let url = format!("{}/CoolMod.zip", server.url());
+    let installed = installer::install_mod(url, Some("CoolMod".to_string()))
+        .await
+        .unwrap();
+    download.assert_async().await;
+    assert_eq!(installed, paths::mods_dir().unwrap().join("CoolMod"));
+    assert!(installed.starts_with(env.root()));
+    assert!(installed.join("CoolMod.lua").exists());
+
+    let db = Database::new().unwrap();
+    assert!(paths::manager_dir()
+        .unwrap()
+        .join("bmm_storage.db")
+        .exists());
+    let detected = local_mod_detection::get_all_detected_mods(&db).unwrap();
+    assert!(detected.iter().any(|m| m.id == "CoolMod"));
+
+    installer::uninstall_mod(installed.clone()).unwrap();
+    assert!(!installed.exists());
+    let detected = local_mod_detection::get_all_detected_mods(&db).unwrap();
+    assert!(detected.iter().all(|m| m.id != "CoolMod"));
+}
@@ -0,0 +1,137 @@
+mod common;
+
+use bmm_lib::database::Database;
+use bmm_lib::mod_sources::ModSource;
+use bmm_lib::paths;
+use bmm_lib::thunderstore::{self, ThunderstoreClient, ThunderstoreSource};
+use common::TestEnv;
+use serde_json::json;
+use std::io::Write;
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+// Thunderstore packages keep manifest.json and README.md at the archive root
+fn package_archive(name: &str) -> Vec<u8> {
+    let mut buf = Vec::new();
+    {
+        let mut zw = ZipWriter::new(std::io::Cursor::new(&mut buf));
+        let opts: FileOptions<'_, ()> = FileOptions::default();
+        zw.start_file("manifest.json", opts).unwrap();
+        zw.write_all(
+            json!({ "name": name, "version_number": "1.0.0" })
+                .to_string()
+                .as_bytes(),
+        )
+        .unwrap();
+        zw.start_file(format!("{name}/main.lua"), opts).unwrap();
+        zw.write_all(b"-- main\n").unwrap();
+        zw.finish().unwrap();
+    }
+    buf
+}
+
+fn package(
+    server_url: &str,
+    owner: &str,
+    name: &str,
+    version: &str,
+    deps: &[&str],
+) -> serde_json::Value {
+    json!({
+        "name": name,
+        "full_name": format!("{owner}-{name}"),
+        "owner": owner,
+        "package_url": format!("{server_url}/c/balatro/p/{owner}/{name}/"),
+        "categories": ["Jokers"],
+        "is_deprecated": false,
+        "versions": [{
+            "name": name,
+            "full_name": format!("{owner}-{name}-{version}"),
+            "version_number": version,
+            "description": format!("{name} description"),
+            "icon": format!("{server_url}/icons/{name}.png"),
+            "dependencies": deps,
+            "download_url": format!("{server_url}/download/{owner}/{name}/{version}/"),
+            "website_url": ""
+        }]
+    })
+}
+
+#[tokio::test]
+async fn installs_package_with_dependencies_from_stand_in_server() {
+    let env = TestEnv::new();
+    let mut server = mockito::Server::new_async().await;
+    let url = server.url();
+    let _list = server
+        .mock("GET", "/c/balatro/api/v1/package/")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(
+            json!([
+                package(&url, "Steamodded", "smods", "1.0.0", &[]),
+                package(&url, "Ace", "Cards", "1.2.0", &["Steamodded-smods-1.0.0"]),
+            ])
+            .to_string(),
+        )
+        .create_async()
+        .await;
+    let smods = server
+        .mock("GET", "/download/Steamodded/smods/1.0.0/")
+        .with_status(200)
+        .with_header("content-type", "application/zip")
+        .with_body(package_archive("smods"))
+        .expect(1)
+        .create_async()
+        .await;
+    let cards = server
+        .mock("GET", "/download/Ace/Cards/1.2.0/")
+        .with_status(200)
+        .with_header("content-type", "application/zip")
+        .with_body(package_archive("Cards"))
+        .expect(2)
+        .create_async()
+        .await;
+
+    let client = ThunderstoreClient::with_url(&format!("{url}/c/balatro"));
+    let packages = client.fetch_packages().await.unwrap();
+    let db = Database::new().unwrap();
+
+    let installed = thunderstore::install(&db, &packages, "Ace-Cards")
+        .await
+        .unwrap();
+    let names: Vec<_> = installed.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["Steamodded", "Cards"]);
+
+    let mods_dir = paths::mods_dir().unwrap();
+    assert!(mods_dir.starts_with(env.root()));
+    assert!(mods_dir.join("Steamodded/manifest.json").exists());
+    assert!(mods_dir.join("Cards/Cards/main.lua").exists());
+    let tracked = db.get_mod_details("Cards").unwrap();
+    assert_eq!(tracked.dependencies, ["Steamodded"]);
+    assert_eq!(tracked.current_version.as_deref(), Some("1.2.0"));
+    let origins = db.get_thunderstore_origins().unwrap();
+    let cards_origin = origins.iter().find(|o| o.mod_name == "Cards").unwrap();
+    assert_eq!(
+        (
+            cards_origin.namespace.as_str(),
+            cards_origin.package.as_str()
+        ),
+        ("Ace", "Cards")
+    );
+
+    // Reinstalling leaves the installed dependency alone
+    let again = thunderstore::install(&db, &packages, "Ace-Cards")
+        .await
+        .unwrap();
+    assert_eq!(again.len(), 1);
+    smods.assert_async().await;
+    cards.assert_async().await;
+
+    let entries = ThunderstoreSource::new("thunderstore", client)
+        .fetch()
+        .await
+        .unwrap();
+    let cards = entries.iter().find(|e| e.dir_name == "Ace-Cards").unwrap();
+    assert!(cards.meta.requires_steamodded);
+    assert_eq!(cards.meta.version, "1.2.0");
+}
@@ -0,0 +1,42 @@
+use crate::util::map_error;
+use bmm_lib::bisect::{self, BisectResult, BisectStatus};
+use bmm_lib::errors::AppError;
+use bmm_lib::paths;
+
+/// Start bisecting the enabled mods to find the one behind a crash.
+#[tauri::command]
+pub async fn start_crash_bisect() -> Result<BisectStatus, String> {
+    let state_file = map_error(bisect::default_state_file())?;
+    let session = map_error(bisect::start(&map_error(paths::mods_dir())?, &state_file))?;
+    Ok(session.status())
+}
+
+/// The running bisection, if any.
+#[tauri::command]
+pub async fn get_crash_bisect_status() -> Result<Option<BisectStatus>, String> {
+    let state_file = map_error(bisect::default_state_file())?;
+    let session = map_error(bisect::load(&state_file))?;
+    Ok(session.map(|s| s.status()))
+}
+
+/// Report whether the last launch crashed and move on to the next step.
+#[tauri::command]
+pub async fn record_crash_bisect_result(crashed: bool) -> Result<BisectStatus, String> {
+    let state_file = map_error(bisect::default_state_file())?;
+    let mut session = map_error(bisect::load(&state_file))?
+        .ok_or_else(|| AppError::InvalidState("No crash bisection in progress".to_string()))?;
+    let result = if crashed {
+        BisectResult::Fail
+    } else {
+        BisectResult::Pass
+    };
+    map_error(session.record(result, &state_file))?;
+    Ok(session.status())
+}
+
+/// Stop the bisection and re-enable the mods it disabled.
+#[tauri::command]
+pub async fn abort_crash_bisect() -> Result<(), String> {
+    let state_file = map_error(bisect::default_state_file())?;
+    map_error(bisect::abort(&state_file))
+}
@@ -2,6 +2,7 @@ use std::time::{SystemTime, UNIX_EPOCH};
 
 use bmm_lib::cache;
 use bmm_lib::cache::Mod;
+use bmm_lib::paths;
 
 use crate::state::AppState;
 use crate::util::map_error;
@@ -47,9 +48,9 @@ pub async fn clear_cache() -> Result<(), String> {
     }
 
     // Also clear the GitLab mod index cache we maintain under the config directory
-    let config_dir = match dirs::config_dir() {
-        Some(p) => p,
-        None => {
+    let manager_dir = match paths::manager_dir() {
+        Ok(p) => p,
+        Err(_) => {
             // If we can't resolve config dir, return any prior error or success for the primary cache
             return if errors.is_empty() {
                 Ok(())
@@ -58,7 +59,7 @@ pub async fn clear_cache() -> Result<(), String> {
             };
         }
     };
-    let mod_index_cache_dir = config_dir.join("Balatro").join("mod_index_cache");
+    let mod_index_cache_dir = manager_dir.join("mod_index_cache");
     if mod_index_cache_dir.exists() {
         if let Err(e) = std::fs::remove_dir_all(&mod_index_cache_dir) {
             errors.push(format!(
@@ -70,7 +71,7 @@ pub async fn clear_cache() -> Result<(), String> {
     }
 
     // Clear UI assets cache (thumbnails/descriptions)
-    let mod_assets_dir = config_dir.join("Balatro").join("mod_assets");
+    let mod_assets_dir = manager_dir.join("mod_assets");
     if mod_assets_dir.exists() {
         if let Err(e) = std::fs::remove_dir_all(&mod_assets_dir) {
             errors.push(format!(
@@ -2,8 +2,9 @@ use std::path::{Path, PathBuf};
 
 use crate::state::AppState;
 use crate::util::map_error;
+use bmm_lib::backup::{BackupReason, BackupStore, RetentionPolicy, StoreUsage};
+use bmm_lib::database::ModBackup;
 use bmm_lib::{cache, database::Database, errors::AppError, local_mod_detection};
-use serde_json::json;
 use tauri::Emitter;
 
 #[tauri::command]
@@ -170,9 +171,7 @@ pub async fn delete_manual_mod(path: String) -> Result<(), String> {
             path.display()
         ));
     }
-    let config_dir =
-        dirs::config_dir().ok_or_else(|| "Could not find config directory".to_string())?;
-    let mods_dir = config_dir.join("Balatro").join("Mods");
+    let mods_dir = bmm_lib::paths::mods_dir().map_err(|e| e.to_string())?;
 
     let canonicalized_path = path
         .canonicalize()
@@ -195,153 +194,150 @@ pub async fn delete_manual_mod(path: String) -> Result<(), String> {
     Ok(())
 }
 
-#[tauri::command]
-pub async fn backup_local_mod(path: String) -> Result<(), String> {
-    let path = PathBuf::from(path);
-    if !path.exists() {
-        return Err(format!("Path doesn't exist: {}", path.display()));
+fn mod_identity(db: &Database, path: &Path) -> (String, Option<String>) {
+    let path_str = path.to_string_lossy();
+    if let Ok(installed) = db.get_installed_mods() {
+        if let Some(m) = installed.into_iter().find(|m| m.path == path_str) {
+            return (m.name, m.current_version.filter(|v| !v.is_empty()));
+        }
     }
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path_str.to_string());
+    (name, None)
+}
 
-    let backup_dir = get_backup_dir()?;
-    let backup_id = format!(
-        "backup_{}",
-        std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| format!("Failed to get timestamp: {e}"))?
-            .as_millis()
-    );
-    let backup_path = backup_dir.join(backup_id);
+#[tauri::command]
+pub async fn backup_local_mod(
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<ModBackup, String> {
+    let path = PathBuf::from(path);
+    let (mod_name, version) = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|_| AppError::LockPoisoned("Database lock poisoned".to_string()))?;
+        mod_identity(&db, &path)
+    };
 
-    std::fs::create_dir_all(&backup_path)
-        .map_err(|e| format!("Failed to create backup directory: {e}"))?;
+    // Copying a large mod takes a while; keep the database free in the meantime
+    let store = map_error(BackupStore::new())?;
+    let (store, snapshot) = tauri::async_runtime::spawn_blocking(move || {
+        let snapshot = store.snapshot(&path);
+        (store, snapshot)
+    })
+    .await
+    .map_err(|e| format!("Backup task failed: {e}"))?;
+    let snapshot = map_error(snapshot)?;
 
-    if path.is_dir() {
-        copy_dir_all(&path, &backup_path.join(path.file_name().unwrap()))
-            .map_err(|e| format!("Failed to copy mod to backup: {e}"))?;
-    } else {
-        std::fs::copy(&path, backup_path.join(path.file_name().unwrap()))
-            .map_err(|e| format!("Failed to copy mod file to backup: {e}"))?;
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::LockPoisoned("Database lock poisoned".to_string()))?;
+    let backup = map_error(store.record(
+        &db,
+        snapshot,
+        &mod_name,
+        version.as_deref(),
+        BackupReason::Update,
+    ))?;
+
+    let (max_per_mod, quota_bytes) = map_error(db.get_backup_limits())?;
+    if let Err(e) = store.cleanup(
+        &db,
+        RetentionPolicy {
+            max_per_mod,
+            quota_bytes,
+        },
+    ) {
+        log::warn!("Backup cleanup failed: {e}");
     }
+    Ok(backup)
+}
 
-    let metadata = json!({
-        "original_path": path.to_string_lossy().to_string(),
-        "backup_time": std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs()
-    });
-
-    std::fs::write(
-        backup_path.join("metadata.json"),
-        serde_json::to_string_pretty(&metadata)
-            .map_err(|e| format!("Failed to serialize metadata: {e}"))?,
-    )
-    .map_err(|e| format!("Failed to write metadata: {e}"))?;
+#[tauri::command]
+pub async fn restore_from_backup(
+    state: tauri::State<'_, AppState>,
+    path: String,
+) -> Result<(), String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::LockPoisoned("Database lock poisoned".to_string()))?;
+    let store = map_error(BackupStore::new())?;
+    map_error(store.restore_latest(&db, Path::new(&path)))?;
+    Ok(())
+}
 
+#[tauri::command]
+pub async fn remove_backup(state: tauri::State<'_, AppState>, path: String) -> Result<(), String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::LockPoisoned("Database lock poisoned".to_string()))?;
+    let store = map_error(BackupStore::new())?;
+    map_error(store.remove_for_path(&db, Path::new(&path)))?;
     Ok(())
 }
 
 #[tauri::command]
-pub async fn restore_from_backup(path: String) -> Result<(), String> {
-    let path = PathBuf::from(path);
-    let backup_dir = get_backup_dir()?;
-
-    let mut latest_backup = None;
-    let mut latest_time = 0;
-    for entry in std::fs::read_dir(&backup_dir)
-        .map_err(|e| format!("Failed to read backup directory: {e}"))?
-    {
-        let entry = entry.map_err(|e| format!("Failed to read backup entry: {e}"))?;
-        let metadata_path = entry.path().join("metadata.json");
-        if metadata_path.exists() {
-            let metadata: serde_json::Value = serde_json::from_str(
-                &std::fs::read_to_string(&metadata_path)
-                    .map_err(|e| format!("Failed to read metadata file: {e}"))?,
-            )
-            .map_err(|e| format!("Failed to parse metadata: {e}"))?;
-            if let Some(original_path) = metadata.get("original_path").and_then(|v| v.as_str()) {
-                if original_path == path.to_string_lossy() {
-                    if let Some(backup_time) = metadata.get("backup_time").and_then(|v| v.as_u64())
-                    {
-                        if backup_time > latest_time {
-                            latest_time = backup_time;
-                            latest_backup = Some(entry.path());
-                        }
-                    }
-                }
-            }
-        }
-    }
+pub async fn list_mod_backups(
+    state: tauri::State<'_, AppState>,
+    path: Option<String>,
+) -> Result<Vec<ModBackup>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let store = map_error(BackupStore::new())?;
+    map_error(store.list(&db, path.as_deref().map(Path::new)))
+}
 
-    let backup_path = latest_backup.ok_or_else(|| "No backup found for this path".to_string())?;
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)
-            .map_err(|e| format!("Failed to create parent directory: {e}"))?;
-    }
-    for entry in std::fs::read_dir(&backup_path)
-        .map_err(|e| format!("Failed to read backup directory: {e}"))?
-    {
-        let entry = entry.map_err(|e| format!("Failed to read backup entry: {e}"))?;
-        let file_name = entry.file_name();
-        if file_name == "metadata.json" {
-            continue;
-        }
-        let dest_path = path.parent().unwrap().join(&file_name);
-        if entry.path().is_dir() {
-            copy_dir_all(&entry.path(), &dest_path)
-                .map_err(|e| format!("Failed to restore directory from backup: {e}"))?;
-        } else {
-            std::fs::copy(entry.path(), &dest_path)
-                .map_err(|e| format!("Failed to restore file from backup: {e}"))?;
-        }
-    }
-    Ok(())
+#[tauri::command]
+pub async fn restore_mod_backup(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<String, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let store = map_error(BackupStore::new())?;
+    let restored = map_error(store.restore(&db, id))?;
+    local_mod_detection::clear_detection_cache();
+    Ok(restored.to_string_lossy().into_owned())
 }
 
 #[tauri::command]
-pub async fn remove_backup(path: String) -> Result<(), String> {
-    let path = PathBuf::from(path);
-    let backup_dir = get_backup_dir()?;
-    for entry in std::fs::read_dir(&backup_dir)
-        .map_err(|e| format!("Failed to read backup directory: {e}"))?
-    {
-        let entry = entry.map_err(|e| format!("Failed to read backup entry: {e}"))?;
-        let metadata_path = entry.path().join("metadata.json");
-        if metadata_path.exists() {
-            let metadata: serde_json::Value = serde_json::from_str(
-                &std::fs::read_to_string(&metadata_path)
-                    .map_err(|e| format!("Failed to read metadata file: {e}"))?,
-            )
-            .map_err(|e| format!("Failed to parse metadata: {e}"))?;
-            if let Some(original_path) = metadata.get("original_path").and_then(|v| v.as_str()) {
-                if original_path == path.to_string_lossy() {
-                    std::fs::remove_dir_all(entry.path())
-                        .map_err(|e| format!("Failed to remove backup: {e}"))?;
-                }
-            }
-        }
-    }
-    Ok(())
+pub async fn delete_mod_backup(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let store = map_error(BackupStore::new())?;
+    map_error(store.remove(&db, id))
 }
 
-fn get_backup_dir() -> Result<PathBuf, String> {
-    let temp_dir = std::env::temp_dir().join("balatro_mod_manager_backups");
-    std::fs::create_dir_all(&temp_dir)
-        .map_err(|e| format!("Failed to create backup directory: {e}"))?;
-    Ok(temp_dir)
+#[tauri::command]
+pub async fn cleanup_mod_backups(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let store = map_error(BackupStore::new())?;
+    let (max_per_mod, quota_bytes) = map_error(db.get_backup_limits())?;
+    map_error(store.cleanup(
+        &db,
+        RetentionPolicy {
+            max_per_mod,
+            quota_bytes,
+        },
+    ))
 }
 
-fn copy_dir_all(src: &Path, dst: &Path) -> std::io::Result<()> {
-    std::fs::create_dir_all(dst)?;
-    for entry in std::fs::read_dir(src)? {
-        let entry = entry?;
-        let ty = entry.file_type()?;
-        let path = entry.path();
-        if ty.is_dir() {
-            copy_dir_all(&path, &dst.join(path.file_name().unwrap()))?;
-        } else {
-            std::fs::copy(&path, dst.join(path.file_name().unwrap()))?;
-        }
-    }
-    Ok(())
+#[tauri::command]
+pub async fn get_backup_usage(state: tauri::State<'_, AppState>) -> Result<StoreUsage, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let store = map_error(BackupStore::new())?;
+    map_error(store.usage(&db))
+}
+
+#[tauri::command]
+pub async fn set_backup_limits(
+    state: tauri::State<'_, AppState>,
+    max_per_mod: usize,
+    quota_bytes: u64,
+) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(db.set_backup_limits(max_per_mod.max(1), quota_bytes))
 }
@@ -5,13 +5,9 @@ use flate2::read::GzDecoder;
 use tar::Archive;
 use zip::ZipArchive;
 
-use bmm_lib::errors::AppError;
-
 #[tauri::command]
 pub async fn process_dropped_file(path: String) -> Result<String, String> {
-    let config_dir =
-        dirs::config_dir().ok_or_else(|| "Could not find config directory".to_string())?;
-    let mods_dir = config_dir.join("Balatro").join("Mods");
+    let mods_dir = bmm_lib::paths::mods_dir().map_err(|e| e.to_string())?;
     std::fs::create_dir_all(&mods_dir)
         .map_err(|e| format!("Failed to create mods directory: {e}"))?;
 
@@ -33,9 +29,7 @@ pub async fn process_dropped_file(path: String) -> Result<String, String> {
 
 #[tauri::command]
 pub async fn process_mod_archive(filename: String, data: Vec<u8>) -> Result<String, String> {
-    let config_dir = dirs::config_dir()
-        .ok_or_else(|| AppError::DirNotFound(PathBuf::from("config directory")).to_string())?;
-    let mods_dir = config_dir.join("Balatro").join("Mods");
+    let mods_dir = bmm_lib::paths::mods_dir().map_err(|e| e.to_string())?;
     std::fs::create_dir_all(&mods_dir)
         .map_err(|e| format!("Failed to create mods directory: {e}"))?;
 
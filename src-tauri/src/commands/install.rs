@@ -1,111 +1,113 @@
+use std::collections::HashMap;
 use std::path::PathBuf;
-#[cfg(any(target_os = "macos", target_os = "windows"))]
-use std::process::Command;
 
+use crate::commands::saves::snapshot_saves_quietly;
 use crate::state::AppState;
 use crate::util::map_error;
+use bmm_lib::database::LaunchRecord;
 use bmm_lib::errors::AppError;
-#[cfg(target_os = "macos")]
-use bmm_lib::lovely;
+use bmm_lib::forge::{self, ForgeClient, RepoRef, RepoVersion};
+use bmm_lib::launch_session::{unix_millis, GameProcess, LaunchSession, LaunchVariant};
+use bmm_lib::launcher::{self, LaunchOptions};
+use bmm_lib::paths;
+use bmm_lib::safe_mode;
+use bmm_lib::save_backup::SaveBackupReason;
 use bmm_lib::smods_installer::{ModInstaller, ModType};
+use bmm_lib::thunderstore::{self, InstallNames, Package, ThunderstoreClient};
 use bmm_lib::{cache, database::InstalledMod};
+use std::time::SystemTime;
+use tauri::Emitter;
 
-#[cfg(any(target_os = "macos", target_os = "windows"))]
-fn get_installation_and_console(
+/// Record a launch and watch the game until it exits, emitting `game-started`
+/// and `game-exited` (with the crash parsed from the Lovely log, if any).
+fn track_launch(
+    app_handle: &tauri::AppHandle,
     state: &tauri::State<'_, AppState>,
-) -> Result<(String, bool), String> {
-    let db = state
-        .db
-        .lock()
-        .map_err(|_| AppError::LockPoisoned("Database lock poisoned".to_string()).to_string())?;
-    let install_path = db
-        .get_installation_path()
-        .map_err(|e| e.to_string())?
-        .ok_or_else(|| {
-            AppError::InvalidState("No installation path set".to_string()).to_string()
-        })?;
-    let lovely_console_enabled = db.is_lovely_console_enabled().map_err(|e| e.to_string())?;
-    Ok((install_path, lovely_console_enabled))
-}
+    launch_mode: &str,
+    variant: LaunchVariant,
+    started: SystemTime,
+    process: GameProcess,
+) {
+    let launch_mode = variant.session_label(launch_mode);
+    let session_id = match state.db.lock() {
+        Ok(db) => db
+            .start_launch_session(&launch_mode, unix_millis(started))
+            .map_err(|e| log::warn!("Failed to record launch session: {e}"))
+            .ok(),
+        Err(_) => None,
+    };
+    let Ok(mods_dir) = paths::mods_dir() else {
+        return;
+    };
 
-#[cfg(target_os = "macos")]
-#[tauri::command]
-pub async fn launch_balatro(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    let (path_str, lovely_console_enabled) = get_installation_and_console(&state)?;
-    let path = PathBuf::from(path_str);
-
-    let lovely_path = map_error(lovely::ensure_lovely_exists().await)?;
-    let balatro_executable = path.join("Balatro.app/Contents/MacOS/love");
-
-    if lovely_console_enabled {
-        let disable_arg = if !lovely_console_enabled {
-            " --disable-console"
-        } else {
-            ""
-        };
-        let command_line = format!(
-            "cd '{}' && DYLD_INSERT_LIBRARIES='{}' '{}'{}",
-            path.display(),
-            lovely_path.display(),
-            balatro_executable.display(),
-            disable_arg
-        );
-
-        let applescript = format!("tell application \"Terminal\" to do script \"{command_line}\"");
-
-        Command::new("osascript")
-            .arg("-e")
-            .arg(applescript)
-            .status()
-            .map_err(|e| e.to_string())?;
-    } else {
-        let cmd = format!(
-            "DYLD_INSERT_LIBRARIES='{}' '{}'",
-            lovely_path.display(),
-            balatro_executable.display()
-        );
-        // Spawn the process without waiting so the UI doesn't block
-        Command::new("sh")
-            .arg("-c")
-            .arg(cmd)
-            .spawn()
-            .map_err(|e| e.to_string())?;
-    }
+    let session = LaunchSession::new(session_id, &launch_mode, started, process, &mods_dir);
+    let _ = app_handle.emit("game-started", session.started_event());
 
-    Ok(())
+    let handle = app_handle.clone();
+    session.watch(move |outcome| {
+        // Never seen means possibly still starting, so safe mode stays on until next time
+        let Some(outcome) = outcome else {
+            return;
+        };
+        if let Err(e) = launcher::finish(variant) {
+            log::error!("Failed to restore mods after safe mode: {e}");
+        }
+        if let Some(id) = outcome.session_id {
+            // The watcher outlives the command, so it opens its own connection
+            let result = bmm_lib::database::Database::new().and_then(|db| {
+                db.finish_launch_session(
+                    id,
+                    outcome.ended_at,
+                    outcome.exit_code,
+                    outcome.crashed,
+                    outcome.crash.as_ref().map(|c| c.message.as_str()),
+                )
+            });
+            if let Err(e) = result {
+                log::warn!("Failed to record end of launch session {id}: {e}");
+            }
+        }
+        let _ = handle.emit("game-exited", outcome);
+    });
 }
 
-#[cfg(target_os = "windows")]
-#[tauri::command]
-pub async fn launch_balatro(state: tauri::State<'_, AppState>) -> Result<(), String> {
-    use std::os::windows::process::CommandExt;
-    const CREATE_NO_WINDOW: u32 = 0x08000000;
-
-    let (path_str, lovely_console_enabled) = get_installation_and_console(&state)?;
-    let path = PathBuf::from(path_str);
-
-    let mut cmd = Command::new(path.join("Balatro.exe"));
-
-    // Respect the "Enable Lovely Console" setting on Windows by hiding the console
-    // when disabled. If enabled, let the process manage its own console normally.
-    if !lovely_console_enabled {
-        // Ask Lovely to suppress its console and also prevent a console window
-        // from being created for the process.
-        cmd.arg("--disable-console");
-        cmd.env("LOVELY_DISABLE_CONSOLE", "1");
-        cmd.env("LOVELY_NO_CONSOLE", "1");
-        cmd.env("LOVELY_CONSOLE", "0");
-        cmd.creation_flags(CREATE_NO_WINDOW);
+/// Re-enable mods left disabled by a safe-mode launch. Called on startup, in case
+/// the manager was closed while the game was running.
+pub fn restore_safe_mode() {
+    let result = safe_mode::default_state_file().and_then(|file| safe_mode::restore(&file));
+    if let Err(e) = result {
+        log::error!("Failed to restore mods after safe mode: {e}");
     }
+}
 
-    cmd.spawn().map_err(|e| e.to_string())?;
-    Ok(())
+/// Past launches, newest first.
+#[tauri::command]
+pub async fn get_launch_sessions(
+    state: tauri::State<'_, AppState>,
+    limit: Option<usize>,
+) -> Result<Vec<LaunchRecord>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(db.get_launch_sessions(limit.unwrap_or(50)))
 }
 
-#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+/// Launch the game. `variant` picks between the modded game (default), vanilla
+/// without Lovely, and safe mode with only Steamodded enabled.
 #[tauri::command]
-pub async fn launch_balatro(_state: tauri::State<'_, AppState>) -> Result<(), String> {
-    Err("Launching Balatro is not supported on this operating system".to_string())
+pub async fn launch_balatro(
+    state: tauri::State<'_, AppState>,
+    app_handle: tauri::AppHandle,
+    variant: Option<LaunchVariant>,
+) -> Result<(), String> {
+    let variant = variant.unwrap_or_default();
+    let options = {
+        let db = state.db.lock().map_err(|_| {
+            AppError::LockPoisoned("Database lock poisoned".to_string()).to_string()
+        })?;
+        map_error(LaunchOptions::from_db(&db, variant))?
+    };
+    let (launch_mode, started, process) = map_error(launcher::spawn(&options).await)?;
+    track_launch(&app_handle, &state, launch_mode, variant, started, process);
+    Ok(())
 }
 
 #[tauri::command]
@@ -119,7 +121,11 @@ pub async fn get_steamodded_versions() -> Result<Vec<String>, String> {
 }
 
 #[tauri::command]
-pub async fn install_steamodded_version(version: String) -> Result<String, String> {
+pub async fn install_steamodded_version(
+    state: tauri::State<'_, AppState>,
+    version: String,
+) -> Result<String, String> {
+    snapshot_saves_quietly(&state, SaveBackupReason::ModInstall);
     let installer = ModInstaller::new(ModType::Steamodded);
     installer
         .install_version(&version)
@@ -162,7 +168,11 @@ pub async fn get_latest_steamodded_release() -> Result<String, String> {
 }
 
 #[tauri::command]
-pub async fn install_talisman_version(version: String) -> Result<String, String> {
+pub async fn install_talisman_version(
+    state: tauri::State<'_, AppState>,
+    version: String,
+) -> Result<String, String> {
+    snapshot_saves_quietly(&state, SaveBackupReason::ModInstall);
     let installer = ModInstaller::new(ModType::Talisman);
     installer
         .install_version(&version)
@@ -200,10 +210,10 @@ pub async fn cascade_uninstall(
         let dependents = map_error(db.get_dependents(&current))?;
         to_uninstall.extend(dependents);
 
-        map_error(bmm_lib::installer::uninstall_mod(PathBuf::from(
-            mod_details.path,
-        )))?;
+        let path = PathBuf::from(mod_details.path);
+        map_error(bmm_lib::installer::uninstall_mod(path.clone()))?;
         map_error(db.remove_installed_mod(&current))?;
+        bmm_lib::installer::forget_user_config(&path);
     }
 
     Ok(())
@@ -215,9 +225,12 @@ pub async fn force_remove_mod(
     name: String,
     path: String,
 ) -> Result<(), String> {
-    map_error(bmm_lib::installer::uninstall_mod(PathBuf::from(path)))?;
+    let path = PathBuf::from(path);
+    map_error(bmm_lib::installer::uninstall_mod(path.clone()))?;
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    map_error(db.remove_installed_mod(&name))
+    map_error(db.remove_installed_mod(&name))?;
+    bmm_lib::installer::forget_user_config(&path);
+    Ok(())
 }
 
 #[tauri::command]
@@ -241,20 +254,114 @@ pub async fn remove_installed_mod(
         }
     }
 
-    map_error(bmm_lib::installer::uninstall_mod(PathBuf::from(path)))?;
-    map_error(db.remove_installed_mod(&name))
+    let path = PathBuf::from(path);
+    map_error(bmm_lib::installer::uninstall_mod(path.clone()))?;
+    map_error(db.remove_installed_mod(&name))?;
+    bmm_lib::installer::forget_user_config(&path);
+    Ok(())
 }
 
 #[tauri::command]
-pub async fn install_mod(url: String, folder_name: String) -> Result<PathBuf, String> {
+pub async fn install_mod(
+    state: tauri::State<'_, AppState>,
+    url: String,
+    folder_name: String,
+) -> Result<PathBuf, String> {
     let folder_name = if folder_name.is_empty() {
         None
     } else {
         Some(folder_name)
     };
+
+    snapshot_saves_quietly(
+        &state,
+        SaveBackupReason::for_install(folder_name.as_deref()),
+    );
     map_error(bmm_lib::installer::install_mod(url, folder_name).await)
 }
 
+/// Install a Thunderstore package plus any of its dependencies that aren't installed.
+#[tauri::command]
+pub async fn install_thunderstore_package(
+    state: tauri::State<'_, AppState>,
+    full_name: String,
+) -> Result<Vec<InstalledMod>, String> {
+    let packages = map_error(ThunderstoreClient::new().fetch_packages().await)?;
+    let (names, planned): (InstallNames, Vec<Package>) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        let names = map_error(InstallNames::from_db(&db, &packages))?;
+        let installed = map_error(db.get_installed_mods())?;
+        let planned = map_error(thunderstore::plan(&names, &full_name, &installed))?
+            .into_iter()
+            .cloned()
+            .collect();
+        (names, planned)
+    };
+
+    snapshot_saves_quietly(&state, SaveBackupReason::ModInstall);
+    let mut records = Vec::new();
+    for package in &planned {
+        records.push(map_error(thunderstore::download(package, &names).await)?);
+    }
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    for (package, record) in planned.iter().zip(&records) {
+        map_error(thunderstore::track(&db, package, record))?;
+    }
+    Ok(records)
+}
+
+/// Releases and tags of a repository given as a URL or `owner/repo[@ref]`.
+#[tauri::command]
+pub async fn list_repo_versions(repo: String) -> Result<Vec<RepoVersion>, String> {
+    let repo = map_error(RepoRef::parse(&repo))?;
+    map_error(ForgeClient::new().list_versions(&repo).await)
+}
+
+/// Install a mod from a GitHub or GitLab repository and remember where it came from.
+#[tauri::command]
+pub async fn install_from_repo(
+    state: tauri::State<'_, AppState>,
+    repo: String,
+    version: Option<String>,
+    asset: Option<String>,
+) -> Result<InstalledMod, String> {
+    let repo = map_error(RepoRef::parse(&repo))?;
+    snapshot_saves_quietly(&state, SaveBackupReason::for_install(Some(repo.name())));
+    let install = map_error(
+        ForgeClient::new()
+            .install(&repo, version.as_deref(), asset.as_deref())
+            .await,
+    )?;
+
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(forge::track(&db, &install))?;
+    Ok(install.installed)
+}
+
+/// Newest release tag of each repository-installed mod that has one it isn't on.
+#[tauri::command]
+pub async fn check_repo_updates(
+    state: tauri::State<'_, AppState>,
+) -> Result<HashMap<String, String>, String> {
+    let origins = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        map_error(db.get_repo_origins())?
+    };
+    let client = ForgeClient::new();
+    let mut updates = HashMap::new();
+    for origin in origins {
+        match client.check_update(&origin).await {
+            Ok(Some(tag)) => {
+                updates.insert(origin.mod_name, tag);
+            }
+            Ok(None) => {}
+            Err(e) => log::warn!("Update check for {} failed: {e}", origin.mod_name),
+        }
+    }
+    Ok(updates)
+}
+
 #[tauri::command]
 pub async fn get_installed_mods_from_db(
     state: tauri::State<'_, AppState>,
@@ -1,49 +1,36 @@
-#[cfg(any(target_os = "macos", target_os = "windows"))]
-use std::path::PathBuf;
-
-#[cfg(target_os = "macos")]
-use bmm_lib::errors::AppError;
-use bmm_lib::lovely;
+use bmm_lib::lovely::{self, LovelyRelease, LovelyTarget};
+use bmm_lib::lovely_patches::{self, ConflictReport, PatchReport};
 
 use crate::state::AppState;
 
 /// Check whether Lovely is currently installed/present on this system.
 /// - macOS: checks for `~/Library/Application Support/Balatro/bins/liblovely.dylib` (via config dir)
 /// - Windows: checks that a `version.dll` exists in the Balatro game directory
+/// - Linux: checks `liblovely.so` (native) or `version.dll` (Proton), depending on the launch mode
 #[tauri::command]
 pub async fn is_lovely_installed(_state: tauri::State<'_, AppState>) -> Result<bool, String> {
     #[cfg(target_os = "macos")]
     {
-        let config_dir = dirs::config_dir()
-            .ok_or_else(|| AppError::DirNotFound(PathBuf::from("config directory")).to_string())?;
-        let lovely_path = config_dir
-            .join("Balatro")
-            .join("bins")
+        let lovely_path = bmm_lib::paths::bins_dir()
+            .map_err(|e| e.to_string())?
             .join("liblovely.dylib");
         Ok(lovely_path.exists())
     }
 
-    #[cfg(target_os = "windows")]
+    #[cfg(any(target_os = "windows", target_os = "linux"))]
     {
-        // Prefer database install path if present
+        // Windows and Proton use version.dll in the active install, native Linux liblovely.so
         let db = _state.db.lock().map_err(|e| e.to_string())?;
-        if let Some(path) = db.get_installation_path().map_err(|e| e.to_string())? {
-            let dll = PathBuf::from(path).join("version.dll");
-            return Ok(dll.exists());
-        }
-
-        // Fallback to first detected Balatro path
-        let candidates = bmm_lib::finder::get_balatro_paths();
-        if let Some(p) = candidates.first() {
-            let dll = p.join("version.dll");
-            return Ok(dll.exists());
-        }
-        return Ok(false);
+        let Ok(target) = LovelyTarget::from_db(&db) else {
+            return Ok(false);
+        };
+        let lovely_path = target.lovely_path().map_err(|e| e.to_string())?;
+        Ok(lovely_path.exists())
     }
 
-    #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
     {
-        // Linux or other targets: Lovely injector not managed; do not warn.
+        // Other targets: Lovely injector not managed; do not warn.
         Ok(true)
     }
 }
@@ -52,6 +39,13 @@ pub async fn is_lovely_installed(_state: tauri::State<'_, AppState>) -> Result<b
 pub async fn check_lovely_update(
     state: tauri::State<'_, AppState>,
 ) -> Result<Option<String>, String> {
+    {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        if db.is_lovely_pinned().map_err(|e| e.to_string())? {
+            return Ok(None);
+        }
+    }
+
     // Load latest from GitHub
     let latest = lovely::get_latest_lovely_version()
         .await
@@ -74,19 +68,98 @@ pub async fn check_lovely_update(
 
 #[tauri::command]
 pub async fn update_lovely_to_latest(state: tauri::State<'_, AppState>) -> Result<String, String> {
-    let latest = lovely::get_latest_lovely_version()
+    let (target, pinned) = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        (
+            LovelyTarget::from_db(&db).map_err(|e| e.to_string())?,
+            lovely::pinned_tag(&db).map_err(|e| e.to_string())?,
+        )
+    };
+    let version = lovely::update_lovely(&target, pinned.as_deref())
         .await
         .map_err(|e| e.to_string())?;
 
-    // Remove current install and reinstall
-    lovely::remove_installed_lovely().map_err(|e| e.to_string())?;
-    lovely::ensure_lovely_exists()
+    // Persist version
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_lovely_version(&version).map_err(|e| e.to_string())?;
+
+    Ok(version)
+}
+
+#[tauri::command]
+pub async fn list_lovely_releases() -> Result<Vec<LovelyRelease>, String> {
+    lovely::list_lovely_releases()
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Install a specific Lovely release (up- or downgrade) and record it as the installed version.
+/// `allow_unverified` is only set once the user confirmed installing a release without a checksum.
+#[tauri::command]
+pub async fn install_lovely_version(
+    state: tauri::State<'_, AppState>,
+    tag: String,
+    allow_unverified: Option<bool>,
+) -> Result<String, String> {
+    let tag = lovely::normalize_tag(&tag);
+    let target = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        LovelyTarget::from_db(&db).map_err(|e| e.to_string())?
+    };
+    lovely::install_lovely_version(&target, &tag, allow_unverified.unwrap_or(false))
         .await
         .map_err(|e| e.to_string())?;
 
-    // Persist version
+    let version = tag.trim_start_matches('v').to_string();
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.set_lovely_version(&version).map_err(|e| e.to_string())?;
+    Ok(version)
+}
+
+#[tauri::command]
+pub async fn is_lovely_pinned(state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    db.is_lovely_pinned().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn set_lovely_pinned(
+    state: tauri::State<'_, AppState>,
+    pinned: bool,
+) -> Result<(), String> {
     let db = state.db.lock().map_err(|e| e.to_string())?;
-    db.set_lovely_version(&latest).map_err(|e| e.to_string())?;
+    db.set_lovely_pinned(pinned).map_err(|e| e.to_string())
+}
+
+fn scan_lovely_patches() -> Result<PatchReport, String> {
+    let mods_dir = bmm_lib::paths::mods_dir().map_err(|e| e.to_string())?;
+    lovely_patches::scan_mods(&mods_dir).map_err(|e| e.to_string())
+}
+
+/// Parse the Lovely patch files of every enabled mod.
+#[tauri::command]
+pub async fn inspect_lovely_patches() -> Result<PatchReport, String> {
+    scan_lovely_patches()
+}
+
+/// Find overlapping patches between mods and patches that no longer match the game.
+#[tauri::command]
+pub async fn analyze_lovely_conflicts(
+    state: tauri::State<'_, AppState>,
+) -> Result<ConflictReport, String> {
+    let report = scan_lovely_patches()?;
+
+    let custom_path = {
+        let db = state.db.lock().map_err(|e| e.to_string())?;
+        db.get_installation_path().map_err(|e| e.to_string())?
+    };
+    let balatro = custom_path
+        .and_then(|p| bmm_lib::balamod::Balatro::from_custom_path(p.into()))
+        .or_else(|| bmm_lib::balamod::find_balatros().into_iter().next());
 
-    Ok(latest)
+    // Without a game install only the static checks can run
+    let sources = balatro
+        .map(|b| lovely_patches::load_game_sources(&b, &report))
+        .unwrap_or_default();
+    Ok(lovely_patches::find_conflicts(&report, &sources))
 }
@@ -1,12 +1,15 @@
+pub mod bisect;
 pub mod cache;
 pub mod detection;
 pub mod import;
 pub mod install;
 pub mod lovely;
+pub mod mod_config;
 pub mod mods;
 pub mod paths;
 pub mod repo;
 pub mod report;
+pub mod saves;
 pub mod settings;
 pub mod system;
 pub mod thumbnails;
@@ -0,0 +1,53 @@
+use crate::util::map_error;
+use bmm_lib::config_preserve::{self, ConfigStore, PendingMerge};
+use bmm_lib::paths;
+use bmm_lib::smods_config::{self, LuaValue, SmodsConfigFile};
+use std::path::PathBuf;
+
+#[tauri::command]
+pub async fn list_pending_config_merges() -> Result<Vec<PendingMerge>, String> {
+    map_error(ConfigStore::new().and_then(|store| store.pending()))
+}
+
+#[tauri::command]
+pub async fn resolve_config_merge(
+    mod_name: String,
+    path: String,
+    content: String,
+) -> Result<(), String> {
+    let mod_dir = map_error(
+        paths::mods_dir().and_then(|dir| config_preserve::installed_mod_dir(&dir, &mod_name)),
+    )?;
+    map_error(
+        ConfigStore::new().and_then(|store| store.resolve(&mod_name, &mod_dir, &path, &content)),
+    )
+}
+
+#[tauri::command]
+pub async fn list_smods_configs() -> Result<Vec<SmodsConfigFile>, String> {
+    let config_dir = map_error(smods_config::config_dir())?;
+    map_error(smods_config::list_configs(&config_dir))
+}
+
+#[tauri::command]
+pub async fn read_smods_config(
+    mod_id: String,
+    mod_path: Option<String>,
+) -> Result<LuaValue, String> {
+    let mod_dir = mod_path.map(PathBuf::from);
+    map_error(smods_config::read_config(
+        &map_error(smods_config::config_dir())?,
+        &mod_id,
+        mod_dir.as_deref(),
+    ))
+}
+
+#[tauri::command]
+pub async fn write_smods_config(mod_id: String, config: LuaValue) -> Result<String, String> {
+    map_error(smods_config::write_config(
+        &map_error(smods_config::config_dir())?,
+        &mod_id,
+        &config,
+    ))
+    .map(|p| p.to_string_lossy().to_string())
+}
@@ -3,7 +3,10 @@ use std::path::PathBuf;
 use crate::state::AppState;
 use crate::util::map_error;
 use bmm_lib::balamod::find_balatros;
+use bmm_lib::database::{InstallPlatform, Installation};
 use bmm_lib::errors::AppError;
+use bmm_lib::paths;
+use bmm_lib::stores::LaunchMethod;
 
 #[tauri::command]
 pub async fn open_directory(path: String) -> Result<(), String> {
@@ -15,13 +18,19 @@ pub async fn open_directory(path: String) -> Result<(), String> {
 
 #[tauri::command]
 pub async fn get_mods_folder() -> Result<String, String> {
-    let config_dir = dirs::config_dir()
-        .ok_or_else(|| AppError::DirNotFound(PathBuf::from("config directory")).to_string())?;
-    Ok(config_dir
-        .join("Balatro")
-        .join("Mods")
-        .to_string_lossy()
-        .into_owned())
+    Ok(map_error(paths::mods_dir())?.to_string_lossy().into_owned())
+}
+
+/// Point the manager at a custom Mods directory; `None` goes back to the one
+/// derived from the active install.
+#[tauri::command]
+pub async fn set_mods_folder(
+    state: tauri::State<'_, AppState>,
+    path: Option<String>,
+) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(db.set_mods_dir(path.as_deref().filter(|p| !p.is_empty())))?;
+    map_error(paths::refresh(&db))
 }
 
 #[tauri::command]
@@ -39,7 +48,8 @@ pub async fn set_balatro_path(
         Ok(db) => db,
         Err(e) => return Err(e.to_string()),
     };
-    map_error(db.set_installation_path(&path))
+    map_error(db.set_installation_path(&path))?;
+    map_error(paths::refresh(&db))
 }
 
 #[tauri::command]
@@ -47,7 +57,23 @@ pub async fn find_steam_balatro(state: tauri::State<'_, AppState>) -> Result<Vec
     let balatros = find_balatros();
     if let Some(path) = balatros.first() {
         let db = state.db.lock().map_err(|e| e.to_string())?;
+        // Register every copy found so the user can switch between them later
+        for other in balatros.iter().skip(1) {
+            let other = other.path.to_string_lossy();
+            if !map_error(db.get_installations())?
+                .iter()
+                .any(|i| i.path == other)
+            {
+                map_error(db.add_installation(
+                    "Steam",
+                    &other,
+                    InstallPlatform::Steam,
+                    Some(&LaunchMethod::Steam),
+                ))?;
+            }
+        }
         map_error(db.set_installation_path(&path.path.to_string_lossy()))?;
+        map_error(paths::refresh(&db))?;
     }
 
     Ok(balatros
@@ -93,6 +119,7 @@ pub async fn check_custom_balatro(
     if is_valid {
         let db = state.db.lock().map_err(|e| e.to_string())?;
         map_error(db.set_installation_path(&path_to_check.to_string_lossy()))?;
+        map_error(paths::refresh(&db))?;
     }
 
     Ok(is_valid)
@@ -106,15 +133,90 @@ pub async fn check_existing_installation(
         .db
         .lock()
         .map_err(|_| AppError::LockPoisoned("Database lock poisoned".to_string()))?;
-    if let Some(path) = db.get_installation_path()? {
-        let path_buf = PathBuf::from(&path);
-        if bmm_lib::balamod::Balatro::from_custom_path(path_buf).is_some() {
-            Ok(Some(path))
-        } else {
-            map_error(db.remove_installation_path())?;
-            Ok(None)
+    let is_valid =
+        |path: &str| bmm_lib::balamod::Balatro::from_custom_path(PathBuf::from(path)).is_some();
+    let Some(path) = db.get_installation_path()? else {
+        return Ok(None);
+    };
+    if is_valid(&path) {
+        return Ok(Some(path));
+    }
+
+    // The install may only be unavailable for now (an unmounted drive), so it stays
+    // registered; another registered install that is there takes over meanwhile
+    log::warn!("Active Balatro installation at {path} is not available");
+    let fallback = map_error(db.get_installations())?
+        .into_iter()
+        .find(|install| !install.active && is_valid(&install.path));
+    match fallback {
+        Some(install) => {
+            map_error(db.set_active_installation(install.id))?;
+            map_error(paths::refresh(&db))?;
+            Ok(Some(install.path))
         }
-    } else {
-        Ok(None)
+        None => Ok(None),
+    }
+}
+
+/// Balatro installs found across all Steam libraries, with build id and Proton/native build.
+#[tauri::command]
+pub async fn list_steam_installs() -> Result<Vec<bmm_lib::steam::SteamInstall>, String> {
+    Ok(bmm_lib::steam::find_steam_installs())
+}
+
+#[tauri::command]
+pub async fn list_installations(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<Installation>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(db.get_installations())
+}
+
+/// Register a Balatro install (or relabel an already registered path). `launch` comes
+/// from `detect_store_installs` when the install was picked from there.
+#[tauri::command]
+pub async fn add_installation(
+    state: tauri::State<'_, AppState>,
+    label: String,
+    path: String,
+    platform: InstallPlatform,
+    launch: Option<LaunchMethod>,
+) -> Result<i64, String> {
+    let path_buf = PathBuf::from(&path);
+    if bmm_lib::balamod::Balatro::from_custom_path(path_buf.clone()).is_none() {
+        return Err(AppError::PathValidation {
+            path: path_buf,
+            reason: "Not a Balatro installation".to_string(),
+        }
+        .to_string());
     }
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    let id = map_error(db.add_installation(&label, &path, platform, launch.as_ref()))?;
+    map_error(paths::refresh(&db))?;
+    Ok(id)
+}
+
+/// Make an install the one every command resolves paths through.
+#[tauri::command]
+pub async fn set_active_installation(
+    state: tauri::State<'_, AppState>,
+    id: i64,
+) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(db.set_active_installation(id))?;
+    map_error(paths::refresh(&db))
+}
+
+#[tauri::command]
+pub async fn remove_installation(state: tauri::State<'_, AppState>, id: i64) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(db.remove_installation(id))?;
+    map_error(paths::refresh(&db))
+}
+
+/// Balatro installs from every supported store (Steam, Heroic, Lutris, GOG Galaxy,
+/// Game Pass, itch), each with the launch method it needs.
+#[tauri::command]
+pub async fn detect_store_installs() -> Result<Vec<bmm_lib::stores::DetectedInstall>, String> {
+    Ok(bmm_lib::stores::detect_all())
 }
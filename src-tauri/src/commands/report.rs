@@ -1,4 +1,6 @@
 use base64::{engine::general_purpose::STANDARD, Engine as _};
+use bmm_lib::game_log::{self, GameCrash};
+use bmm_lib::paths;
 use serde::Serialize;
 use std::fs;
 use sysinfo::System;
@@ -17,6 +19,8 @@ struct ReportPayload<'a> {
     ram: String,
     log_b64: String,
     log_filename: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    game_crash: Option<GameCrash>,
 }
 
 /// Submit an issue report to the Jimbo bot server.
@@ -25,6 +29,7 @@ pub async fn submit_report(
     title: String,
     description: String,
     mm_version: String,
+    include_game_crash: Option<bool>,
 ) -> Result<(), String> {
     // Collect system info
     let (cpu, ram_str) = get_cpu_and_ram();
@@ -43,6 +48,16 @@ pub async fn submit_report(
         latest_log().unwrap_or_else(|| ("bmm.log".to_string(), String::from("No logs found.")));
     let log_b64 = STANDARD.encode(log_text.as_bytes());
 
+    // Attach the last game crash only when the user opted in
+    let game_crash = if include_game_crash.unwrap_or(false) {
+        latest_game_crash().unwrap_or_else(|e| {
+            log::warn!("Failed to read game crash for report: {e}");
+            None
+        })
+    } else {
+        None
+    };
+
     let payload = ReportPayload {
         title: &title,
         description: &description,
@@ -54,6 +69,7 @@ pub async fn submit_report(
         ram: ram_str,
         log_b64,
         log_filename,
+        game_crash,
     };
 
     // Send
@@ -87,8 +103,31 @@ pub async fn get_latest_log() -> Result<(String, String), String> {
     latest_log().ok_or_else(|| "No logs found.".to_string())
 }
 
+/// Returns the crash from the most recent Lovely game log, if the last run crashed.
+#[tauri::command]
+pub async fn get_latest_game_crash() -> Result<Option<GameCrash>, String> {
+    latest_game_crash()
+}
+
+/// Returns the most recent Lovely game log's filename and text, if any.
+#[tauri::command]
+pub async fn get_latest_game_log() -> Result<Option<(String, String)>, String> {
+    let log = game_log::read_latest_log(&paths::mods_dir()?).map_err(|e| e.to_string())?;
+    Ok(log.map(|(path, text)| {
+        let filename = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        (filename, text)
+    }))
+}
+
+fn latest_game_crash() -> Result<Option<GameCrash>, String> {
+    game_log::latest_crash(&paths::mods_dir()?).map_err(|e| e.to_string())
+}
+
 fn latest_log() -> Option<(String, String)> {
-    let dir = dirs::config_dir()?.join("Balatro").join("logs");
+    let dir = paths::logs_dir().ok()?;
     let entries = fs::read_dir(&dir).ok()?;
     let mut files: Vec<_> = entries
         .filter_map(|e| e.ok())
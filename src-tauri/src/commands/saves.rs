@@ -0,0 +1,75 @@
+use crate::state::AppState;
+use crate::util::map_error;
+use bmm_lib::errors::AppError;
+use bmm_lib::save_backup::{SaveBackupManager, SaveBackupReason, SaveFileChange, SaveSnapshot};
+
+fn manager(state: &tauri::State<'_, AppState>) -> Result<SaveBackupManager, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|_| AppError::LockPoisoned("Database lock poisoned".to_string()))?;
+    map_error(SaveBackupManager::from_db(&db))
+}
+
+/// Best-effort snapshot used before installs; never blocks the caller. The database
+/// lock is released before the saves are archived.
+pub fn snapshot_saves_quietly(state: &tauri::State<'_, AppState>, reason: SaveBackupReason) {
+    match manager(state) {
+        Ok(manager) => manager.snapshot_quietly(reason),
+        Err(e) => log::warn!("Failed to snapshot save files ({reason:?}): {e}"),
+    }
+}
+
+#[tauri::command]
+pub async fn list_save_backups(
+    state: tauri::State<'_, AppState>,
+) -> Result<Vec<SaveSnapshot>, String> {
+    map_error(manager(&state)?.list())
+}
+
+#[tauri::command]
+pub async fn create_save_backup(
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<SaveSnapshot>, String> {
+    map_error(manager(&state)?.snapshot(SaveBackupReason::Manual))
+}
+
+#[tauri::command]
+pub async fn diff_save_backup(
+    state: tauri::State<'_, AppState>,
+    id: String,
+    other: Option<String>,
+) -> Result<Vec<SaveFileChange>, String> {
+    map_error(manager(&state)?.diff(&id, other.as_deref()))
+}
+
+#[tauri::command]
+pub async fn restore_save_backup(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    map_error(manager(&state)?.restore(&id))
+}
+
+#[tauri::command]
+pub async fn delete_save_backup(
+    state: tauri::State<'_, AppState>,
+    id: String,
+) -> Result<(), String> {
+    map_error(manager(&state)?.delete(&id))
+}
+
+#[tauri::command]
+pub async fn get_save_backup_retention(state: tauri::State<'_, AppState>) -> Result<usize, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(db.get_save_backup_retention())
+}
+
+#[tauri::command]
+pub async fn set_save_backup_retention(
+    state: tauri::State<'_, AppState>,
+    retention: usize,
+) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(db.set_save_backup_retention(retention.max(1)))
+}
@@ -1,6 +1,7 @@
 use crate::state::AppState;
 use crate::util::map_error;
 use bmm_lib::errors::AppError;
+use bmm_lib::lovely::LinuxLaunchMode;
 
 #[tauri::command]
 pub async fn get_lovely_console_status(state: tauri::State<'_, AppState>) -> Result<bool, String> {
@@ -82,3 +83,23 @@ pub async fn set_security_warning_acknowledged(
     let db = state.db.lock().map_err(|e| e.to_string())?;
     map_error(db.set_security_warning_acknowledged(acknowledged))
 }
+
+#[tauri::command]
+pub async fn get_linux_launch_mode(
+    state: tauri::State<'_, AppState>,
+) -> Result<Option<LinuxLaunchMode>, String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(db.get_linux_launch_mode())
+}
+
+/// `None` switches back to detecting the mode from the installation.
+#[tauri::command]
+pub async fn set_linux_launch_mode(
+    state: tauri::State<'_, AppState>,
+    mode: Option<LinuxLaunchMode>,
+) -> Result<(), String> {
+    let db = state.db.lock().map_err(|e| e.to_string())?;
+    map_error(db.set_linux_launch_mode(mode))?;
+    // Proton and native builds read mods from different places
+    map_error(bmm_lib::paths::refresh(&db))
+}
@@ -11,7 +11,8 @@ use tauri::{Emitter, Manager};
 use tauri_plugin_window_state::StateFlags;
 
 use bmm_lib::{
-    database::Database, discord_rpc::DiscordRpcManager, errors::AppError, local_mod_detection,
+    backup::BackupStore, database::Database, discord_rpc::DiscordRpcManager, errors::AppError,
+    local_mod_detection,
 };
 
 use crate::models::Payload;
@@ -45,6 +46,9 @@ pub fn run() {
             let discord_rpc = DiscordRpcManager::new();
             let discord_rpc_enabled = db.is_discord_rpc_enabled().unwrap_or(true);
             discord_rpc.set_enabled(discord_rpc_enabled);
+            if let Err(e) = bmm_lib::paths::refresh(&db) {
+                log::warn!("Failed to resolve game paths from settings: {e}");
+            }
             app.manage(AppState {
                 db: Mutex::new(db),
                 discord_rpc: Mutex::new(discord_rpc),
@@ -52,8 +56,8 @@ pub fn run() {
             });
 
             // Remove legacy GitHub-based local clone directory if it exists.
-            if let Some(cfg_dir) = dirs::config_dir() {
-                let legacy_repo = cfg_dir.join("Balatro").join("mod_index");
+            if let Ok(manager_dir) = bmm_lib::paths::manager_dir() {
+                let legacy_repo = manager_dir.join("mod_index");
                 if legacy_repo.exists() {
                     match std::fs::remove_dir_all(&legacy_repo) {
                         Ok(()) => log::info!(
@@ -69,6 +73,21 @@ pub fn run() {
                 }
             }
 
+            // Backups used to be plain copies in the temp dir; move any left into the store
+            let legacy_backups = bmm_lib::backup::legacy_backup_dir();
+            if legacy_backups.is_dir() {
+                tauri::async_runtime::spawn_blocking(move || {
+                    let imported = Database::new()
+                        .and_then(|db| BackupStore::new()?.import_legacy(&db, &legacy_backups));
+                    if let Err(e) = imported {
+                        log::warn!("Failed to import legacy mod backups: {e}");
+                    }
+                });
+            }
+
+            // Mods disabled for a safe-mode launch stay disabled if we exited mid-game
+            commands::install::restore_safe_mode();
+
             tauri::async_runtime::spawn(async move {
                 let db = match Database::new() {
                     Ok(db) => db,
@@ -114,8 +133,7 @@ pub fn run() {
 
                 // Lightweight fingerprint of Mods directory to detect additions/removals
                 fn mods_dir_fingerprint() -> Option<u64> {
-                    let config_dir = dirs::config_dir()?;
-                    let mods_dir = config_dir.join("Balatro").join("Mods");
+                    let mods_dir = bmm_lib::paths::mods_dir().ok()?;
                     if !mods_dir.exists() {
                         return Some(0);
                     }
@@ -234,18 +252,34 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             commands::paths::find_steam_balatro,
+            commands::paths::list_steam_installs,
+            commands::paths::detect_store_installs,
+            commands::paths::list_installations,
+            commands::paths::add_installation,
+            commands::paths::set_active_installation,
+            commands::paths::remove_installation,
             commands::paths::check_custom_balatro,
             commands::paths::check_existing_installation,
             commands::paths::get_balatro_path,
             commands::paths::set_balatro_path,
             commands::paths::get_mods_folder,
+            commands::paths::set_mods_folder,
             commands::paths::open_directory,
             commands::install::launch_balatro,
+            commands::install::get_launch_sessions,
+            commands::bisect::start_crash_bisect,
+            commands::bisect::get_crash_bisect_status,
+            commands::bisect::record_crash_bisect_result,
+            commands::bisect::abort_crash_bisect,
             commands::system::check_steam_running,
             commands::system::check_balatro_running,
             commands::system::get_app_version,
             commands::install::get_installed_mods_from_db,
             commands::install::install_mod,
+            commands::install::install_thunderstore_package,
+            commands::install::list_repo_versions,
+            commands::install::install_from_repo,
+            commands::install::check_repo_updates,
             commands::install::add_installed_mod,
             commands::install::remove_installed_mod,
             commands::install::get_steamodded_versions,
@@ -264,9 +298,17 @@ pub fn run() {
             commands::cache::load_versions_cache,
             commands::settings::get_lovely_console_status,
             commands::settings::set_lovely_console_status,
+            commands::settings::get_linux_launch_mode,
+            commands::settings::set_linux_launch_mode,
             commands::lovely::check_lovely_update,
             commands::lovely::update_lovely_to_latest,
             commands::lovely::is_lovely_installed,
+            commands::lovely::list_lovely_releases,
+            commands::lovely::install_lovely_version,
+            commands::lovely::is_lovely_pinned,
+            commands::lovely::set_lovely_pinned,
+            commands::lovely::inspect_lovely_patches,
+            commands::lovely::analyze_lovely_conflicts,
             commands::settings::get_background_state,
             commands::settings::set_background_state,
             commands::settings::get_discord_rpc_status,
@@ -281,6 +323,10 @@ pub fn run() {
             commands::repo::fetch_gitlab_mods_archive,
             commands::repo::fetch_gitlab_mods,
             commands::repo::fetch_gitlab_mods_meta_only,
+            commands::repo::list_mod_sources,
+            commands::repo::add_mod_source,
+            commands::repo::set_mod_source_enabled,
+            commands::repo::remove_mod_source,
             commands::repo::get_cached_installed_thumbnail,
             commands::repo::get_cached_thumbnail_by_title,
             commands::repo::cache_thumbnail_from_url,
@@ -291,6 +337,8 @@ pub fn run() {
             commands::thumbnails::enqueue_thumbnail,
             commands::report::submit_report,
             commands::report::get_latest_log,
+            commands::report::get_latest_game_log,
+            commands::report::get_latest_game_crash,
             commands::mods::is_mod_enabled,
             commands::mods::toggle_mod_enabled,
             commands::mods::is_mod_enabled_by_path,
@@ -307,6 +355,24 @@ pub fn run() {
             commands::detection::backup_local_mod,
             commands::detection::restore_from_backup,
             commands::detection::remove_backup,
+            commands::detection::list_mod_backups,
+            commands::detection::restore_mod_backup,
+            commands::detection::delete_mod_backup,
+            commands::detection::cleanup_mod_backups,
+            commands::detection::set_backup_limits,
+            commands::detection::get_backup_usage,
+            commands::mod_config::list_pending_config_merges,
+            commands::mod_config::resolve_config_merge,
+            commands::mod_config::list_smods_configs,
+            commands::mod_config::read_smods_config,
+            commands::mod_config::write_smods_config,
+            commands::saves::list_save_backups,
+            commands::saves::create_save_backup,
+            commands::saves::diff_save_backup,
+            commands::saves::restore_save_backup,
+            commands::saves::delete_save_backup,
+            commands::saves::get_save_backup_retention,
+            commands::saves::set_save_backup_retention,
             exit_application
         ])
         .run(tauri::generate_context!());
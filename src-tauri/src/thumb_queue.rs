@@ -225,8 +225,9 @@ fn safe_slug(input: &str) -> String {
 }
 
 fn ensure_assets_dirs() -> Result<(std::path::PathBuf, std::path::PathBuf), String> {
-    let config_dir = dirs::config_dir().ok_or_else(|| "config dir not found".to_string())?;
-    let base = config_dir.join("Balatro").join("mod_assets");
+    let base = bmm_lib::paths::manager_dir()
+        .map_err(|e| e.to_string())?
+        .join("mod_assets");
     let thumbs = base.join("thumbnails");
     let descs = base.join("descriptions");
     std::fs::create_dir_all(&thumbs).map_err(|e| e.to_string())?;